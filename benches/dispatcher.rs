@@ -1,5 +1,6 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use lieutenant::{command, CommandDispatcher, Context};
+use lieutenant::parsers::{FromStrChecker, StringArg};
+use lieutenant::{command, ArgumentChecker, CommandDispatcher, Context};
 use thiserror::Error;
 
 #[derive(Debug, Error, PartialEq)]
@@ -8,8 +9,7 @@ enum Error {
     ParsingInt,
 }
 
-impl From<std::num::ParseIntError> for Error
-{
+impl From<std::num::ParseIntError> for Error {
     fn from(_: std::num::ParseIntError) -> Self {
         Error::ParsingInt
     }
@@ -194,12 +194,239 @@ fn multiple_commands(c: &mut Criterion) {
     });
 }
 
+fn literal_chains(c: &mut Criterion) {
+    struct State;
+    impl Context for State {
+        type Error = Error;
+        type Ok = ();
+    }
+    #[command(usage = "world border set center here")]
+    async fn set_center(_state: &mut State) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "world border set size max")]
+    async fn set_size(_state: &mut State) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "world border get center here")]
+    async fn get_center(_state: &mut State) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "world border get size max")]
+    async fn get_size(_state: &mut State) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "world spawn set here now")]
+    async fn set_spawn(_state: &mut State) -> Result<(), Error> {
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default()
+        .with(set_center)
+        .with(set_size)
+        .with(get_center)
+        .with(get_size)
+        .with(set_spawn);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+
+    c.bench_function("dispatch deep literal chains", |b| {
+        b.iter(|| {
+            assert!(smol::block_on(dispatcher.dispatch(
+                &mut nodes,
+                &mut errors,
+                &mut State,
+                black_box("world border get size max")
+            ))
+            .is_ok());
+            assert!(smol::block_on(dispatcher.dispatch(
+                &mut nodes,
+                &mut errors,
+                &mut State,
+                black_box("world spawn set here now")
+            ))
+            .is_ok());
+            assert!(smol::block_on(dispatcher.dispatch(
+                &mut nodes,
+                &mut errors,
+                &mut State,
+                black_box("world border get size min")
+            ))
+            .is_err());
+        })
+    });
+}
+
+fn pure_literals(c: &mut Criterion) {
+    struct State;
+    impl Context for State {
+        type Error = Error;
+        type Ok = ();
+    }
+    #[command(usage = "server <name> <port>")]
+    async fn server_port(_state: &mut State, _name: String, _port: u16) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "server <name> <motd>")]
+    async fn server_motd(_state: &mut State, _name: String, _motd: String) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "server reload all")]
+    async fn reload(_state: &mut State) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "stop")]
+    async fn stop(_state: &mut State) -> Result<(), Error> {
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default()
+        .with(server_port)
+        .with(server_motd)
+        .with(reload)
+        .with(stop);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+
+    c.bench_function("dispatch pure literal commands", |b| {
+        b.iter(|| {
+            assert!(smol::block_on(dispatcher.dispatch(
+                &mut nodes,
+                &mut errors,
+                &mut State,
+                black_box("server reload all")
+            ))
+            .is_ok());
+            assert!(smol::block_on(dispatcher.dispatch(
+                &mut nodes,
+                &mut errors,
+                &mut State,
+                black_box("stop")
+            ))
+            .is_ok());
+        })
+    });
+}
+
+fn sibling_candidates(c: &mut Criterion) {
+    struct State;
+    impl Context for State {
+        type Error = Error;
+        type Ok = ();
+    }
+    #[command(usage = "set <value> bytes")]
+    async fn set_bytes(_state: &mut State, _value: u8) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "set <value> ticks")]
+    async fn set_ticks(_state: &mut State, _value: u32) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "set <value> blocks")]
+    async fn set_blocks(_state: &mut State, _value: i64) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "set <value> name")]
+    async fn set_name(_state: &mut State, _value: String) -> Result<(), Error> {
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default()
+        .with(set_bytes)
+        .with(set_ticks)
+        .with(set_blocks)
+        .with(set_name);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+
+    // Every `<value>` accepts `7`, so each sibling is tried in turn until
+    // the one followed by `name` matches.
+    c.bench_function("dispatch through sibling candidates", |b| {
+        b.iter(|| {
+            assert!(smol::block_on(dispatcher.dispatch(
+                &mut nodes,
+                &mut errors,
+                &mut State,
+                black_box("set 7 name")
+            ))
+            .is_ok());
+        })
+    });
+}
+
+fn string_arguments(c: &mut Criterion) {
+    let mut group = c.benchmark_group("string arguments");
+    group.bench_function("borrowed", |b| {
+        b.iter(|| {
+            let mut input = black_box("hello world");
+            StringArg::borrowed(&mut input)
+        })
+    });
+    group.bench_function("borrowed quoted", |b| {
+        b.iter(|| {
+            let mut input = black_box(r#""hello world" again"#);
+            StringArg::borrowed(&mut input)
+        })
+    });
+    group.bench_function("escaped", |b| {
+        b.iter(|| {
+            let mut input = black_box(r#""hello \"world\"" again"#);
+            StringArg::borrowed(&mut input)
+        })
+    });
+    group.finish();
+}
+
+fn integer_arguments(c: &mut Criterion) {
+    struct State;
+    impl Context for State {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    let checker = <FromStrChecker<i64> as ArgumentChecker<State>>::default();
+    let mut group = c.benchmark_group("integer arguments");
+    group.bench_function("str::parse", |b| {
+        b.iter(|| black_box("-9223372036854775808").parse::<i64>())
+    });
+    group.bench_function("checker", |b| {
+        b.iter(|| {
+            let mut input = black_box("-9223372036854775808");
+            smol::block_on(checker.satisfies(&State, &mut input))
+        })
+    });
+    group.finish();
+}
+
 criterion_group!(single_command_bench, single_command);
 criterion_group!(single_command_parallel_bench, single_command_prallel);
 criterion_group!(multiple_commands_bench, multiple_commands);
+criterion_group!(literal_chains_bench, literal_chains);
+criterion_group!(pure_literals_bench, pure_literals);
+criterion_group!(sibling_candidates_bench, sibling_candidates);
+criterion_group!(string_arguments_bench, string_arguments);
+criterion_group!(integer_arguments_bench, integer_arguments);
 
 criterion_main!(
     single_command_bench,
     single_command_parallel_bench,
-    multiple_commands_bench
+    multiple_commands_bench,
+    literal_chains_bench,
+    pure_literals_bench,
+    sibling_candidates_bench,
+    string_arguments_bench,
+    integer_arguments_bench
 );
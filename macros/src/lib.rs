@@ -3,8 +3,12 @@ use darling::FromMeta;
 use proc_macro2::{Ident, Span, TokenStream};
 use proc_macro_error::*;
 use quote::quote;
+use syn::parse::{Parse, ParseStream};
 use syn::spanned::Spanned;
-use syn::{parse_macro_input, AttributeArgs, Block, FnArg, ItemFn, Pat, PatType, Type, ReturnType, TypePath};
+use syn::{
+    parse_macro_input, AttributeArgs, Block, Data, DeriveInput, Expr, ExprClosure, Fields, FnArg,
+    ItemFn, LitStr, Pat, PatType, ReturnType, Token, Type, TypePath,
+};
 
 #[derive(Debug, FromMeta)]
 struct Args {
@@ -13,6 +17,12 @@ struct Args {
     description: Option<String>,
     #[darling(default)]
     priority: usize,
+    #[darling(multiple, rename = "permission")]
+    permissions: Vec<String>,
+    #[darling(default)]
+    deprecated: Option<String>,
+    #[darling(default)]
+    named: bool,
 }
 
 #[derive(Debug)]
@@ -22,9 +32,18 @@ struct Usage {
 
 #[derive(Debug)]
 enum Argument {
-    Parameter { name: String, priority: usize },
-    OptionalParameter { name: String, priority: usize },
-    Literal { value: String },
+    Parameter {
+        name: String,
+        priority: usize,
+    },
+    OptionalParameter {
+        name: String,
+        priority: usize,
+        default: Option<String>,
+    },
+    Literal {
+        value: String,
+    },
 }
 
 #[proc_macro_error]
@@ -40,7 +59,7 @@ pub fn provider(
         abort_call_site!("expected #[provider] with no paramters");
     }
 
-    if let None = input.sig.asyncness {
+    if input.sig.asyncness.is_none() {
         abort_call_site!("command must be an async fn");
     }
 
@@ -69,28 +88,18 @@ pub fn provider(
 
     let params = input.sig.inputs;
 
-    let block = input.block;
-
-    let ctx_type = quote! {};
-    let t = quote! {};
-
     (quote! {
         #impl_header {
             fn provide<'a>(#params) -> Pin<Box<dyn Future<Output = Result<Self, C::Error>> + Send + Sync + 'a>> {
-                
+
             }
         }
     }).into()
 }
 
-fn parse_result(output: ReturnType) -> Option<(TypePath, TypePath)> {
-    return None;
-    // let ReturnType::Type(_, kind) = output;
-    // let Type::Path(path) = *kind;
-    // let 
-
-    abort_call_site!("{:#?}", output);
-    unimplemented!()
+fn parse_result(_output: ReturnType) -> Option<(TypePath, TypePath)> {
+    // TODO: extract the `Ok` and `Error` types from a `Result` return type.
+    None
 }
 
 #[proc_macro_error]
@@ -120,11 +129,12 @@ pub fn command(
         );
     }
 
-    if let None = input.sig.asyncness {
+    if input.sig.asyncness.is_none() {
         abort_call_site!("command must be an async fn");
-    } 
+    }
 
-    let usage = parse_usage(&args.usage);
+    let usage = parse_usage(&args.usage, args.priority);
+    validate_usage(&usage);
     let parameters = collect_parameters(&usage, &input.sig.inputs.iter());
 
     let ctx_type = detect_context_type(&parameters, input.sig.inputs.iter().next());
@@ -147,13 +157,28 @@ pub fn command(
         quote! { C }
     };
 
-    let command_spec = generate_command_spec(
-        &usage,
-        args.description,
-        &parameters,
-        ctx_type,
-        &input.block,
-    );
+    // A `Trailing` parameter receives the input left over after parsing,
+    // which is only ever non-empty when dispatching leniently.
+    let mut block = input.block.as_ref().clone();
+    for arg in input.sig.inputs.iter().skip(1) {
+        if let FnArg::Typed(pat_type) = arg {
+            if is_trailing(&pat_type.ty) && !parameters.contains(&pat_type) {
+                let PatType { pat, ty, .. } = pat_type;
+                block = syn::parse_quote!({
+                    let #pat: #ty = lieutenant::Trailing(args.to_owned());
+                    #block
+                });
+            }
+        }
+    }
+
+    let metadata = Metadata {
+        description: args.description,
+        permissions: args.permissions,
+        deprecated: args.deprecated,
+        named: args.named,
+    };
+    let command_spec = generate_command_spec(&usage, metadata, &parameters, ctx_type, &block);
     let visibility = &input.vis;
 
     let tokens = quote! {
@@ -169,21 +194,432 @@ pub fn command(
     tokens.into()
 }
 
-fn parse_usage(usage: &str) -> Usage {
+struct CommandSpecInput {
+    usage: LitStr,
+    closure: ExprClosure,
+}
+
+impl Parse for CommandSpecInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let usage = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let closure = input.parse()?;
+        input.parse::<Option<Token![,]>>()?;
+        Ok(Self { usage, closure })
+    }
+}
+
+/// Builds a `CommandSpec` from a usage message declaring the type of each
+/// parameter, and a closure taking the context followed by the parameters:
+///
+/// ```ignore
+/// command_spec!("tp <x:i32> <y:i32> <z:i32>", |ctx: &mut State, x, y, z| {
+///     ctx.position = (x, y, z);
+///     Ok(())
+/// })
+/// ```
+///
+/// Alternatively, the closure may take the parameters collected into a
+/// single value, such as a struct deriving `FromExtract`:
+///
+/// ```ignore
+/// command_spec!("tp <x:i32> <y:i32> <z:i32>", |ctx: &mut State, args: TpArgs| {
+///     ctx.position = (args.x, args.y, args.z);
+///     Ok(())
+/// })
+/// ```
+///
+/// The closure body is run inside an `async` block and may `.await`, but it
+/// may not capture its environment.
+#[proc_macro_error]
+#[proc_macro]
+pub fn command_spec(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let CommandSpecInput { usage, closure } = parse_macro_input!(input as CommandSpecInput);
+
+    let parsed = parse_usage(&usage.value(), 0);
+    validate_usage(&parsed);
+
+    let mut inputs = closure.inputs.iter();
+    let ctx = match inputs.next() {
+        Some(ctx) => ctx,
+        None => {
+            abort!(closure.inputs.span(), "command closure must take the context as its first parameter";
+
+                help = "add a context parameter: `|ctx: &mut Context, ...|`";
+            )
+        }
+    };
+    let (ctx_pat, ctx_type) = match ctx {
+        Pat::Type(pat_type) => match pat_type.ty.as_ref() {
+            Type::Reference(reference) => (pat_type.pat.as_ref(), reference.elem.as_ref()),
+            ty => abort!(ty.span(), "context input must be a reference";
+
+                help = "change the type of the first closure parameter to be a mutable reference";
+            ),
+        },
+        pat => abort!(pat.span(), "the context parameter must have a type annotation";
+
+            help = "annotate the context type: `|ctx: &mut Context, ...|`";
+        ),
+    };
+
+    let declared = parsed
+        .arguments
+        .iter()
+        .filter(|argument| !matches!(argument, Argument::Literal { .. }))
+        .count();
+    let mut patterns: Vec<Pat> = inputs.cloned().collect();
+
+    // A single annotated parameter collects all of the declared parameters
+    // into a type implementing `FromExtract`.
+    let collect = match patterns.as_slice() {
+        [Pat::Type(pat_type)] => Some(pat_type.clone()),
+        _ => None,
+    };
+    if collect.is_some() {
+        patterns = (0..declared)
+            .map(|i| {
+                let ident = Ident::new(&format!("__extract_{}", i), Span::call_site());
+                syn::parse_quote!(#ident)
+            })
+            .collect();
+    }
+
+    if patterns.len() != declared {
+        abort!(
+            closure.inputs.span(),
+            "command closure takes {} parameter(s) after the context, but the usage message declares {}",
+            patterns.len(),
+            declared;
+
+            help = "take one closure parameter for each `<name:Type>` or `[name:Type]` in the usage message, or a single parameter annotated with a type implementing `FromExtract`";
+        );
+    }
+
+    let mut parameters = vec![];
+    for (argument, pat) in parsed
+        .arguments
+        .iter()
+        .filter(|argument| !matches!(argument, Argument::Literal { .. }))
+        .zip(&patterns)
+    {
+        if let Pat::Type(pat_type) = pat {
+            emit_error!(pat_type.ty.span(), "command closure parameters may not have type annotations";
+
+                help = "declare the type in the usage message instead";
+            );
+        }
+
+        let (name, ty) = match argument {
+            Argument::Parameter { name, .. } | Argument::OptionalParameter { name, .. } => {
+                split_typed_parameter(name, &usage)
+            }
+            Argument::Literal { .. } => unreachable!(),
+        };
+        let parameter = PatType {
+            attrs: vec![],
+            pat: Box::new(pat.clone()),
+            colon_token: Default::default(),
+            ty: Box::new(ty),
+        };
+        validate_parameter(&name, argument, &parameter);
+        parameters.push(parameter);
+    }
+
+    // Strip the types from the parameter names, so that they read like
+    // those of `#[command]` in usage and command paths.
+    let parsed = Usage {
+        arguments: parsed
+            .arguments
+            .into_iter()
+            .map(|argument| match argument {
+                Argument::Parameter { name, priority } => Argument::Parameter {
+                    name: split_typed_parameter(&name, &usage).0,
+                    priority,
+                },
+                Argument::OptionalParameter {
+                    name,
+                    priority,
+                    default,
+                } => Argument::OptionalParameter {
+                    name: split_typed_parameter(&name, &usage).0,
+                    priority,
+                    default,
+                },
+                literal => literal,
+            })
+            .collect(),
+    };
+
+    let parameters: Vec<&PatType> = parameters.iter().collect();
+    let mut block: Block = match closure.body.as_ref() {
+        Expr::Block(block) => block.block.clone(),
+        body => syn::parse_quote!({ #body }),
+    };
+    if let Some(PatType { pat, ty, .. }) = collect {
+        block = syn::parse_quote!({
+            let #pat = <#ty as lieutenant::FromExtract<_>>::from_extract((#(#patterns,)*));
+            #block
+        });
+    }
+
+    let command_spec = generate_command_spec(
+        &parsed,
+        Metadata::default(),
+        &parameters,
+        Some((ctx_type, ctx_pat)),
+        &block,
+    );
+
+    (quote! {
+        {
+            #command_spec
+        }
+    })
+    .into()
+}
+
+/// Implements `FromExtract` for a struct, assembling it from the tuple of
+/// its fields in declaration order.
+#[proc_macro_error]
+#[proc_macro_derive(FromExtract)]
+pub fn derive_from_extract(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => abort!(
+            input.span(),
+            "`FromExtract` can only be derived for structs"
+        ),
+    };
+    let types: Vec<&Type> = fields.iter().map(|field| &field.ty).collect();
+    let bindings: Vec<Ident> = (0..fields.len())
+        .map(|i| Ident::new(&format!("field_{}", i), Span::call_site()))
+        .collect();
+    let construct = match fields {
+        Fields::Named(fields) => {
+            let names = fields.named.iter().map(|field| &field.ident);
+            quote! { #ident { #(#names: #bindings),* } }
+        }
+        Fields::Unnamed(_) => quote! { #ident(#(#bindings),*) },
+        Fields::Unit => quote! { #ident },
+    };
+
+    (quote! {
+        impl #impl_generics lieutenant::FromExtract<(#(#types,)*)> for #ident #ty_generics #where_clause {
+            fn from_extract((#(#bindings,)*): (#(#types,)*)) -> Self {
+                #construct
+            }
+        }
+    })
+    .into()
+}
+
+#[derive(Debug, FromMeta)]
+struct DeriveArgs {
+    name: String,
+    context: String,
+    #[darling(default)]
+    description: Option<String>,
+    #[darling(multiple, rename = "permission")]
+    permissions: Vec<String>,
+    #[darling(default)]
+    deprecated: Option<String>,
+    #[darling(default)]
+    named: bool,
+}
+
+/// Implements `Command` for a struct whose fields are the parameters of the
+/// command, following the literals of its name:
+///
+/// ```ignore
+/// #[derive(Command)]
+/// #[command(name = "tp", context = "State")]
+/// struct Tp {
+///     x: i64,
+///     y: i64,
+///     z: i64,
+/// }
+///
+/// impl Tp {
+///     async fn execute(&self, ctx: &mut State) -> Result<(), Error> {
+///         ctx.position = (self.x, self.y, self.z);
+///         Ok(())
+///     }
+/// }
+/// ```
+///
+/// Fields of type `Option` are optional parameters. When the command is
+/// dispatched, the struct is assembled from the parsed fields and its
+/// `execute` method is called. The command is registered with
+/// `Tp::command()`, or with any value of the struct, whose fields are then
+/// ignored.
+#[proc_macro_error]
+#[proc_macro_derive(Command, attributes(command))]
+pub fn derive_command(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let ident = &input.ident;
+
+    if !input.generics.params.is_empty() {
+        abort!(
+            input.generics.span(),
+            "`Command` cannot be derived for generic structs"
+        );
+    }
+
+    let fields: Vec<&syn::Field> = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => fields.named.iter().collect(),
+            Fields::Unit => Vec::new(),
+            Fields::Unnamed(fields) => abort!(
+                fields.span(),
+                "`Command` can only be derived for structs with named fields"
+            ),
+        },
+        _ => abort!(input.span(), "`Command` can only be derived for structs"),
+    };
+
+    let nested: Vec<syn::NestedMeta> = input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("command"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(syn::Meta::List(list)) => Some(list.nested.into_iter()),
+            _ => abort!(
+                attr.span(),
+                "expected `#[command(name = \"...\", context = \"...\")]`"
+            ),
+        })
+        .flatten()
+        .collect();
+    let args = match DeriveArgs::from_list(&nested) {
+        Ok(args) => args,
+        Err(e) => return e.write_errors().into(),
+    };
+    let ctx_type: Type = match syn::parse_str(&args.context) {
+        Ok(ty) => ty,
+        Err(e) => abort_call_site!("invalid context type {}: {}", args.context, e),
+    };
+
+    let mut usage = Usage {
+        arguments: args
+            .name
+            .split(' ')
+            .map(|value| Argument::Literal {
+                value: value.to_owned(),
+            })
+            .collect(),
+    };
+    let mut parameters = vec![];
+    for field in &fields {
+        let field_ident = field.ident.as_ref().expect("named field");
+        let name = field_ident.to_string();
+        validate_argument_type(&field.ty, &name);
+        usage.arguments.push(if is_option(&field.ty) {
+            Argument::OptionalParameter {
+                name,
+                priority: 0,
+                default: None,
+            }
+        } else {
+            Argument::Parameter { name, priority: 0 }
+        });
+        parameters.push(PatType {
+            attrs: vec![],
+            pat: Box::new(syn::parse_quote!(#field_ident)),
+            colon_token: Default::default(),
+            ty: Box::new(field.ty.clone()),
+        });
+    }
+    validate_usage(&usage);
+
+    let names = fields.iter().map(|field| &field.ident);
+    let block: Block = syn::parse_quote!({
+        let __command = #ident { #(#names),* };
+        __command.execute(__ctx).await
+    });
+    let ctx_pat: Pat = syn::parse_quote!(__ctx);
+
+    let metadata = Metadata {
+        description: args.description,
+        permissions: args.permissions,
+        deprecated: args.deprecated,
+        named: args.named,
+    };
+    let parameters: Vec<&PatType> = parameters.iter().collect();
+    let command_spec = generate_command_spec(
+        &usage,
+        metadata,
+        &parameters,
+        Some((&ctx_type, &ctx_pat)),
+        &block,
+    );
+
+    (quote! {
+        impl #ident {
+            /// Returns the command, to be registered with a dispatcher.
+            pub fn command() -> lieutenant::CommandSpec<#ctx_type> {
+                #command_spec
+            }
+        }
+
+        impl lieutenant::Command<#ctx_type> for #ident {
+            fn build(self) -> lieutenant::CommandSpec<#ctx_type> {
+                Self::command()
+            }
+        }
+    })
+    .into()
+}
+
+/// Splits a `name:Type` parameter of a `command_spec!` usage message.
+fn split_typed_parameter(parameter: &str, usage: &LitStr) -> (String, Type) {
+    let (name, ty) = match parameter.find(':') {
+        Some(index) => (&parameter[..index], &parameter[index + 1..]),
+        None => abort!(usage.span(), "parameter {} has no type", parameter;
+
+            help = "declare the type of the parameter: `<{}:Type>`", parameter;
+        ),
+    };
+    match syn::parse_str(ty) {
+        Ok(ty) => (name.to_owned(), ty),
+        Err(e) => abort!(usage.span(), "invalid type for parameter {}: {}", name, e),
+    }
+}
+
+fn parse_usage(usage: &str, priority: usize) -> Usage {
     let mut arguments = vec![];
 
     for splitted in usage.split(' ') {
         let (first, middle) = splitted.split_at(1.min(splitted.len()));
-        let (middle, last) = middle.split_at(middle.len() - 1);
+        let (middle, last) = middle.split_at(middle.len().saturating_sub(1));
         match (first, middle, last) {
             ("<", param, ">") => arguments.push(Argument::Parameter {
                 name: param.to_owned(),
-                priority: 0,
-            }),
-            ("[", param, "]") => arguments.push(Argument::OptionalParameter {
-                name: param.to_owned(),
-                priority: 0,
+                priority,
             }),
+            ("[", param, "]") => {
+                // `[name=default]` supplies the input parsed when the argument is omitted.
+                let (name, default) = match param.find('=') {
+                    Some(index) => {
+                        let default = &param[index + 1..];
+                        let default = default
+                            .strip_prefix('"')
+                            .and_then(|default| default.strip_suffix('"'))
+                            .unwrap_or(default);
+                        (&param[..index], Some(default.to_owned()))
+                    }
+                    None => (param, None),
+                };
+                arguments.push(Argument::OptionalParameter {
+                    name: name.to_owned(),
+                    priority,
+                    default,
+                })
+            }
             (_, _, _) => arguments.push(Argument::Literal {
                 value: splitted.to_owned(),
             }),
@@ -193,6 +629,26 @@ fn parse_usage(usage: &str) -> Usage {
     Usage { arguments }
 }
 
+fn validate_usage(usage: &Usage) {
+    // Optional parameters may only be followed by other optional parameters.
+    let mut optional = None;
+    for argument in &usage.arguments {
+        match argument {
+            Argument::OptionalParameter { name, .. } => optional = Some(name),
+            _ => {
+                if let Some(name) = optional {
+                    emit_call_site_error!(
+                        "optional parameter {} is followed by a required argument", name;
+
+                        help = "move optional parameters to the end of the usage message";
+                    );
+                    return;
+                }
+            }
+        }
+    }
+}
+
 fn collect_parameters<'a>(
     usage: &Usage,
     inputs: &(impl Iterator<Item = &'a FnArg> + Clone),
@@ -234,21 +690,56 @@ fn collect_parameter<'a>(
 fn validate_parameter(name: &str, arg: &Argument, arg_type: &PatType) {
     // If not an optional parameter, ensure the type is not an option.
     // Otherwise, ensure it _is_ an Option.
-    if let Argument::Parameter { .. } = arg {
-        // not optional
-        validate_argument_type(&arg_type.ty, name);
-        if let Type::Path(path) = arg_type.ty.as_ref() {
-            // verify that path is not an `Option`
-            if path.path.is_ident(&Ident::new("Option", Span::call_site())) {
+    validate_argument_type(&arg_type.ty, name);
+    match arg {
+        Argument::Parameter { .. }
+        | Argument::OptionalParameter {
+            default: Some(_), ..
+        } => {
+            // Required parameters and optional parameters with a default
+            // always receive a value.
+            if is_option(&arg_type.ty) {
                 emit_error!(
-                    path.span(), "the parameter {} is defined as an `Option`, but the usage message indicates it is a required argument", name;
+                    arg_type.ty.span(), "the parameter {} is defined as an `Option`, but the usage message indicates it always has a value", name;
 
-                    help = "change the usage instructions to make the argument optional: `<{}>`", name;
+                    help = "change the usage instructions to make the argument optional: `[{}]`", name;
                 );
             }
-        };
-    } else {
-        // optional
+        }
+        Argument::OptionalParameter { default: None, .. } => {
+            if !is_option(&arg_type.ty) {
+                emit_error!(
+                    arg_type.ty.span(), "the parameter {} is optional, but is not defined as an `Option`", name;
+
+                    help = "change the type of the parameter to `Option<_>`, or give it a default: `[{}=<default>]`", name;
+                );
+            }
+        }
+        Argument::Literal { .. } => (),
+    }
+}
+
+fn is_option(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident == Ident::new("Option", Span::call_site()))
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+fn is_trailing(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident == "Trailing")
+            .unwrap_or(false),
+        _ => false,
     }
 }
 
@@ -259,15 +750,15 @@ fn validate_argument_type(ty: &Type, name: &str) {
 
             help = "change the type of the parameter {}", name;
         ),
-        Type::Reference(reference) => {
-            if reference.lifetime.clone().map(|l| l.ident.to_string()) != Some("static".to_owned())
-            {
-                emit_error!(
-                    reference.span(), "command function may not take non-'static references as paramters";
-
-                    hint = "use an owned value instead by removing the '&'";
-                );
-            }
+        Type::Reference(reference)
+            if reference.lifetime.clone().map(|l| l.ident.to_string())
+                != Some("static".to_owned()) =>
+        {
+            emit_error!(
+                reference.span(), "command function may not take non-'static references as paramters";
+
+                hint = "use an owned value instead by removing the '&'";
+            );
         }
         _ => (),
     }
@@ -312,7 +803,7 @@ fn detect_context_type<'a>(
     first_arg: Option<&'a FnArg>,
 ) -> Option<(&'a Type, &'a Pat)> {
     first_arg
-        .map(|first_arg| {
+        .and_then(|first_arg| {
             let first_arg = match first_arg {
                 FnArg::Typed(arg) => arg,
                 _ => unreachable!(),
@@ -328,7 +819,6 @@ fn detect_context_type<'a>(
                 Some((first_arg.ty.as_ref(), first_arg.pat.as_ref()))
             }
         })
-        .flatten()
         .map(|(ty, pat)| {
             let ty = match ty {
                 Type::Reference(reference) => &reference.elem,
@@ -342,9 +832,18 @@ fn detect_context_type<'a>(
         })
 }
 
+/// Fields of the generated `CommandSpec` besides its arguments and executable.
+#[derive(Debug, Default)]
+struct Metadata {
+    description: Option<String>,
+    permissions: Vec<String>,
+    deprecated: Option<String>,
+    named: bool,
+}
+
 fn generate_command_spec(
     usage: &Usage,
-    description: Option<String>,
+    metadata: Metadata,
     parameters: &[&PatType],
     ctx_type: Option<(&Type, &Pat)>,
     block: &Block,
@@ -362,10 +861,11 @@ fn generate_command_spec(
     for argument in &usage.arguments {
         let argument = match argument {
             Argument::Parameter { name, priority }
-            | Argument::OptionalParameter { name, priority } => {
+            | Argument::OptionalParameter { name, priority, .. } => {
                 let argument_type = parameters[i];
 
                 let ty = &argument_type.ty;
+                let optional = matches!(argument, Argument::OptionalParameter { .. });
                 i += 1;
 
                 quote! {
@@ -374,6 +874,8 @@ fn generate_command_spec(
                         checker: Box::new(<<#ty as lieutenant::ArgumentKind<#ctx_param>>::Checker
                             as lieutenant::ArgumentChecker<#ctx_param>>::default()),
                         priority: #priority,
+                        optional: #optional,
+                        error: None,
                     }
                 }
             }
@@ -405,10 +907,27 @@ fn generate_command_spec(
                     None => quote! { _ctx },
                 };
 
-                parse_args.push(quote! {
-                    let #ident = <<#ty as lieutenant::ArgumentKind<#ctx_param>>::Parser
-                    as lieutenant::ArgumentParser<#ctx_param>>::default().parse(#ctx_ident, &mut args).await.unwrap();
-                });
+                let parser = quote! {
+                    <<#ty as lieutenant::ArgumentKind<#ctx_param>>::Parser
+                    as lieutenant::ArgumentParser<#ctx_param>>::default()
+                };
+
+                match argument {
+                    Argument::OptionalParameter {
+                        default: Some(default),
+                        ..
+                    } => parse_args.push(quote! {
+                        let #ident = if args.is_empty() {
+                            let mut default: &str = #default;
+                            #parser.parse(#ctx_ident, &mut default).await.unwrap()
+                        } else {
+                            #parser.parse(#ctx_ident, &mut args).await.unwrap()
+                        };
+                    }),
+                    _ => parse_args.push(quote! {
+                        let #ident = #parser.parse(#ctx_ident, &mut args).await.unwrap();
+                    }),
+                }
 
                 i += 1;
             }
@@ -419,17 +938,32 @@ fn generate_command_spec(
         }
     }
 
+    let (ctx_name, ctx_actual) = match ctx_type {
+        Some((t, name)) => (quote! { #name }, quote! { #t }),
+        None => (quote! { _ctx }, quote! { C }),
+    };
     let ctx_type = match ctx_type {
         Some((t, name)) => quote! { #name: &mut #t },
         None => quote! { _ctx: &mut C },
     };
 
+    let Metadata {
+        description,
+        permissions,
+        deprecated,
+        named,
+    } = metadata;
     let description = match description {
         Some(description) => quote! { Some(#description.into()) },
         None => quote! { None },
     };
+    let deprecated = match deprecated {
+        Some(replacement) => quote! { Some(#replacement.into()) },
+        None => quote! { None },
+    };
 
     let arguments_len = arguments.len();
+    let extracted = parameters.iter().map(|parameter| &parameter.pat);
 
     let res = quote! {
         use lieutenant::ParserUtil;
@@ -439,12 +973,24 @@ fn generate_command_spec(
         lieutenant::CommandSpec {
             arguments,
             description: #description,
-            exec: |#ctx_type, args| Box::pin(async move {
+            permissions: vec![#(#permissions.into()),*],
+            deprecated: #deprecated,
+            named: #named,
+            exec: lieutenant::Executable::Fn(|#ctx_type, args| Box::pin(async move {
                 use lieutenant::{ArgumentParser as _, ArgumentChecker as _};
                 let mut args = args;
                 #(#parse_args)*
                 #block
-            }),
+            })),
+            extract: Some(|__ctx: &mut #ctx_actual, args| Box::pin(async move {
+                use lieutenant::{ArgumentParser as _, ArgumentChecker as _};
+                // The context is only used by the parsers, if any.
+                #[allow(unused_variables, unused_mut)]
+                let #ctx_name = __ctx;
+                let mut args = args;
+                #(#parse_args)*
+                Ok(Box::new((#(#extracted,)*)) as Box<dyn std::any::Any + Send>)
+            })),
         }
     };
     res
@@ -1,12 +1,59 @@
-use crate::{ArgumentChecker, Context};
+use crate::{ArgumentChecker, ArgumentKind, BoxedParser, Context, ParserUtil};
+use std::any::Any;
 use std::borrow::Cow;
 use std::cmp::Ordering;
+use std::fmt;
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::{Arc, Mutex};
 
-pub trait Command<C: Context> {
+pub trait Command<C: Context>: BuildBoxed<C> {
     /// Returns the root node for parsing this command.
-    fn build(self) -> CommandSpec<C>;
+    fn build(self) -> CommandSpec<C>
+    where
+        Self: Sized;
+}
+
+/// Builds a boxed command, making `Box<dyn Command<C>>` usable.
+///
+/// Implemented for every `Command`.
+pub trait BuildBoxed<C: Context> {
+    fn build_boxed(self: Box<Self>) -> CommandSpec<C>;
+}
+
+impl<C: Context, T: Command<C>> BuildBoxed<C> for T {
+    fn build_boxed(self: Box<Self>) -> CommandSpec<C> {
+        (*self).build()
+    }
+}
+
+impl<C: Context> Command<C> for Box<dyn Command<C>> {
+    fn build(self) -> CommandSpec<C> {
+        self.build_boxed()
+    }
+}
+
+/// Assembles a value from the tuple of parsed command parameters, so that
+/// a command can take them as a single named-field struct.
+///
+/// Usually derived with `#[derive(FromExtract)]`.
+pub trait FromExtract<T> {
+    fn from_extract(extract: T) -> Self;
+}
+
+/// Input left over after a command's arguments, when dispatched leniently.
+///
+/// A `#[command]` function receives it by taking a parameter of this type
+/// which does not appear in the usage message.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Trailing(pub String);
+
+impl std::ops::Deref for Trailing {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
 }
 
 pub enum Argument<C: Context> {
@@ -17,16 +64,91 @@ pub enum Argument<C: Context> {
         name: Cow<'static, str>,
         checker: Box<dyn ArgumentChecker<C>>,
         priority: usize,
+        /// Whether the command may also be executed without this argument.
+        /// Optional arguments may only be followed by other optional arguments.
+        optional: bool,
+        /// Message reported when the input fails to parse as this argument,
+        /// in place of a generic one.
+        error: Option<Cow<'static, str>>,
     },
 }
 
 impl<C: Context> Argument<C> {
+    /// A literal argument, matching `value` exactly.
+    pub fn literal(value: impl Into<Cow<'static, str>>) -> Self {
+        Argument::Literal {
+            value: value.into(),
+        }
+    }
+
+    /// A required parser argument called `name`, checked like `K`.
+    pub fn parser<K>(name: impl Into<Cow<'static, str>>) -> Self
+    where
+        K: ArgumentKind<C>,
+    {
+        Argument::Parser {
+            name: name.into(),
+            checker: Box::new(<K::Checker as ArgumentChecker<C>>::default()),
+            priority: 0,
+            optional: false,
+            error: None,
+        }
+    }
+
     pub fn priority(&self) -> usize {
         match self {
             Argument::Literal { .. } => 0,
             Argument::Parser { priority, .. } => *priority,
         }
     }
+
+    pub fn is_optional(&self) -> bool {
+        match self {
+            Argument::Literal { .. } => false,
+            Argument::Parser { optional, .. } => *optional,
+        }
+    }
+
+    /// Formats the argument like in a usage message, with the type of
+    /// parsers added, such as `<x:i32>` or `[page:usize]`, as returned by
+    /// `ArgumentChecker::type_label`.
+    pub fn typed_label(&self) -> String {
+        match self {
+            Argument::Literal { value } => value.to_string(),
+            Argument::Parser {
+                name,
+                checker,
+                optional: false,
+                ..
+            } => format!("<{}:{}>", name, checker.type_label()),
+            Argument::Parser {
+                name,
+                checker,
+                optional: true,
+                ..
+            } => format!("[{}:{}]", name, checker.type_label()),
+        }
+    }
+}
+
+impl<C: Context> fmt::Display for Argument<C> {
+    /// Formats the argument as it would appear in a usage message:
+    /// literals verbatim, parsers as `<name>` or, if optional, `[name]`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Argument::Literal { value } => f.write_str(value),
+            Argument::Parser {
+                name,
+                optional: false,
+                ..
+            } => write!(f, "<{}>", name),
+            Argument::Parser {
+                name,
+                optional: true,
+                ..
+            } => write!(f, "[{}]", name),
+        }
+    }
 }
 
 impl<C: Context> Clone for Argument<C> {
@@ -39,10 +161,14 @@ impl<C: Context> Clone for Argument<C> {
                 name,
                 checker,
                 priority,
+                optional,
+                error,
             } => Argument::Parser {
                 name: name.clone(),
                 checker: checker.box_clone(),
                 priority: *priority,
+                optional: *optional,
+                error: error.clone(),
             },
         }
     }
@@ -56,7 +182,7 @@ where
         match (self, other) {
             (Argument::Literal { value }, Argument::Literal { value: other }) => value == other,
             (Argument::Parser { checker, .. }, Argument::Parser { checker: other, .. }) => {
-                checker.equals(other)
+                checker.equals(&**other as &dyn Any)
             }
             (_, _) => false,
         }
@@ -90,10 +216,245 @@ pub type Exec<C> = for<'a> fn(
     Box<dyn Future<Output = Result<<C as Context>::Ok, <C as Context>::Error>> + Send + 'a>,
 >;
 
+/// Parses the parameters of a command without executing it, returning
+/// them boxed as a tuple in parameter order.
+pub type Extract<C> = for<'a> fn(
+    &'a mut C,
+    &'a str,
+) -> Pin<
+    Box<dyn Future<Output = Result<Box<dyn Any + Send>, <C as Context>::Error>> + Send + 'a>,
+>;
+
 pub struct CommandSpec<C: Context> {
     pub arguments: Vec<Argument<C>>,
     pub description: Option<Cow<'static, str>>,
-    pub exec: Exec<C>,
+    /// Permission nodes, such as `admin.stop`, required to run this command.
+    pub permissions: Vec<Cow<'static, str>>,
+    /// Replacement to suggest if this command is deprecated.
+    pub deprecated: Option<Cow<'static, str>>,
+    /// Whether parser arguments may also be given by name, in any order,
+    /// such as `tp z=3 x=1 y=2`.
+    pub named: bool,
+    pub exec: Executable<C>,
+    /// Parses the parameters without executing the command, if available,
+    /// as generated by `#[command]`.
+    pub extract: Option<Extract<C>>,
+}
+
+/// Boxed closure run when a command is executed, which may mutate
+/// captured state between invocations.
+pub type ExecMut<C> = Box<dyn for<'a> FnMut(&'a mut C, &'a str) -> ExecFuture<'a, C> + Send>;
+
+/// Future of an executing command.
+pub(crate) type ExecFuture<'a, C> =
+    Pin<Box<dyn Future<Output = Result<<C as Context>::Ok, <C as Context>::Error>> + Send + 'a>>;
+
+/// Code run when a command is executed.
+pub enum Executable<C: Context> {
+    /// A plain function, as generated by `#[command]`.
+    Fn(Exec<C>),
+    /// A closure with mutable state.
+    ///
+    /// Since dispatching only borrows the dispatcher, the closure is kept
+    /// behind a mutex. The lock is held while the closure is called to
+    /// create the future of the command, but not while that future runs,
+    /// so the command may still dispatch other commands.
+    FnMut(Mutex<ExecMut<C>>),
+}
+
+impl<C: Context> Executable<C> {
+    /// Wraps a closure which may mutate captured state, such as a counter.
+    pub fn boxed<F>(exec: F) -> Self
+    where
+        F: for<'a> FnMut(&'a mut C, &'a str) -> ExecFuture<'a, C> + Send + 'static,
+    {
+        Executable::FnMut(Mutex::new(Box::new(exec)))
+    }
+
+    /// Calls the executable, returning the future of the command.
+    pub fn call<'a>(&self, ctx: &'a mut C, command: &'a str) -> ExecFuture<'a, C> {
+        match self {
+            Executable::Fn(exec) => exec(ctx, command),
+            Executable::FnMut(exec) => {
+                let mut exec = exec.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                exec(ctx, command)
+            }
+        }
+    }
+
+    /// Returns whether both are the same function. Closures are never
+    /// considered the same.
+    pub(crate) fn same(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Executable::Fn(this), Executable::Fn(other)) => *this as usize == *other as usize,
+            _ => false,
+        }
+    }
+}
+
+impl<C: Context> From<Exec<C>> for Executable<C> {
+    fn from(exec: Exec<C>) -> Self {
+        Executable::Fn(exec)
+    }
+}
+
+/// Values parsed for the parser arguments of a command, in order, to be
+/// downcast by the handler.
+pub type Values = Vec<Box<dyn Any + Send + Sync>>;
+
+/// The arguments of a command built with `CommandSpec::new`, along with
+/// the parser of each parser argument.
+pub struct ParserChain<C: Context> {
+    arguments: Vec<Argument<C>>,
+    parsers: Vec<Option<BoxedParser<C>>>,
+}
+
+impl<C: Context> Default for ParserChain<C> {
+    fn default() -> Self {
+        Self {
+            arguments: Vec::new(),
+            parsers: Vec::new(),
+        }
+    }
+}
+
+impl<C: Context> ParserChain<C> {
+    /// Creates a chain without any arguments.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a literal argument, matching `value` exactly.
+    pub fn literal(self, value: impl Into<Cow<'static, str>>) -> Self {
+        self.argument(Argument::literal(value), None)
+    }
+
+    /// Appends a required parser argument called `name`, checked and
+    /// parsed like `K`.
+    pub fn parser<K>(self, name: impl Into<Cow<'static, str>>) -> Self
+    where
+        K: ArgumentKind<C> + 'static,
+    {
+        self.argument(Argument::parser::<K>(name), Some(BoxedParser::of::<K>()))
+    }
+
+    /// Appends `argument`, parsed by `parser` if it is a parser argument.
+    pub fn argument(mut self, argument: Argument<C>, parser: Option<BoxedParser<C>>) -> Self {
+        self.arguments.push(argument);
+        self.parsers.push(parser);
+        self
+    }
+}
+
+impl<C: Context> CommandSpec<C> {
+    /// Creates a command from its arguments and a closure run with the
+    /// values parsed for them, without defining a type for it.
+    ///
+    /// ```
+    /// use lieutenant::{CommandDispatcher, CommandSpec, Context, ParserChain};
+    ///
+    /// struct State(i32);
+    ///
+    /// impl Context for State {
+    ///     type Error = std::num::ParseIntError;
+    ///     type Ok = ();
+    /// }
+    ///
+    /// let double = CommandSpec::new(
+    ///     ParserChain::new().literal("double").parser::<i32>("x"),
+    ///     |state: &mut State, values| {
+    ///         let x = *values[0].downcast_ref::<i32>().unwrap();
+    ///         Box::pin(async move {
+    ///             state.0 = x * 2;
+    ///             Ok(())
+    ///         })
+    ///     },
+    /// );
+    /// let dispatcher = CommandDispatcher::default().with(double);
+    ///
+    /// let (mut nodes, mut errors) = (Vec::new(), Vec::new());
+    /// let mut state = State(0);
+    /// let dispatch = dispatcher.dispatch(&mut nodes, &mut errors, &mut state, "double 21");
+    /// assert!(futures::executor::block_on(dispatch).is_ok());
+    /// assert_eq!(state.0, 42);
+    /// ```
+    pub fn new<F>(chain: ParserChain<C>, handler: F) -> Self
+    where
+        F: for<'a> Fn(&'a mut C, Values) -> ExecFuture<'a, C> + Send + Sync + 'static,
+    {
+        let parsers = Arc::new(chain.parsers);
+        let handler = Arc::new(handler);
+        let exec = Executable::boxed(move |ctx, command| {
+            let parsers = Arc::clone(&parsers);
+            let handler = Arc::clone(&handler);
+            Box::pin(async move {
+                let mut input = command;
+                let mut values = Values::new();
+                for parser in parsers.iter() {
+                    if input.is_empty() {
+                        break;
+                    }
+                    match parser {
+                        Some(parser) => values.push(parser.parse(ctx, &mut input).await?),
+                        None => {
+                            input.advance_until(" ");
+                        }
+                    }
+                }
+                handler(ctx, values).await
+            })
+        });
+        Self {
+            arguments: chain.arguments,
+            description: None,
+            permissions: Vec::new(),
+            deprecated: None,
+            named: false,
+            exec,
+            extract: None,
+        }
+    }
+
+    /// Sets the message reported when the input fails to parse as the
+    /// parser argument `name`, such as "Please enter a whole number".
+    ///
+    /// Arguments shared with a previously registered command keep the
+    /// message of that command.
+    ///
+    /// # Panics
+    /// Panics if this command has no parser argument called `name`.
+    pub fn on_error(mut self, name: &str, message: impl Into<Cow<'static, str>>) -> Self {
+        let error = self
+            .arguments
+            .iter_mut()
+            .find_map(|argument| match argument {
+                Argument::Parser {
+                    name: argument,
+                    error,
+                    ..
+                } if argument == name => Some(error),
+                _ => None,
+            })
+            .unwrap_or_else(|| panic!("no argument called `{}`", name));
+        *error = Some(message.into());
+        self
+    }
+
+    /// Returns the path of arguments leading to this command.
+    pub fn path(&self) -> CommandPath {
+        CommandPath(self.arguments.iter().map(ToString::to_string).collect())
+    }
+}
+
+/// Path through the command graph, as a list of argument labels
+/// formatted like in a usage message: `["tp", "<x>", "<y>", "<z>"]`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CommandPath(pub Vec<String>);
+
+impl fmt::Display for CommandPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0.join(" "))
+    }
 }
 
 impl<C: Context> Command<C> for CommandSpec<C> {
@@ -0,0 +1,233 @@
+use crate::command::ExecFuture;
+use crate::parsers::{InvalidString, StringArg};
+use crate::{
+    Argument, ArgumentChecker, ArgumentKind, BoxedParser, CommandSpec, Context, ParserChain,
+    RegisterError, Values,
+};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::num::{ParseFloatError, ParseIntError};
+use std::str::ParseBoolError;
+use std::sync::Arc;
+
+type Handler<C> = Arc<dyn for<'a> Fn(&'a mut C, Values) -> ExecFuture<'a, C> + Send + Sync>;
+
+/// Creates the checker and parser of a new argument of some type.
+pub type ArgumentFactory<C> =
+    Box<dyn Fn() -> (Box<dyn ArgumentChecker<C>>, BoxedParser<C>) + Send + Sync>;
+
+/// Argument types which commands loaded from data refer to by name, such
+/// as `integer`.
+pub struct ArgumentRegistry<C: Context> {
+    factories: HashMap<String, ArgumentFactory<C>>,
+}
+
+impl<C: Context> Default for ArgumentRegistry<C> {
+    fn default() -> Self {
+        Self {
+            factories: HashMap::new(),
+        }
+    }
+}
+
+impl<C: Context> ArgumentRegistry<C> {
+    /// Creates a registry without any argument types.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a registry of the standard argument types:
+    ///
+    /// | Name      | Type        |
+    /// |-----------|-------------|
+    /// | `bool`    | `bool`      |
+    /// | `integer` | `i32`       |
+    /// | `long`    | `i64`       |
+    /// | `float`   | `f32`       |
+    /// | `double`  | `f64`       |
+    /// | `word`    | `String`    |
+    /// | `string`  | `StringArg` |
+    pub fn with_builtins() -> Self
+    where
+        C::Error: From<ParseBoolError>
+            + From<ParseIntError>
+            + From<ParseFloatError>
+            + From<Infallible>
+            + From<InvalidString>,
+    {
+        let mut registry = Self::new();
+        registry.register::<bool>("bool");
+        registry.register::<i32>("integer");
+        registry.register::<i64>("long");
+        registry.register::<f32>("float");
+        registry.register::<f64>("double");
+        registry.register::<String>("word");
+        registry.register::<StringArg>("string");
+        registry
+    }
+
+    /// Makes the argument type `name` available, creating the checker and
+    /// parser of each argument of that type with `factory`. Replaces any
+    /// type previously registered under `name`.
+    pub fn register_parser<F>(&mut self, name: impl Into<String>, factory: F)
+    where
+        F: Fn() -> (Box<dyn ArgumentChecker<C>>, BoxedParser<C>) + Send + Sync + 'static,
+    {
+        self.factories.insert(name.into(), Box::new(factory));
+    }
+
+    /// Makes the argument kind `K` available under `name`, using its
+    /// default checker and parser.
+    pub fn register<K>(&mut self, name: impl Into<String>)
+    where
+        K: ArgumentKind<C> + 'static,
+    {
+        self.register_parser(name, || {
+            (
+                Box::new(<K::Checker as ArgumentChecker<C>>::default()),
+                BoxedParser::of::<K>(),
+            )
+        });
+    }
+
+    /// Creates the checker and parser of an argument of the type `name`.
+    pub fn create(&self, name: &str) -> Option<(Box<dyn ArgumentChecker<C>>, BoxedParser<C>)> {
+        self.factories.get(name).map(|factory| factory())
+    }
+}
+
+/// Argument types and handlers which commands loaded from data refer to
+/// by name.
+pub struct HandlerRegistry<C: Context> {
+    arguments: ArgumentRegistry<C>,
+    handlers: HashMap<String, Handler<C>>,
+}
+
+impl<C: Context> Default for HandlerRegistry<C> {
+    fn default() -> Self {
+        Self {
+            arguments: ArgumentRegistry::new(),
+            handlers: HashMap::new(),
+        }
+    }
+}
+
+impl<C: Context> HandlerRegistry<C> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Makes the argument kind `K` available under `name`, such as `integer`.
+    pub fn with_argument<K>(mut self, name: impl Into<String>) -> Self
+    where
+        K: ArgumentKind<C> + 'static,
+    {
+        self.arguments.register::<K>(name);
+        self
+    }
+
+    /// Uses the argument types of `arguments`, replacing those registered
+    /// so far.
+    pub fn with_arguments(mut self, arguments: ArgumentRegistry<C>) -> Self {
+        self.arguments = arguments;
+        self
+    }
+
+    /// Makes `handler` available under `name`. It is called with the values
+    /// of the parser arguments given to the command; omitted optional
+    /// arguments are left out.
+    pub fn with_handler<F>(mut self, name: impl Into<String>, handler: F) -> Self
+    where
+        F: for<'a> Fn(&'a mut C, Values) -> ExecFuture<'a, C> + Send + Sync + 'static,
+    {
+        self.handlers.insert(name.into(), Arc::new(handler));
+        self
+    }
+
+    /// Builds the command described by `data`, an object such as:
+    ///
+    /// ```json
+    /// {
+    ///     "usage": "give <player> [amount]",
+    ///     "arguments": { "player": "word", "amount": "integer" },
+    ///     "handler": "give",
+    ///     "description": "Gives an item to a player"
+    /// }
+    /// ```
+    ///
+    /// The usage is written like for `#[command]`, so optional arguments may
+    /// only be followed by other optional arguments, and the type of each
+    /// parser argument is looked up among the registered argument types.
+    pub fn build(&self, data: &Value) -> Result<CommandSpec<C>, RegisterError> {
+        let field = |name: &str| {
+            data.get(name)
+                .and_then(Value::as_str)
+                .ok_or_else(|| RegisterError::InvalidData(format!("missing `{}`", name)))
+        };
+        let usage = field("usage")?;
+        let handler = field("handler")?;
+        let handler = Arc::clone(
+            self.handlers
+                .get(handler)
+                .ok_or_else(|| RegisterError::UnknownHandler(handler.to_owned()))?,
+        );
+
+        let mut chain = ParserChain::new();
+        let mut last_optional = None;
+        for word in usage.split(' ') {
+            let required = word
+                .strip_prefix('<')
+                .and_then(|word| word.strip_suffix('>'))
+                .map(|name| (name, false));
+            let parameter = required.or_else(|| {
+                word.strip_prefix('[')
+                    .and_then(|word| word.strip_suffix(']'))
+                    .map(|name| (name, true))
+            });
+            // Optional parameters may only be followed by other optional parameters.
+            let optional = parameter.is_some_and(|(_, optional)| optional);
+            if let (Some(last), false) = (last_optional, optional) {
+                return Err(RegisterError::InvalidData(format!(
+                    "optional `{}` is followed by a required argument",
+                    last
+                )));
+            }
+            let (name, optional) = match parameter {
+                Some(parameter) => parameter,
+                None => {
+                    chain = chain.literal(word.to_owned());
+                    continue;
+                }
+            };
+            if optional {
+                last_optional = Some(name);
+            }
+
+            let kind = data
+                .get("arguments")
+                .and_then(|arguments| arguments.get(name))
+                .and_then(Value::as_str)
+                .ok_or_else(|| RegisterError::InvalidData(format!("no type for `{}`", name)))?;
+            let (checker, parser) = self
+                .arguments
+                .create(kind)
+                .ok_or_else(|| RegisterError::UnknownArgumentType(kind.to_owned()))?;
+            let argument = Argument::Parser {
+                name: name.to_owned().into(),
+                checker,
+                priority: 0,
+                optional,
+                error: None,
+            };
+            chain = chain.argument(argument, Some(parser));
+        }
+
+        let mut spec = CommandSpec::new(chain, move |ctx, values| handler(ctx, values));
+        spec.description = data
+            .get("description")
+            .and_then(Value::as_str)
+            .map(|description| description.to_owned().into());
+        Ok(spec)
+    }
+}
@@ -1,14 +1,246 @@
-use crate::{command::Exec, Argument, Command, CommandSpec, Context, ParserUtil};
+#[cfg(feature = "serde")]
+use crate::HandlerRegistry;
+use crate::{
+    command::Executable, Argument, Command, CommandPath, CommandSpec, Completion, Context,
+    Feedback, ParserUtil,
+};
 use slab::Slab;
 use smallvec::SmallVec;
+use std::any::Any;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::{ControlFlow, DerefMut};
+use std::sync::Mutex;
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 pub enum RegisterError {
     /// Overlapping commands exist: two commands
     /// have an executable node at the same point.
     OverlappingCommands,
     /// Attempted to register an executable command at the root of the command graph.
     ExecutableRoot,
+    /// An argument follows one which consumes the rest of the input, so
+    /// that it could never be reached.
+    UnreachableAfterGreedy,
+    /// A command loaded from data is malformed.
+    InvalidData(String),
+    /// A command loaded from data refers to an unregistered argument type.
+    UnknownArgumentType(String),
+    /// A command loaded from data refers to an unregistered handler.
+    UnknownHandler(String),
+}
+
+/// Reason an alias could not be registered with `CommandDispatcher::register_alias`.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum AliasError {
+    /// No top-level command is named like the target.
+    #[error("no command named `{0}`")]
+    TargetNotFound(String),
+    /// The alias is already the name of a command or of another alias.
+    #[error("`{0}` is already a command or alias")]
+    Conflict(String),
+}
+
+/// Non-fatal problem found while registering a command, collected in
+/// `CommandDispatcher::warnings`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RegisterWarning {
+    /// A command was registered where another command is already
+    /// executable, so that it only runs if the earlier one fails.
+    ShadowedCommand(CommandPath),
+    /// A top-level command was registered under the name of an alias, so
+    /// that it runs in place of the alias target.
+    ShadowedAlias(String),
+}
+
+/// Reason a command would not be dispatched.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum DispatchError {
+    /// No registered command matches the input.
+    #[error("unknown command at `{}`", .0.token())]
+    UnknownCommand(InputContext),
+    /// A command matched, but was followed by input it does not take.
+    #[error("unexpected input after command: `{}`", .0.remaining())]
+    TrailingInput(InputContext),
+    /// The input did not parse as the argument expected next, which
+    /// carries a custom `message` if one was set with `on_error`.
+    #[error("{}", match .message {
+        Some(message) => message.to_string(),
+        None => format!("invalid <{}> at `{}`", .argument, .context.token()),
+    })]
+    ArgumentParseFailed {
+        context: InputContext,
+        argument: Cow<'static, str>,
+        message: Option<Cow<'static, str>>,
+    },
+}
+
+impl DispatchError {
+    /// Returns the input line and where in it the error occurred.
+    pub fn context(&self) -> &InputContext {
+        match self {
+            DispatchError::UnknownCommand(context)
+            | DispatchError::TrailingInput(context)
+            | DispatchError::ArgumentParseFailed { context, .. } => context,
+        }
+    }
+}
+
+/// Error returned by `dispatch_full`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DispatchFailure<E> {
+    /// No command would be dispatched to, as reported by `check`.
+    Unmatched(DispatchError),
+    /// Commands matched the input, but each one failed when executed. Holds
+    /// the errors they returned in order, or none if middleware stopped the
+    /// command from being executed.
+    CommandFailed(Vec<E>),
+}
+
+/// Error returned by `dispatch_bytes`.
+#[derive(Debug)]
+pub enum Utf8OrDispatchError<'c, E> {
+    /// The command was not valid UTF-8, so nothing was dispatched.
+    Utf8(std::str::Utf8Error),
+    /// The errors of the executables which were tried, like `dispatch`.
+    Dispatch(&'c Vec<E>),
+}
+
+/// The full command line a `DispatchError` occurred in, along with the
+/// position of the offending input, for displaying errors to clients.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InputContext {
+    line: String,
+    position: usize,
+}
+
+impl InputContext {
+    /// Creates a context for the error at byte offset `position` of `line`.
+    ///
+    /// The position indexes `line` exactly as given. An offset past the end
+    /// is clamped to the end, and one inside a character is moved back to
+    /// the start of that character.
+    pub fn new(line: impl Into<String>, position: usize) -> Self {
+        let line = line.into();
+        let mut position = position.min(line.len());
+        while !line.is_char_boundary(position) {
+            position -= 1;
+        }
+        Self { line, position }
+    }
+
+    /// Returns the complete command line the position indexes.
+    pub fn line(&self) -> &str {
+        &self.line
+    }
+
+    /// Returns the byte offset of the offending input in the line.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the input from the offending token onwards.
+    pub fn remaining(&self) -> &str {
+        &self.line[self.position..]
+    }
+
+    /// Returns the offending token.
+    pub fn token(&self) -> &str {
+        self.remaining().split(' ').next().unwrap_or("")
+    }
+}
+
+/// A successful dispatch, as returned by `CommandDispatcher::dispatch_full`.
+pub struct DispatchSuccess<'s, C: Context> {
+    /// Value returned by the executed command.
+    pub ok: C::Ok,
+    /// The executed command, along with its metadata.
+    pub command: &'s CommandSpec<C>,
+    /// Byte span of each argument of the executed command.
+    pub spans: Vec<ArgSpan>,
+    /// The line the spans index, which is the command after rewriting if it
+    /// was rewritten before matching, such as by an alias.
+    pub line: String,
+}
+
+/// How far a possibly incomplete input matches the command graph, as
+/// computed by `CommandDispatcher::analyze`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ParseAnalysis {
+    /// Arguments matched along the furthest matching path.
+    pub path: CommandPath,
+    /// Byte offset in the input up to which the path matched.
+    pub offset: usize,
+    /// Labels of the arguments which may follow, such as `<y>` or `stop`.
+    pub expected: Vec<String>,
+}
+
+/// Byte span of an argument in a command, as returned by
+/// `CommandDispatcher::parse_with_spans`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArgSpan {
+    /// Label of the argument, such as `<x>` or `tp`.
+    pub label: String,
+    /// Byte offset at which the argument starts.
+    pub start: usize,
+    /// Byte offset at which the argument ends, exclusive.
+    pub end: usize,
+}
+
+/// Work done matching a command, as recorded by `CommandDispatcher::dispatch_traced`.
+///
+/// Commands made up only of literals are found without visiting any nodes,
+/// unless some parser argument is given a priority or commit mode is
+/// enabled, as are inputs held in the match cache.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct DispatchTrace {
+    /// Number of nodes whose argument was matched against the input.
+    pub visited: usize,
+    /// Number of calls to `ArgumentChecker::satisfies`.
+    pub satisfies_calls: usize,
+}
+
+/// Difference between two command graphs, as computed by `CommandDispatcher::diff`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TreeDiff {
+    /// Executable paths only present in the new graph.
+    pub added: Vec<CommandPath>,
+    /// Executable paths only present in the old graph.
+    pub removed: Vec<CommandPath>,
+    /// Commands present in both graphs whose description, permissions or deprecation differ.
+    pub changed: Vec<CommandPath>,
+}
+
+impl TreeDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Row of the table of commands returned by `CommandDispatcher::command_table`.
+#[cfg(feature = "serde")]
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize)]
+pub struct CommandTableEntry {
+    /// Name of the top-level command, such as `tp`.
+    pub name: String,
+    /// Usage message, such as `tp <x> <y> <z>`.
+    pub usage: String,
+    pub description: Option<String>,
+    /// Permission nodes required to run the command.
+    pub permissions: Vec<String>,
+    /// Parser arguments taken by the command, in order.
+    pub arguments: Vec<ArgumentDescriptor>,
+}
+
+/// Parser argument of a command, as listed in a `CommandTableEntry`.
+#[cfg(feature = "serde")]
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize)]
+pub struct ArgumentDescriptor {
+    pub name: String,
+    /// Identifier of the Brigadier parser, such as `brigadier:integer`.
+    pub parser: String,
+    pub optional: bool,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -26,18 +258,107 @@ pub struct CommandDispatcher<C: Context> {
     nodes: Slab<Node<C>>,
     children: SmallVec<[NodeKey; 4]>,
     commands: Vec<CommandSpec<C>>,
+    /// Symbol ids of all literals, so that dispatch compares ids instead
+    /// of strings.
+    symbols: HashMap<Cow<'static, str>, u32>,
+    /// Top-level and executable node of every command made up only of
+    /// literals, keyed by its full text, such as `world border reset`.
+    literal_paths: HashMap<String, (NodeKey, NodeKey)>,
+    /// Whether some parser argument has a priority, so that it may be tried
+    /// before a literal and `literal_paths` can not be used.
+    prioritized: bool,
+    /// Executable nodes recently matched by whole inputs, if enabled.
+    match_cache: Option<Mutex<MatchCache>>,
+    warnings: Vec<RegisterWarning>,
+    on_deprecated: Option<DeprecationHandler>,
+    localizer: Option<Localizer<C>>,
+    /// Conditions under which top-level commands exist, keyed by name.
+    conditions: HashMap<String, Condition<C>>,
+    /// Names of top-level commands, keyed by their aliases.
+    aliases: HashMap<String, String>,
+    commit: bool,
+    lenient: bool,
+    separator: char,
+    tokenizer: Option<Tokenizer>,
+    ranking: Option<Ranking>,
+    middleware: Vec<Box<dyn Middleware<C>>>,
 }
 
+/// Code run around every dispatch, such as for logging or auditing.
+///
+/// Middleware is layered: the `before` hooks run in the order the
+/// middleware was added, and the `after` hooks in reverse.
+pub trait Middleware<C: Context>: Send + Sync {
+    /// Runs before the command is dispatched. Returning `false` aborts the
+    /// dispatch, skipping the `before` hooks of all inner middleware.
+    fn before(&self, _ctx: &mut C, _command: &str) -> bool {
+        true
+    }
+
+    /// Runs after the command was dispatched, or aborted by an inner
+    /// middleware, with whether some command executed successfully.
+    fn after(&self, _ctx: &mut C, _command: &str, _executed: bool) {}
+}
+
+/// Callback invoked with the path of a deprecated command and its replacement.
+type DeprecationHandler = Box<dyn Fn(&CommandPath, &str) + Send + Sync>;
+
+/// Callback translating a message key for the user owning a context.
+type Localizer<C> = Box<dyn Fn(&str, &C) -> String + Send + Sync>;
+
+/// Predicate on the state of the world deciding whether commands exist.
+type Condition<C> = Box<dyn Fn(&C) -> bool + Send + Sync>;
+
+/// Splits input into the tokens matched against the command tree.
+type Tokenizer = Box<dyn for<'a> Fn(&'a str) -> Vec<Cow<'a, str>> + Send + Sync>;
+
+/// Scores a completion for the word being typed, higher being better.
+type Ranking = Box<dyn Fn(&str, &str) -> Option<u32> + Send + Sync>;
+
 impl<C: Context> Default for CommandDispatcher<C> {
     fn default() -> Self {
         Self {
             nodes: Default::default(),
             children: Default::default(),
             commands: Default::default(),
+            symbols: Default::default(),
+            literal_paths: Default::default(),
+            prioritized: false,
+            match_cache: None,
+            warnings: Vec::new(),
+            on_deprecated: None,
+            localizer: None,
+            conditions: HashMap::new(),
+            aliases: HashMap::new(),
+            commit: false,
+            lenient: false,
+            separator: ' ',
+            tokenizer: None,
+            ranking: None,
+            middleware: Vec::new(),
         }
     }
 }
 
+/// Collects commands into a dispatcher.
+///
+/// # Panics
+/// Panics if a command fails to register, like `with`. Use `register_all`
+/// to handle this error.
+impl<C, T> std::iter::FromIterator<T> for CommandDispatcher<C>
+where
+    C: Context,
+    T: Command<C>,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(commands: I) -> Self {
+        commands
+            .into_iter()
+            .fold(Self::default(), |dispatcher, command| {
+                dispatcher.with(command)
+            })
+    }
+}
+
 impl<C> CommandDispatcher<C>
 where
     C: Context,
@@ -47,16 +368,225 @@ where
         Self::default()
     }
 
+    /// Creates a new `CommandDispatcher` with room for `nodes` argument nodes
+    /// and `commands` commands before reallocating.
+    pub fn with_capacity(nodes: usize, commands: usize) -> Self {
+        Self {
+            nodes: Slab::with_capacity(nodes),
+            commands: Vec::with_capacity(commands),
+            ..Self::default()
+        }
+    }
+
+    /// Sets whether matching commits to the first argument which matches.
+    ///
+    /// By default, if a command fails to match further along, alternative
+    /// arguments matching the same input are tried as well. With `commit`
+    /// set, they are not, which is faster for grammars where the first
+    /// match is always the right one.
+    pub fn with_commit(mut self, commit: bool) -> Self {
+        self.commit = commit;
+        self
+    }
+
+    /// Sets whether commands followed by extra input are run when no
+    /// command matches the input exactly.
+    ///
+    /// The extra input is passed to such commands through a `Trailing`
+    /// parameter. By default, commands only run if they consume all input.
+    pub fn with_lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Sets the character separating arguments, such as `|` to dispatch
+    /// `tp|1|2|3`. Defaults to a space.
+    ///
+    /// The input is matched as if each separator were a space, so spaces
+    /// separate arguments as well.
+    ///
+    /// # Panics
+    /// Panics if `separator` is not ASCII.
+    pub fn with_separator(mut self, separator: char) -> Self {
+        assert!(separator.is_ascii(), "separator must be ASCII");
+        self.separator = separator;
+        self
+    }
+
+    /// Sets the function splitting input into tokens, replacing the
+    /// default of splitting on the separator.
+    ///
+    /// The tokens are joined by spaces and matched against the command
+    /// tree, so a token should not itself contain spaces. Byte offsets
+    /// reported in errors are relative to the joined tokens rather than
+    /// to the original input.
+    pub fn with_tokenizer(mut self, tokenizer: Tokenizer) -> Self {
+        self.tokenizer = Some(tokenizer);
+        self
+    }
+
+    /// Enables caching the node matched by each of the `capacity` most
+    /// recently dispatched inputs, so that dispatching the same input again
+    /// runs the matched command without matching its arguments anew.
+    ///
+    /// The cache is cleared whenever commands are registered, replaced,
+    /// enabled or disabled. As cached inputs skip the argument checkers,
+    /// it should only be used if these match regardless of the context. If
+    /// the commands of a cached match fail, no other command is tried.
+    pub fn with_match_cache(mut self, capacity: usize) -> Self {
+        self.match_cache = Some(Mutex::new(MatchCache::new(capacity)));
+        self
+    }
+
+    /// Ranks completions with `ranking`, called with the word being typed
+    /// and a completion for it, such as `Completion::rank`. `suggestions`
+    /// then leaves out completions it returns `None` for and orders the
+    /// rest best-first.
+    ///
+    /// Unlike by default, literals and the completions of parser arguments
+    /// need not start with the word being typed, as `ranking` decides which
+    /// ones match.
+    pub fn with_ranking(
+        mut self,
+        ranking: impl Fn(&str, &str) -> Option<u32> + Send + Sync + 'static,
+    ) -> Self {
+        self.ranking = Some(Box::new(ranking));
+        self
+    }
+
+    fn clear_match_cache(&mut self) {
+        if let Some(cache) = &mut self.match_cache {
+            cache.get_mut().unwrap().clear();
+        }
+    }
+
+    /// Returns `command` with the separator replaced by spaces, which
+    /// arguments are split on, any alias replaced by its target unless a
+    /// command has the same name, and named arguments put in order. Byte
+    /// offsets are unchanged unless a custom tokenizer is set, or an alias
+    /// or named arguments were given.
+    fn tokenize<'a>(&self, command: &'a str) -> Cow<'a, str> {
+        let mut command = if let Some(tokenizer) = &self.tokenizer {
+            Cow::Owned(tokenizer(command).join(" "))
+        } else if self.separator == ' ' || !command.contains(self.separator) {
+            Cow::Borrowed(command)
+        } else {
+            Cow::Owned(command.replace(self.separator, " "))
+        };
+        let name = command.split(' ').next().unwrap_or("");
+        let target = self.aliases.get(name);
+        if let Some(target) = target.filter(|_| !self.is_root_literal(name)) {
+            command = Cow::Owned(format!("{}{}", target, &command[name.len()..]));
+        }
+        if command.contains('=') {
+            if let Some(positional) = self.positional(&command) {
+                return Cow::Owned(positional);
+            }
+        }
+        command
+    }
+
+    /// Returns the line offsets into `tokenized`, the result of
+    /// `tokenize(command)`, are reported against: `command` itself if
+    /// tokenizing only replaced the separator, and `tokenized` otherwise, as
+    /// offsets no longer line up.
+    fn reported_line<'l>(&self, command: &'l str, tokenized: &'l str) -> &'l str {
+        if self.separator.len_utf8() == 1 && tokenized == command.replace(self.separator, " ") {
+            command
+        } else {
+            tokenized
+        }
+    }
+
+    /// Rewrites `command` to the positional form of the first command
+    /// taking named arguments which it invokes by name, such as
+    /// `tp z=3 x=1 y=2` to `tp 1 2 3`.
+    ///
+    /// Such an invocation gives all literals of the command first, followed
+    /// by a `name=value` token for each parser argument, in any order.
+    /// Optional arguments may only be left out if all following parser
+    /// arguments are left out as well.
+    fn positional(&self, command: &str) -> Option<String> {
+        self.commands
+            .iter()
+            .filter(|spec| spec.named)
+            .find_map(|spec| {
+                let mut tokens = command.split(' ');
+                for argument in &spec.arguments {
+                    if let Argument::Literal { value } = argument {
+                        if tokens.next()? != value {
+                            return None;
+                        }
+                    }
+                }
+
+                let mut named = HashMap::new();
+                for token in tokens {
+                    let (name, value) = token.split_once('=')?;
+                    if named.insert(name, value).is_some() {
+                        return None;
+                    }
+                }
+
+                let mut positional = Vec::with_capacity(spec.arguments.len());
+                let mut omitted = false;
+                for argument in &spec.arguments {
+                    let token = match argument {
+                        Argument::Literal { value } => Some(value.as_ref()),
+                        Argument::Parser { name, .. } => named.remove(name.as_ref()),
+                    };
+                    match token {
+                        Some(token) if !omitted => positional.push(token),
+                        None if argument.is_optional() => omitted = true,
+                        _ => return None,
+                    }
+                }
+                // Every name must belong to an argument of the command.
+                if !named.is_empty() {
+                    return None;
+                }
+                Some(positional.join(" "))
+            })
+    }
+
     /// Registers a command to this `CommandDispatcher`.
+    ///
+    /// When several sibling parser arguments could match the same input,
+    /// the one with the highest priority is tried first. Literals are tried
+    /// before parsers of the same priority.
     pub fn register(&mut self, command: impl Command<C>) -> Result<(), RegisterError>
     where
         C: 'static,
     {
         let spec = command.build();
+        self.clear_match_cache();
+
+        // The command is executable at the end of its path, as well as
+        // before each of its trailing optional arguments.
+        let mut executable = spec.arguments.len();
+        while executable > 0 && spec.arguments[executable - 1].is_optional() {
+            executable -= 1;
+        }
+        if executable == 0 {
+            // Command with zero (required) arguments?
+            return Err(RegisterError::ExecutableRoot);
+        }
+        let greedy = spec.arguments.iter().position(
+            |argument| matches!(argument, Argument::Parser { checker, .. } if checker.is_greedy()),
+        );
+        if greedy.is_some_and(|index| index + 1 < spec.arguments.len()) {
+            return Err(RegisterError::UnreachableAfterGreedy);
+        }
+
+        self.prioritized |= spec
+            .arguments
+            .iter()
+            .any(|argument| argument.priority() > 0);
 
         let mut arguments = spec.arguments.iter().peekable();
 
         let mut node_key: Option<NodeKey> = None;
+        let mut path = SmallVec::<[NodeKey; 8]>::new();
 
         'argument: while let Some(argument) = arguments.peek() {
             let children = match node_key {
@@ -70,6 +600,7 @@ where
                 if argument == &&child.argument {
                     arguments.next();
                     node_key = Some(*child_key);
+                    path.push(*child_key);
                     continue 'argument;
                 }
             }
@@ -77,32 +608,404 @@ where
         }
 
         for argument in arguments {
-            let child = Node::from(argument.clone());
+            let mut child = Node::from(argument.clone());
+            if let Argument::Literal { value } = argument {
+                let next = self.symbols.len() as u32;
+                child.symbol = Some(*self.symbols.entry(value.clone()).or_insert(next));
+            }
             let child_key = NodeKey(self.nodes.insert(child));
 
-            if let Some(node_key) = node_key {
-                let node = &mut self.nodes[*node_key];
-                node.children.push(child_key);
-            } else {
-                self.children.push(child_key);
+            let children = match node_key {
+                Some(key) => &self.nodes[*key].children,
+                None => &self.children,
+            };
+            // Keep siblings ordered by descending priority so that dispatch
+            // tries them in that order, with literals ahead of parsers of
+            // the same priority; other ties keep registration order.
+            let rank = |argument: &Argument<C>| {
+                let literal = matches!(argument, Argument::Literal { .. });
+                (argument.priority(), literal)
+            };
+            let index = children
+                .iter()
+                .position(|key| rank(&self.nodes[**key].argument) < rank(argument))
+                .unwrap_or_else(|| children.len());
+
+            match node_key {
+                Some(key) => self.nodes[*key].children.insert(index, child_key),
+                None => {
+                    if let Argument::Literal { value } = argument {
+                        if self.aliases.contains_key(value.as_ref()) {
+                            self.warnings
+                                .push(RegisterWarning::ShadowedAlias(value.to_string()));
+                        }
+                    }
+                    self.children.insert(index, child_key)
+                }
             }
 
             node_key = Some(child_key);
+            path.push(child_key);
         }
 
-        if let Some(key) = node_key {
-            let node = &mut self.nodes[*key];
-            node.execs.push(spec.exec.clone());
-        } else {
-            // Command with zero arguments?
-            return Err(RegisterError::ExecutableRoot);
+        // Registering the identical executable again at a node is a no-op.
+        let mut registered = false;
+        let index = self.commands.len();
+        let commands = &self.commands;
+        for (depth, key) in path.iter().enumerate().skip(executable - 1) {
+            let execs = &mut self.nodes[**key].execs;
+            if !execs
+                .iter()
+                .any(|exec| commands[*exec].exec.same(&spec.exec))
+            {
+                if !execs.is_empty() {
+                    let path = spec.arguments[..=depth].iter().map(ToString::to_string);
+                    self.warnings
+                        .push(RegisterWarning::ShadowedCommand(CommandPath(
+                            path.collect(),
+                        )));
+                }
+                execs.push(index);
+                registered = true;
+            }
+        }
+
+        if registered {
+            let literals: Option<Vec<&str>> = spec
+                .arguments
+                .iter()
+                .map(|argument| match argument {
+                    Argument::Literal { value } => Some(value.as_ref()),
+                    Argument::Parser { .. } => None,
+                })
+                .collect();
+            if let (Some(literals), Some(root), Some(last)) = (literals, path.first(), path.last())
+            {
+                self.literal_paths
+                    .insert(literals.join(" "), (*root, *last));
+            }
+            self.commands.push(spec);
+        }
+
+        Ok(())
+    }
+
+    /// Replaces every command whose first argument is the literal `name`
+    /// with `command`, such as when reloading a plugin.
+    ///
+    /// If `command` fails to register, the old commands are kept.
+    pub fn replace(&mut self, name: &str, command: impl Command<C>) -> Result<(), RegisterError>
+    where
+        C: 'static,
+    {
+        let position = self.children.iter().position(|key| {
+            matches!(&self.nodes[**key].argument, Argument::Literal { value } if value == name)
+        });
+        // Detach the old subtree so that the new command does not merge into it.
+        let old = position.map(|position| (position, self.children.remove(position)));
+
+        if let Err(err) = self.register(command) {
+            if let Some((position, key)) = old {
+                self.children.insert(position, key);
+            }
+            return Err(err);
+        }
+
+        if let Some((_, key)) = old {
+            self.remove_subtree(key);
+        }
+        Ok(())
+    }
+
+    /// Registers a command like `register`, but replaces the commands
+    /// executable where it is, rather than only running it if they fail,
+    /// such as for a plugin overriding a built-in command.
+    ///
+    /// Returns whether some command was replaced.
+    pub fn register_override(&mut self, command: impl Command<C>) -> Result<bool, RegisterError>
+    where
+        C: 'static,
+    {
+        let warnings = self.warnings.len();
+        let index = self.commands.len();
+        self.register(command)?;
+        // The commands it shadows are about to be replaced.
+        self.warnings.truncate(warnings);
+        if self.commands.len() == index {
+            return Ok(false);
+        }
+
+        let mut replaced = Vec::new();
+        for (_, node) in &mut self.nodes {
+            if node.execs.contains(&index) {
+                replaced.extend(node.execs.drain(..).filter(|exec| *exec != index));
+                node.execs.push(index);
+            }
+        }
+        let any = !replaced.is_empty();
+
+        // Replaced commands still executable elsewhere, such as before an
+        // optional argument the new command does not take, are kept.
+        replaced.sort_unstable();
+        replaced.dedup();
+        replaced.retain(|exec| !self.nodes.iter().any(|(_, node)| node.execs.contains(exec)));
+        self.remove_commands(&replaced);
+        Ok(any)
+    }
+
+    /// Removes a detached node and all of its descendants, along with the
+    /// commands executable there.
+    fn remove_subtree(&mut self, key: NodeKey) {
+        self.clear_match_cache();
+        self.literal_paths.retain(|_, (root, _)| *root != key);
+        let mut removed = Vec::new();
+        let mut stack = vec![key];
+        while let Some(key) = stack.pop() {
+            let node = self.nodes.remove(*key);
+            removed.extend(node.execs);
+            stack.extend(node.children);
+        }
+        self.remove_commands(&removed);
+    }
+
+    /// Removes the commands at the indices `removed`, which no node may
+    /// still execute.
+    fn remove_commands(&mut self, removed: &[usize]) {
+        // Shift the indices of the remaining commands down to fill the gaps.
+        let mut index = 0;
+        let remap: Vec<Option<usize>> = (0..self.commands.len())
+            .map(|old| {
+                if removed.contains(&old) {
+                    None
+                } else {
+                    index += 1;
+                    Some(index - 1)
+                }
+            })
+            .collect();
+        let mut old = 0;
+        self.commands.retain(|_| {
+            old += 1;
+            remap[old - 1].is_some()
+        });
+        for (_, node) in &mut self.nodes {
+            for exec in &mut node.execs {
+                *exec = remap[*exec].expect("executable of a removed command");
+            }
+        }
+    }
+
+    /// Enables or disables every command whose first argument is the
+    /// literal `name`.
+    ///
+    /// Disabled commands remain registered, but are neither dispatched
+    /// nor suggested until enabled again.
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) {
+        self.clear_match_cache();
+        for key in &self.children {
+            let node = &mut self.nodes[**key];
+            if let Argument::Literal { value } = &node.argument {
+                if value == name {
+                    node.enabled = enabled;
+                }
+            }
         }
+    }
+
+    /// Makes every command whose first argument is the literal `name` exist
+    /// only while `condition` holds for the context, such as `pvp` only
+    /// while PvP is on. Replaces any condition previously set for `name`.
+    ///
+    /// Unlike disabled commands, which are the same for everyone, the
+    /// condition is checked against the context of each dispatch and
+    /// completion. Commands whose condition is false are neither dispatched
+    /// nor suggested.
+    pub fn set_condition(
+        &mut self,
+        name: &str,
+        condition: impl Fn(&C) -> bool + Send + Sync + 'static,
+    ) {
+        self.conditions.insert(name.to_owned(), Box::new(condition));
+    }
 
-        self.commands.push(spec);
+    /// Returns whether the condition set for the top-level command `name`,
+    /// if any, holds.
+    fn condition_holds(&self, name: &str, ctx: &C) -> bool {
+        self.conditions
+            .get(name)
+            .is_none_or(|condition| condition(ctx))
+    }
+
+    /// Returns whether some top-level literal is called `name`.
+    fn is_root_literal(&self, name: &str) -> bool {
+        self.children
+            .iter()
+            .any(|key| match &self.nodes[**key].argument {
+                Argument::Literal { value } => value == name,
+                Argument::Parser { .. } => false,
+            })
+    }
 
+    /// Makes `alias` dispatch to the top-level command `target`, such as
+    /// `tp` to `teleport`, so that `tp 1 2 3` runs `teleport 1 2 3`.
+    ///
+    /// A command named `alias` registered later takes precedence over the
+    /// alias, with a `RegisterWarning::ShadowedAlias` collected.
+    pub fn register_alias(
+        &mut self,
+        alias: impl Into<String>,
+        target: impl Into<String>,
+    ) -> Result<(), AliasError> {
+        let (alias, target) = (alias.into(), target.into());
+        if self.is_root_literal(&alias) || self.aliases.contains_key(&alias) {
+            return Err(AliasError::Conflict(alias));
+        }
+        if !self.is_root_literal(&target) {
+            return Err(AliasError::TargetNotFound(target));
+        }
+        self.aliases.insert(alias, target);
         Ok(())
     }
 
+    /// Registers each pair of an alias and its target like `register_alias`,
+    /// such as from a configuration file, returning the result of each.
+    pub fn register_aliases(
+        &mut self,
+        pairs: impl IntoIterator<Item = (String, String)>,
+    ) -> Vec<Result<(), AliasError>> {
+        pairs
+            .into_iter()
+            .map(|(alias, target)| self.register_alias(alias, target))
+            .collect()
+    }
+
+    /// Sets the callback invoked before a deprecated command is executed,
+    /// with the path of the command and its replacement.
+    pub fn on_deprecated(&mut self, handler: impl Fn(&CommandPath, &str) + Send + Sync + 'static) {
+        self.on_deprecated = Some(Box::new(handler));
+    }
+
+    /// Sets the callback translating the names of parser arguments and the
+    /// descriptions of commands shown by `usage`, `description` and
+    /// `help_page`, given the context of the user they are shown to, such as
+    /// to look up their locale. Literals are never translated, since they
+    /// must be typed as registered.
+    pub fn set_localizer(
+        &mut self,
+        localizer: impl Fn(&str, &C) -> String + Send + Sync + 'static,
+    ) {
+        self.localizer = Some(Box::new(localizer));
+    }
+
+    fn localize(&self, key: &str, ctx: &C) -> String {
+        match &self.localizer {
+            Some(localizer) => localizer(key, ctx),
+            None => key.to_owned(),
+        }
+    }
+
+    /// Returns the usage message of `command` as shown to the user owning
+    /// `ctx`, such as `tp <x> <y> <z>`.
+    pub fn usage(&self, ctx: &C, command: &CommandSpec<C>) -> String {
+        let arguments: Vec<String> = command
+            .arguments
+            .iter()
+            .map(|argument| match argument {
+                Argument::Literal { value } => value.to_string(),
+                Argument::Parser {
+                    name,
+                    optional: false,
+                    ..
+                } => format!("<{}>", self.localize(name, ctx)),
+                Argument::Parser {
+                    name,
+                    optional: true,
+                    ..
+                } => format!("[{}]", self.localize(name, ctx)),
+            })
+            .collect();
+        arguments.join(" ")
+    }
+
+    /// Returns the description of `command` as shown to the user owning
+    /// `ctx`, if it has one.
+    pub fn description(&self, ctx: &C, command: &CommandSpec<C>) -> Option<String> {
+        let description = command.description.as_ref()?;
+        Some(self.localize(description, ctx))
+    }
+
+    /// Adds a middleware, run around every dispatch after those added before.
+    ///
+    /// `dispatch_scoped` only runs middleware once some command matched,
+    /// as it obtains the mutable context only then.
+    pub fn add_middleware(&mut self, middleware: impl Middleware<C> + 'static) {
+        self.middleware.push(Box::new(middleware));
+    }
+
+    /// Runs the `before` hooks of the middleware, returning how many
+    /// allowed dispatch to continue.
+    fn before_middleware(&self, ctx: &mut C, command: &str) -> usize {
+        self.middleware
+            .iter()
+            .take_while(|middleware| middleware.before(ctx, command))
+            .count()
+    }
+
+    /// Runs the `after` hooks of the first `passed` middleware, innermost first.
+    fn after_middleware(&self, passed: usize, ctx: &mut C, command: &str, executed: bool) {
+        for middleware in self.middleware[..passed].iter().rev() {
+            middleware.after(ctx, command, executed);
+        }
+    }
+
+    /// Returns the executable of the command at `index`, first reporting
+    /// it to the deprecation handler if it is deprecated.
+    fn prepare_exec(&self, index: usize) -> &Executable<C> {
+        let spec = &self.commands[index];
+        if let (Some(replacement), Some(handler)) = (&spec.deprecated, &self.on_deprecated) {
+            handler(&spec.path(), replacement);
+        }
+        &spec.exec
+    }
+
+    /// Returns the enabled top-level nodes.
+    fn root_keys(&self) -> impl DoubleEndedIterator<Item = &NodeKey> {
+        self.children
+            .iter()
+            .filter(move |key| self.nodes[***key].enabled)
+    }
+
+    /// Returns the enabled top-level nodes whose condition holds for `ctx`.
+    fn active_roots<'s>(&'s self, ctx: &'s C) -> impl DoubleEndedIterator<Item = &'s NodeKey> {
+        self.root_keys()
+            .filter(move |key| self.root_active(***key, ctx))
+    }
+
+    /// Returns whether the condition of the top-level node `key`, if any,
+    /// holds for `ctx`.
+    fn root_active(&self, key: usize, ctx: &C) -> bool {
+        match &self.nodes[key].argument {
+            Argument::Literal { value } => self.condition_holds(value, ctx),
+            Argument::Parser { .. } => true,
+        }
+    }
+
+    /// Registers each of `commands`, returning the result of registering
+    /// each in turn. Unlike chaining `register`, a failure does not stop
+    /// the remaining commands from being registered.
+    pub fn register_all(
+        &mut self,
+        commands: impl IntoIterator<Item = Box<dyn Command<C>>>,
+    ) -> Vec<Result<(), RegisterError>>
+    where
+        C: 'static,
+    {
+        commands
+            .into_iter()
+            .map(|command| self.register(command))
+            .collect()
+    }
+
     /// Method-chaining function to register a command.
     ///
     /// # Panics
@@ -117,48 +1020,1344 @@ where
     }
 
     /// Dispatches a command. Returns whether a command was executed.
-    pub async fn dispatch<'a, 'b, 'c>(
+    pub async fn dispatch<'a, 'c>(
         &self,
-        nodes: &'b mut Vec<(&'a str, NodeKey)>,
+        nodes: &mut Vec<(&'a str, NodeKey)>,
         errors: &'c mut Vec<C::Error>,
         ctx: &mut C,
         command: &'a str,
     ) -> Result<C::Ok, &'c Vec<C::Error>> {
-        nodes.clear();
-        errors.clear();
-
-        for child_key in &self.children {
-            nodes.push((&command, *child_key));
-        }
-
-        while let Some((mut input, node_key)) = nodes.pop() {
-            let node = &self.nodes[*node_key];
-            let satisfies = match &node.argument {
-                Argument::Literal { value } => value == input.advance_until(" "),
-                Argument::Parser { checker, .. } => checker.satisfies(ctx, &mut input).await,
-            };
+        self.dispatch_matched(nodes, errors, ctx, command)
+            .await
+            .map(|(ok, _)| ok)
+    }
 
-            if input.is_empty() && satisfies {
-                for exec in &node.execs {
-                    match exec(ctx, command).await {
-                        Ok(ok) => return Ok(ok),
-                        Err(err) => errors.push(err),
-                    }
-                }
+    /// Dispatches each of the commands in `line` separated by `;`, such as
+    /// `say hi; stop`, in turn. A `;` in a quoted argument, such as in
+    /// `say "a; b"`, does not separate commands.
+    ///
+    /// Stops at the first command which fails or returns
+    /// `ControlFlow::Break`, skipping the rest of the line. Returns the
+    /// `Break` value if there was one, or the errors of the command which
+    /// failed.
+    pub async fn dispatch_line<'a, 'c, B>(
+        &self,
+        nodes: &mut Vec<(&'a str, NodeKey)>,
+        errors: &'c mut Vec<C::Error>,
+        ctx: &mut C,
+        line: &'a str,
+    ) -> Result<ControlFlow<B>, &'c Vec<C::Error>>
+    where
+        C: Context<Ok = ControlFlow<B>>,
+    {
+        for command in split_commands(line) {
+            let command = command.trim();
+            if command.is_empty() {
                 continue;
             }
-
-            if satisfies {
-                for child_key in &node.children {
-                    nodes.push((&mut input, *child_key));
-                }
+            match self.dispatch(nodes, errors, ctx, command).await {
+                Ok(ControlFlow::Continue(())) => {}
+                Ok(ControlFlow::Break(value)) => return Ok(ControlFlow::Break(value)),
+                Err(_) => return Err(&*errors),
             }
         }
-        Err(&*errors)
+        Ok(ControlFlow::Continue(()))
     }
 
-    pub fn commands(&self) -> impl Iterator<Item = &CommandSpec<C>> {
-        self.commands.iter()
+    /// Dispatches a command received as bytes, such as straight off the
+    /// network, without copying it. The bytes must be valid UTF-8.
+    pub async fn dispatch_bytes<'a, 'c>(
+        &self,
+        nodes: &mut Vec<(&'a str, NodeKey)>,
+        errors: &'c mut Vec<C::Error>,
+        ctx: &mut C,
+        command: &'a [u8],
+    ) -> Result<C::Ok, Utf8OrDispatchError<'c, C::Error>> {
+        let command = std::str::from_utf8(command).map_err(Utf8OrDispatchError::Utf8)?;
+        self.dispatch(nodes, errors, ctx, command)
+            .await
+            .map_err(Utf8OrDispatchError::Dispatch)
+    }
+
+    /// Dispatches a command like `dispatch`, additionally returning the
+    /// command which was executed.
+    pub async fn dispatch_matched<'s, 'a, 'c>(
+        &'s self,
+        nodes: &mut Vec<(&'a str, NodeKey)>,
+        errors: &'c mut Vec<C::Error>,
+        ctx: &mut C,
+        command: &'a str,
+    ) -> Result<(C::Ok, &'s CommandSpec<C>), &'c Vec<C::Error>> {
+        match self.tokenize(command) {
+            Cow::Borrowed(command) => {
+                self.dispatch_tokenized(nodes, errors, ctx, command, &mut DispatchTrace::default())
+                    .await
+            }
+            Cow::Owned(command) => {
+                let mut trace = DispatchTrace::default();
+                self.dispatch_tokenized(&mut Vec::new(), errors, ctx, &command, &mut trace)
+                    .await
+            }
+        }
+    }
+
+    /// Dispatches a command like `dispatch_matched`, additionally returning
+    /// the span of each argument, or why no command was executed.
+    pub async fn dispatch_full(
+        &self,
+        ctx: &mut C,
+        command: &str,
+    ) -> Result<DispatchSuccess<'_, C>, DispatchFailure<C::Error>> {
+        let tokenized = self.tokenize(command);
+        let mut executables = SmallVec::<[NodeKey; 4]>::new();
+        let found = self
+            .find_executables(&mut Vec::new(), ctx, &tokenized, &mut executables)
+            .await;
+        if executables.is_empty() {
+            return Err(DispatchFailure::Unmatched(
+                self.dispatch_error(command, &tokenized, found),
+            ));
+        }
+
+        let line = self.reported_line(command, &tokenized);
+        let mut errors = Vec::new();
+        let passed = self.before_middleware(ctx, &tokenized);
+        let mut success = None;
+        if passed == self.middleware.len() {
+            'executables: for node_key in executables {
+                for index in &self.nodes[*node_key].execs {
+                    let spec = &self.commands[*index];
+                    let spans = self.spans(ctx, spec, &tokenized).await;
+                    match self.prepare_exec(*index).call(ctx, &tokenized).await {
+                        Ok(ok) => {
+                            success = Some(DispatchSuccess {
+                                ok,
+                                command: spec,
+                                spans,
+                                line: line.to_owned(),
+                            });
+                            break 'executables;
+                        }
+                        Err(err) => errors.push(err),
+                    }
+                }
+            }
+        }
+        self.after_middleware(passed, ctx, &tokenized, success.is_some());
+        success.ok_or(DispatchFailure::CommandFailed(errors))
+    }
+
+    /// Returns the span of each argument of `spec` in `command`, which
+    /// matches it, up to where the input ends.
+    async fn spans(&self, ctx: &C, spec: &CommandSpec<C>, command: &str) -> Vec<ArgSpan> {
+        let mut spans = Vec::with_capacity(spec.arguments.len());
+        let mut input = command;
+        for argument in &spec.arguments {
+            if input.is_empty() {
+                break;
+            }
+            let start = command.len() - input.len();
+            let satisfies = match argument {
+                Argument::Literal { value } => input.advance_until(" ") == value,
+                Argument::Parser { checker, .. } => checker.satisfies(ctx, &mut input).await,
+            };
+            if !satisfies {
+                break;
+            }
+            let consumed = &command[start..command.len() - input.len()];
+            spans.push(ArgSpan {
+                label: argument.to_string(),
+                start,
+                end: start + consumed.trim_end_matches(' ').len(),
+            });
+        }
+        spans
+    }
+
+    /// Dispatches a command like `dispatch`, returning whether a command was
+    /// executed along with how much work matching the input took, such as
+    /// for finding ambiguous command graphs.
+    pub async fn dispatch_traced(&self, ctx: &mut C, command: &str) -> (bool, DispatchTrace) {
+        let command = self.tokenize(command);
+        let mut errors = Vec::new();
+        let mut trace = DispatchTrace::default();
+        let executed = self
+            .dispatch_tokenized(&mut Vec::new(), &mut errors, ctx, &command, &mut trace)
+            .await
+            .is_ok();
+        (executed, trace)
+    }
+
+    /// Dispatches a command like `dispatch`, returning whether a command was
+    /// executed along with the feedback messages written while dispatching,
+    /// including by commands which were tried but failed.
+    pub async fn dispatch_collecting(&self, ctx: &mut C, command: &str) -> (bool, Vec<String>)
+    where
+        C: Feedback,
+    {
+        let written = ctx.feedback().len();
+        let command = self.tokenize(command);
+        let mut errors = Vec::new();
+        let mut trace = DispatchTrace::default();
+        let executed = self
+            .dispatch_tokenized(&mut Vec::new(), &mut errors, ctx, &command, &mut trace)
+            .await
+            .is_ok();
+        (executed, ctx.feedback().split_off(written))
+    }
+
+    /// Dispatches a command like `dispatch_matched`, with arguments
+    /// already separated by spaces.
+    async fn dispatch_tokenized<'s, 'a, 'c>(
+        &'s self,
+        nodes: &mut Vec<(&'a str, NodeKey)>,
+        errors: &'c mut Vec<C::Error>,
+        ctx: &mut C,
+        command: &'a str,
+        trace: &mut DispatchTrace,
+    ) -> Result<(C::Ok, &'s CommandSpec<C>), &'c Vec<C::Error>> {
+        let passed = self.before_middleware(ctx, command);
+        let result = if passed == self.middleware.len() {
+            self.dispatch_unwrapped(nodes, errors, ctx, command, trace)
+                .await
+        } else {
+            errors.clear();
+            Err(&*errors)
+        };
+        self.after_middleware(passed, ctx, command, result.is_ok());
+        result
+    }
+
+    /// Dispatches a command like `dispatch_matched`, without middleware.
+    async fn dispatch_unwrapped<'s, 'a, 'c>(
+        &'s self,
+        nodes: &mut Vec<(&'a str, NodeKey)>,
+        errors: &'c mut Vec<C::Error>,
+        ctx: &mut C,
+        command: &'a str,
+        trace: &mut DispatchTrace,
+    ) -> Result<(C::Ok, &'s CommandSpec<C>), &'c Vec<C::Error>> {
+        nodes.clear();
+        errors.clear();
+
+        // Commands made up only of literals are found with a single lookup,
+        // as without priorities or commit mode they are tried ahead of any
+        // other command matching the same input anyway. If they fail, the
+        // remaining commands are matched as usual.
+        let mut tried = None;
+        let literal_path = (!self.prioritized && !self.commit)
+            .then(|| self.literal_paths.get(command))
+            .flatten();
+        if let Some((root, node_key)) = literal_path {
+            if self.nodes[**root].enabled && self.root_active(**root, ctx) {
+                for index in &self.nodes[**node_key].execs {
+                    match self.prepare_exec(*index).call(ctx, command).await {
+                        Ok(ok) => return Ok((ok, &self.commands[*index])),
+                        Err(err) => errors.push(err),
+                    }
+                }
+                tried = Some(*node_key);
+            }
+        }
+
+        let cached = self
+            .match_cache
+            .as_ref()
+            .and_then(|cache| cache.lock().unwrap().get(command))
+            .filter(|_| self.condition_holds(command.split(' ').next().unwrap_or(""), ctx))
+            .filter(|node_key| Some(*node_key) != tried);
+        if let Some(node_key) = cached {
+            for index in &self.nodes[*node_key].execs {
+                match self.prepare_exec(*index).call(ctx, command).await {
+                    Ok(ok) => return Ok((ok, &self.commands[*index])),
+                    Err(err) => errors.push(err),
+                }
+            }
+            return Err(&*errors);
+        }
+
+        let mut leftovers = SmallVec::<[(usize, NodeKey); 4]>::new();
+        let mut tokens = Tokens::new(&self.symbols);
+
+        // Children are pushed in reverse so that the highest-priority
+        // sibling is popped, and thus tried, first.
+        for child_key in self.active_roots(ctx).rev() {
+            nodes.push((command, *child_key));
+        }
+
+        while let Some((mut input, node_key)) = nodes.pop() {
+            let node = &self.nodes[*node_key];
+            trace.visited += 1;
+            let satisfies = match &node.argument {
+                Argument::Literal { .. } => tokens.matches(node.symbol, &mut input),
+                Argument::Parser { checker, .. } => {
+                    trace.satisfies_calls += 1;
+                    checker.satisfies(ctx, &mut input).await
+                }
+            };
+            if satisfies && self.commit {
+                nodes.clear();
+            }
+
+            if input.is_empty() && satisfies {
+                if Some(node_key) == tried {
+                    continue;
+                }
+                for index in &node.execs {
+                    match self.prepare_exec(*index).call(ctx, command).await {
+                        Ok(ok) => {
+                            if let Some(cache) = &self.match_cache {
+                                cache.lock().unwrap().insert(command, node_key);
+                            }
+                            return Ok((ok, &self.commands[*index]));
+                        }
+                        Err(err) => errors.push(err),
+                    }
+                }
+                continue;
+            }
+
+            if satisfies {
+                if self.lenient && !node.execs.is_empty() {
+                    leftovers.push((input.len(), node_key));
+                }
+                for child_key in node.children.iter().rev() {
+                    nodes.push((input, *child_key));
+                }
+            }
+        }
+
+        // Without an exact match, commands followed by extra input are run,
+        // those with the least input left over first.
+        leftovers.sort_by_key(|(remaining, _)| *remaining);
+        for (_, node_key) in leftovers {
+            for index in &self.nodes[*node_key].execs {
+                match self.prepare_exec(*index).call(ctx, command).await {
+                    Ok(ok) => return Ok((ok, &self.commands[*index])),
+                    Err(err) => errors.push(err),
+                }
+            }
+        }
+        Err(&*errors)
+    }
+
+    /// Dispatches a command, obtaining the mutable context only once an
+    /// executable is about to run.
+    ///
+    /// Argument checkers only ever need shared access, so the command is
+    /// first matched in full against `ctx`, which may be a read-only
+    /// projection of the real context. Only if some command matched is
+    /// `ctx_fn` called, exactly once, to obtain the context the executables
+    /// run against. Callers keeping their context behind a lock can thus
+    /// hold it only while the command actually executes.
+    pub async fn dispatch_scoped<'a, 'c, F, G>(
+        &self,
+        nodes: &mut Vec<(&'a str, NodeKey)>,
+        errors: &'c mut Vec<C::Error>,
+        ctx: &C,
+        command: &'a str,
+        ctx_fn: F,
+    ) -> Result<C::Ok, &'c Vec<C::Error>>
+    where
+        F: FnOnce() -> G,
+        G: DerefMut<Target = C>,
+    {
+        match self.try_dispatch(nodes, ctx, command).await {
+            DispatchOutcome::NoMatch => {
+                errors.clear();
+                Err(&*errors)
+            }
+            DispatchOutcome::Matched(pending) => pending.run(errors, &mut *ctx_fn()).await,
+        }
+    }
+
+    /// Matches a command with only shared access to `ctx`, returning the
+    /// executables to run, if any, for `PendingExec::run` to run once
+    /// mutable access is obtained.
+    pub async fn try_dispatch<'s, 'a>(
+        &'s self,
+        nodes: &mut Vec<(&'a str, NodeKey)>,
+        ctx: &C,
+        command: &'a str,
+    ) -> DispatchOutcome<'s, 'a, C> {
+        let command = self.tokenize(command);
+        let mut executables = SmallVec::<[NodeKey; 4]>::new();
+        match &command {
+            Cow::Borrowed(command) => {
+                self.find_executables(nodes, ctx, command, &mut executables)
+                    .await
+            }
+            Cow::Owned(command) => {
+                self.find_executables(&mut Vec::new(), ctx, command, &mut executables)
+                    .await
+            }
+        };
+        if executables.is_empty() {
+            DispatchOutcome::NoMatch
+        } else {
+            DispatchOutcome::Matched(PendingExec {
+                dispatcher: self,
+                command,
+                executables,
+            })
+        }
+    }
+
+    /// Parses the arguments of the command `command` would be dispatched to,
+    /// without executing it. The values are boxed as a tuple in the order of
+    /// the parameters, such as `(i32, i32, i32)` for `tp <x> <y> <z>`.
+    ///
+    /// Returns `None` if no command matches, or none of the matching ones
+    /// were generated by `#[command]`.
+    pub async fn parse_args(&self, ctx: &mut C, command: &str) -> Option<Box<dyn Any + Send>> {
+        let command = self.tokenize(command);
+        let mut executables = SmallVec::<[NodeKey; 4]>::new();
+        self.find_executables(&mut Vec::new(), ctx, &command, &mut executables)
+            .await;
+        for node_key in executables {
+            for index in &self.nodes[*node_key].execs {
+                if let Some(extract) = self.commands[*index].extract {
+                    if let Ok(values) = extract(ctx, &command).await {
+                        return Some(values);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Checks whether `command` would be dispatched to some executable,
+    /// without executing anything.
+    ///
+    /// If `command` was rewritten before matching, such as by an alias, the
+    /// context of the error refers to the rewritten line.
+    pub async fn check(&self, ctx: &C, command: &str) -> Result<(), DispatchError> {
+        let mut nodes = Vec::new();
+        let mut executables = SmallVec::<[NodeKey; 4]>::new();
+        let tokenized = self.tokenize(command);
+        let found = self
+            .find_executables(&mut nodes, ctx, &tokenized, &mut executables)
+            .await;
+        if executables.is_empty() {
+            Err(self.dispatch_error(command, &tokenized, found))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns why `command` would not be dispatched, given what
+    /// `find_executables` found matching its tokenized form `tokenized`.
+    fn dispatch_error(
+        &self,
+        command: &str,
+        tokenized: &str,
+        (trailing, position, failed): (bool, usize, Option<NodeKey>),
+    ) -> DispatchError {
+        let context = InputContext::new(self.reported_line(command, tokenized), position);
+        if trailing {
+            DispatchError::TrailingInput(context)
+        } else if let Some(Argument::Parser { name, error, .. }) =
+            failed.map(|key| &self.nodes[*key].argument)
+        {
+            DispatchError::ArgumentParseFailed {
+                context,
+                argument: name.clone(),
+                message: error.clone(),
+            }
+        } else {
+            DispatchError::UnknownCommand(context)
+        }
+    }
+
+    /// Checks each line of a batch of commands, such as a configuration
+    /// file, without executing anything. Blank lines are skipped.
+    ///
+    /// Returns the result of `check` for every remaining line, along with
+    /// its 1-based line number.
+    pub async fn validate_lines<I, S>(
+        &self,
+        ctx: &C,
+        lines: I,
+    ) -> Vec<(usize, Result<(), DispatchError>)>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut results = Vec::new();
+        for (index, line) in lines.into_iter().enumerate() {
+            let line = line.as_ref().trim();
+            if line.is_empty() {
+                continue;
+            }
+            results.push((index + 1, self.check(ctx, line).await));
+        }
+        results
+    }
+
+    /// Returns whether `command` is a complete, runnable command: some path
+    /// through the command graph consumes all of it and ends on an
+    /// executable node. Nothing is executed.
+    pub async fn is_complete(&self, ctx: &C, command: &str) -> bool {
+        let mut nodes = Vec::new();
+        let mut executables = SmallVec::<[NodeKey; 4]>::new();
+        let command = self.tokenize(command);
+        self.find_executables(&mut nodes, ctx, &command, &mut executables)
+            .await;
+        !executables.is_empty()
+    }
+
+    /// Returns completions for the last, possibly empty, word of `input`.
+    ///
+    /// Each completion replaces that word in full. Literals are suggested
+    /// when they start with it; parser arguments suggest whatever their
+    /// checker's `complete` returns. With a ranking set by `with_ranking`,
+    /// completions are instead filtered and ordered by it.
+    pub async fn suggestions(&self, ctx: &C, input: &str) -> Vec<Completion> {
+        let input = self.tokenize(input);
+        let input = &*input;
+        let mut nodes = Vec::new();
+        let mut suggestions = Vec::new();
+        let mut tokens = Tokens::new(&self.symbols);
+
+        for child_key in self.active_roots(ctx).rev() {
+            nodes.push((input, *child_key));
+        }
+
+        while let Some((mut input, node_key)) = nodes.pop() {
+            let node = &self.nodes[*node_key];
+
+            if !input.contains(' ') {
+                let (completions, filtered) = match &node.argument {
+                    Argument::Literal { value } => (vec![Completion::new(value.as_ref())], false),
+                    Argument::Parser { checker, .. } if self.ranking.is_some() => {
+                        (checker.complete(ctx, "").await, false)
+                    }
+                    Argument::Parser { checker, .. } => (checker.complete(ctx, input).await, true),
+                };
+                for completion in completions {
+                    let score = match &self.ranking {
+                        Some(ranking) => ranking(input, &completion.text),
+                        None if filtered || completion.text.starts_with(input) => Some(0),
+                        None => None,
+                    };
+                    if let Some(score) = score {
+                        if !suggestions
+                            .iter()
+                            .any(|(_, suggestion): &(u32, Completion)| {
+                                suggestion.text == completion.text
+                            })
+                        {
+                            suggestions.push((score, completion));
+                        }
+                    }
+                }
+                continue;
+            }
+
+            let satisfies = match &node.argument {
+                Argument::Literal { .. } => tokens.matches(node.symbol, &mut input),
+                Argument::Parser { checker, .. } => checker.satisfies(ctx, &mut input).await,
+            };
+            if satisfies {
+                for child_key in node.children.iter().rev() {
+                    nodes.push((input, *child_key));
+                }
+            }
+        }
+
+        suggestions.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        suggestions
+            .into_iter()
+            .map(|(_, completion)| completion)
+            .collect()
+    }
+
+    /// Determines how far `input` matches the command graph and what may
+    /// follow, such as for highlighting errors while a command is typed.
+    ///
+    /// Among the paths matching the most input, the deepest one is
+    /// reported, along with what may follow any of them.
+    pub async fn analyze(&self, ctx: &C, input: &str) -> ParseAnalysis {
+        let input = self.tokenize(input);
+        let command = &*input;
+        let mut nodes = Vec::new();
+        let mut path = Vec::new();
+        let mut tokens = Tokens::new(&self.symbols);
+
+        let mut best = (0, 0);
+        let mut best_path = Vec::new();
+        let mut expected: Vec<String> = self
+            .active_roots(ctx)
+            .map(|key| self.nodes[**key].argument.to_string())
+            .collect();
+
+        for child_key in self.active_roots(ctx).rev() {
+            nodes.push((command, *child_key, 0));
+        }
+
+        while let Some((mut input, node_key, depth)) = nodes.pop() {
+            let node = &self.nodes[*node_key];
+            let satisfies = match &node.argument {
+                Argument::Literal { .. } => tokens.matches(node.symbol, &mut input),
+                Argument::Parser { checker, .. } => checker.satisfies(ctx, &mut input).await,
+            };
+            if !satisfies {
+                continue;
+            }
+            path.truncate(depth);
+            path.push(node_key);
+
+            let reached = (command.len() - input.len(), depth + 1);
+            let children = node
+                .children
+                .iter()
+                .map(|key| self.nodes[**key].argument.to_string());
+            if reached > best {
+                best = reached;
+                best_path = path.clone();
+                expected = children.collect();
+            } else if reached.0 == best.0 {
+                for child in children {
+                    if !expected.contains(&child) {
+                        expected.push(child);
+                    }
+                }
+            }
+
+            if !input.is_empty() {
+                for child_key in node.children.iter().rev() {
+                    nodes.push((input, *child_key, depth + 1));
+                }
+            }
+        }
+
+        ParseAnalysis {
+            path: CommandPath(
+                best_path
+                    .iter()
+                    .map(|key| self.nodes[**key].argument.to_string())
+                    .collect(),
+            ),
+            offset: best.0,
+            expected,
+        }
+    }
+
+    /// Returns the arguments matched along the furthest matching path of
+    /// `command`, such as `["region", "flags"]` for `region flags build,xyz`
+    /// if `build,xyz` is not valid there. After a failed dispatch, this tells
+    /// how far the input got before failing.
+    pub async fn last_matched_path(&self, ctx: &C, command: &str) -> CommandPath {
+        self.analyze(ctx, command).await.path
+    }
+
+    /// Returns the byte span of each argument of the first command `command`
+    /// would be dispatched to, in order, such as for highlighting arguments.
+    ///
+    /// Returns `None` if no command consumes all of `command`.
+    pub async fn parse_with_spans(&self, ctx: &C, command: &str) -> Option<Vec<ArgSpan>> {
+        let command = self.tokenize(command);
+        let command = &*command;
+        let mut nodes = Vec::new();
+        let mut path = Vec::new();
+        let mut tokens = Tokens::new(&self.symbols);
+
+        for child_key in self.active_roots(ctx).rev() {
+            nodes.push((command, *child_key, 0));
+        }
+
+        while let Some((mut input, node_key, depth)) = nodes.pop() {
+            let node = &self.nodes[*node_key];
+            let start = command.len() - input.len();
+            let satisfies = match &node.argument {
+                Argument::Literal { .. } => tokens.matches(node.symbol, &mut input),
+                Argument::Parser { checker, .. } => checker.satisfies(ctx, &mut input).await,
+            };
+            if !satisfies {
+                continue;
+            }
+            let consumed = &command[start..command.len() - input.len()];
+            path.truncate(depth);
+            path.push(ArgSpan {
+                label: node.argument.to_string(),
+                start,
+                end: start + consumed.trim_end_matches(' ').len(),
+            });
+
+            if input.is_empty() {
+                if !node.execs.is_empty() {
+                    return Some(path);
+                }
+                continue;
+            }
+            for child_key in node.children.iter().rev() {
+                nodes.push((input, *child_key, depth + 1));
+            }
+        }
+        None
+    }
+
+    /// Walks the command graph without executing anything, collecting every
+    /// executable node whose path consumes all of `command` in the order
+    /// `dispatch` would try them. When lenient, these are followed by the
+    /// executable nodes matching with input left over.
+    ///
+    /// Returns whether some executable node matched with input left over,
+    /// the byte offset in `command` up to which the furthest path matched,
+    /// and the parser argument which failed on the input at that offset.
+    async fn find_executables<'a>(
+        &self,
+        nodes: &mut Vec<(&'a str, NodeKey)>,
+        ctx: &C,
+        command: &'a str,
+        executables: &mut SmallVec<[NodeKey; 4]>,
+    ) -> (bool, usize, Option<NodeKey>) {
+        nodes.clear();
+        let mut trailing = false;
+        let mut leftovers = SmallVec::<[(usize, NodeKey); 4]>::new();
+        let mut position = 0;
+        let mut failed = None;
+        let mut tokens = Tokens::new(&self.symbols);
+
+        for child_key in self.active_roots(ctx).rev() {
+            nodes.push((command, *child_key));
+        }
+
+        while let Some((mut input, node_key)) = nodes.pop() {
+            let node = &self.nodes[*node_key];
+            let start = command.len() - input.len();
+            let satisfies = match &node.argument {
+                Argument::Literal { .. } => tokens.matches(node.symbol, &mut input),
+                Argument::Parser { checker, .. } => checker.satisfies(ctx, &mut input).await,
+            };
+
+            if !satisfies {
+                if let Argument::Parser { .. } = node.argument {
+                    if start < command.len() && failed.is_none_or(|(at, _)| at < start) {
+                        failed = Some((start, node_key));
+                    }
+                }
+                continue;
+            }
+            position = position.max(command.len() - input.len());
+            if self.commit {
+                nodes.clear();
+            }
+
+            if input.is_empty() {
+                if !node.execs.is_empty() {
+                    executables.push(node_key);
+                }
+                continue;
+            }
+            if !node.execs.is_empty() {
+                trailing = true;
+                if self.lenient {
+                    leftovers.push((input.len(), node_key));
+                }
+            }
+
+            for child_key in node.children.iter().rev() {
+                nodes.push((input, *child_key));
+            }
+        }
+
+        leftovers.sort_by_key(|(remaining, _)| *remaining);
+        executables.extend(leftovers.into_iter().map(|(_, node_key)| node_key));
+        let failed = failed
+            .filter(|(at, _)| *at == position)
+            .map(|(_, node_key)| node_key);
+        (trailing, position, failed)
+    }
+
+    /// Finds the node reached by following `path` from the root, where each
+    /// element is an argument label as it appears in a usage message, such
+    /// as `tp` or `<x>`.
+    pub fn find(&self, path: &[&str]) -> Option<NodeRef<'_, C>> {
+        let mut children = &self.children;
+        let mut found = None;
+        for label in path {
+            let key = *children
+                .iter()
+                .find(|key| self.nodes[***key].argument.to_string() == *label)?;
+            children = &self.nodes[*key].children;
+            found = Some(NodeRef {
+                dispatcher: self,
+                key,
+            });
+        }
+        found
+    }
+
+    /// Exports the command graph in the JSON format of Brigadier, as
+    /// generated by Minecraft's data generator, such as for sending the
+    /// commands to clients.
+    ///
+    /// Disabled commands are left out.
+    #[cfg(feature = "serde")]
+    pub fn to_brigadier_json(&self) -> serde_json::Value {
+        use serde_json::{Map, Value};
+
+        fn children<C: Context>(
+            dispatcher: &CommandDispatcher<C>,
+            keys: impl Iterator<Item = NodeKey>,
+        ) -> Map<String, Value> {
+            keys.map(|key| {
+                let node = &dispatcher.nodes[*key];
+                let mut json = Map::new();
+                let name = match &node.argument {
+                    Argument::Literal { value } => {
+                        json.insert("type".into(), "literal".into());
+                        value.to_string()
+                    }
+                    Argument::Parser { name, checker, .. } => {
+                        let (parser, properties) = checker.brigadier();
+                        json.insert("type".into(), "argument".into());
+                        json.insert("parser".into(), parser.into());
+                        if let Some(properties) = properties {
+                            json.insert("properties".into(), properties);
+                        }
+                        name.to_string()
+                    }
+                };
+                let grandchildren = children(dispatcher, node.children.iter().copied());
+                if !grandchildren.is_empty() {
+                    json.insert("children".into(), Value::Object(grandchildren));
+                }
+                if !node.execs.is_empty() {
+                    json.insert("executable".into(), true.into());
+                }
+                (name, Value::Object(json))
+            })
+            .collect()
+        }
+
+        serde_json::json!({
+            "type": "root",
+            "children": children(self, self.root_keys().copied()),
+        })
+    }
+
+    /// Registers the commands described by `data`, either a single command
+    /// or an array of them, in the format of `HandlerRegistry::build`.
+    ///
+    /// Commands before the first one which fails to register stay registered.
+    #[cfg(feature = "serde")]
+    pub fn register_json(
+        &mut self,
+        data: &serde_json::Value,
+        handlers: &HandlerRegistry<C>,
+    ) -> Result<(), RegisterError>
+    where
+        C: 'static,
+    {
+        match data {
+            serde_json::Value::Array(commands) => commands
+                .iter()
+                .try_for_each(|command| self.register(handlers.build(command)?)),
+            command => self.register(handlers.build(command)?),
+        }
+    }
+
+    /// Returns the problems found while registering commands which did not
+    /// prevent them from being registered.
+    pub fn warnings(&self) -> &[RegisterWarning] {
+        &self.warnings
+    }
+
+    /// Returns a row for every registered command, sorted by usage, such as
+    /// for an admin interface to list commands. Unlike the Brigadier
+    /// format, the rows do not depend on how commands share nodes.
+    #[cfg(feature = "serde")]
+    pub fn command_table(&self) -> Vec<CommandTableEntry> {
+        let mut table: Vec<CommandTableEntry> = self
+            .commands
+            .iter()
+            .map(|command| CommandTableEntry {
+                name: command
+                    .arguments
+                    .first()
+                    .map(ToString::to_string)
+                    .unwrap_or_default(),
+                usage: command.path().to_string(),
+                description: command.description.as_ref().map(ToString::to_string),
+                permissions: command
+                    .permissions
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect(),
+                arguments: command
+                    .arguments
+                    .iter()
+                    .filter_map(|argument| match argument {
+                        Argument::Parser {
+                            name,
+                            checker,
+                            optional,
+                            ..
+                        } => Some(ArgumentDescriptor {
+                            name: name.to_string(),
+                            parser: checker.brigadier().0.to_owned(),
+                            optional: *optional,
+                        }),
+                        Argument::Literal { .. } => None,
+                    })
+                    .collect(),
+            })
+            .collect();
+        table.sort_by(|a, b| a.usage.cmp(&b.usage));
+        table
+    }
+
+    /// Returns the number of argument nodes in the command graph.
+    ///
+    /// Commands share the nodes of their common prefix, so this is usually
+    /// less than the total number of arguments of all commands.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns the number of registered commands.
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    /// Returns whether no commands are registered.
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    pub fn commands(&self) -> impl Iterator<Item = &CommandSpec<C>> {
+        self.commands.iter()
+    }
+
+    /// Returns the registered commands like `commands`, sorted
+    /// alphabetically by their path, such as for stable help output.
+    pub fn sorted_commands(&self) -> Vec<&CommandSpec<C>> {
+        let mut commands: Vec<_> = self.commands.iter().collect();
+        commands.sort_by_cached_key(|command| command.path());
+        commands
+    }
+
+    /// Returns one page of the usage messages of the commands `ctx` holds
+    /// all permissions for and whose condition holds, sorted alphabetically.
+    /// Pages start at 1.
+    pub fn help_page(&self, ctx: &C, page: usize, per_page: usize) -> Vec<String> {
+        let mut usages: Vec<String> = self
+            .commands
+            .iter()
+            .filter(|command| {
+                command
+                    .permissions
+                    .iter()
+                    .all(|permission| ctx.has_permission(permission))
+            })
+            .filter(|command| match command.arguments.first() {
+                Some(Argument::Literal { value }) => self.condition_holds(value, ctx),
+                _ => true,
+            })
+            .map(|command| self.usage(ctx, command))
+            .collect();
+        usages.sort();
+        usages.dedup();
+
+        match page.checked_sub(1) {
+            Some(page) => usages
+                .into_iter()
+                .skip(page.saturating_mul(per_page))
+                .take(per_page)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns the name of every enabled top-level command, such as `tp`,
+    /// in the order `dispatch` tries them.
+    pub fn roots(&self) -> impl Iterator<Item = &str> {
+        self.root_keys()
+            .filter_map(move |key| match &self.nodes[**key].argument {
+                Argument::Literal { value } => Some(value.as_ref()),
+                Argument::Parser { .. } => None,
+            })
+    }
+
+    /// Returns the path to every executable node of the command graph,
+    /// in the order `dispatch` tries them.
+    pub fn walk(&self) -> Vec<CommandPath> {
+        self.walk_with(&|argument| argument.to_string())
+    }
+
+    /// Returns the path to every executable node like `walk`, with the type
+    /// of each parser argument in its label, such as `<x:i32>`, as for
+    /// generating documentation.
+    pub fn walk_typed(&self) -> Vec<CommandPath> {
+        self.walk_with(&Argument::typed_label)
+    }
+
+    fn walk_with(&self, label: &dyn Fn(&Argument<C>) -> String) -> Vec<CommandPath> {
+        fn walk_node<C: Context>(
+            dispatcher: &CommandDispatcher<C>,
+            key: NodeKey,
+            label: &dyn Fn(&Argument<C>) -> String,
+            path: &mut Vec<String>,
+            paths: &mut Vec<CommandPath>,
+        ) {
+            let node = &dispatcher.nodes[*key];
+            path.push(label(&node.argument));
+            if !node.execs.is_empty() {
+                paths.push(CommandPath(path.clone()));
+            }
+            for child in &node.children {
+                walk_node(dispatcher, *child, label, path, paths);
+            }
+            path.pop();
+        }
+
+        let mut paths = Vec::new();
+        for child in &self.children {
+            walk_node(self, *child, label, &mut Vec::new(), &mut paths);
+        }
+        paths
+    }
+
+    /// Computes what changed going from this dispatcher to `other`, so that
+    /// only the difference needs to be sent to clients after a reload.
+    pub fn diff(&self, other: &CommandDispatcher<C>) -> TreeDiff {
+        let old = self.walk();
+        let new = other.walk();
+
+        let mut diff = TreeDiff {
+            added: new
+                .iter()
+                .filter(|path| !old.contains(path))
+                .cloned()
+                .collect(),
+            removed: old
+                .iter()
+                .filter(|path| !new.contains(path))
+                .cloned()
+                .collect(),
+            changed: Vec::new(),
+        };
+
+        for command in &self.commands {
+            let path = command.path();
+            let changed = other.commands.iter().any(|other| {
+                other.path() == path
+                    && (other.description != command.description
+                        || other.permissions != command.permissions
+                        || other.deprecated != command.deprecated)
+            });
+            if changed && !diff.changed.contains(&path) {
+                diff.changed.push(path);
+            }
+        }
+
+        diff
+    }
+
+    /// Returns whether `other` has the same command graph, node by node:
+    /// the same arguments in the same order, executing commands with the
+    /// same description, permissions and deprecation. What the commands do
+    /// is not compared. This makes golden tests of registration possible.
+    pub fn structurally_eq(&self, other: &CommandDispatcher<C>) -> bool {
+        fn nodes_eq<C: Context>(
+            a: &CommandDispatcher<C>,
+            a_keys: &[NodeKey],
+            b: &CommandDispatcher<C>,
+            b_keys: &[NodeKey],
+        ) -> bool {
+            a_keys.len() == b_keys.len()
+                && a_keys.iter().zip(b_keys).all(|(a_key, b_key)| {
+                    let (a_node, b_node) = (&a.nodes[**a_key], &b.nodes[**b_key]);
+                    a_node.argument == b_node.argument
+                        && a_node.argument.to_string() == b_node.argument.to_string()
+                        && a_node.enabled == b_node.enabled
+                        && a_node.execs.len() == b_node.execs.len()
+                        && a_node.execs.iter().zip(&b_node.execs).all(|(i, j)| {
+                            let (a_spec, b_spec) = (&a.commands[*i], &b.commands[*j]);
+                            a_spec.description == b_spec.description
+                                && a_spec.permissions == b_spec.permissions
+                                && a_spec.deprecated == b_spec.deprecated
+                                && a_spec.named == b_spec.named
+                        })
+                        && nodes_eq(a, &a_node.children, b, &b_node.children)
+                })
+        }
+
+        nodes_eq(self, &self.children, other, &other.children)
+    }
+
+    /// Renders the command graph as a Graphviz DOT digraph. Literals are
+    /// drawn as boxes, parsers as ellipses, and executable nodes get a
+    /// double border.
+    pub fn to_dot(&self) -> String {
+        use std::fmt::Write;
+
+        fn escape(label: &str) -> String {
+            label.replace('\\', "\\\\").replace('"', "\\\"")
+        }
+
+        let mut dot = String::from("digraph commands {\n    root [shape=point];\n");
+        for (key, node) in &self.nodes {
+            let shape = match node.argument {
+                Argument::Literal { .. } => "box",
+                Argument::Parser { .. } => "ellipse",
+            };
+            let _ = write!(
+                dot,
+                "    n{} [label=\"{}\", shape={}",
+                key,
+                escape(&node.argument.to_string()),
+                shape
+            );
+            if !node.execs.is_empty() {
+                dot.push_str(", peripheries=2");
+            }
+            dot.push_str("];\n");
+        }
+        for child in &self.children {
+            let _ = writeln!(dot, "    root -> n{};", **child);
+        }
+        for (key, node) in &self.nodes {
+            for child in &node.children {
+                let _ = writeln!(dot, "    n{} -> n{};", key, **child);
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+impl<C: Context> fmt::Debug for CommandDispatcher<C> {
+    /// Renders the command graph as an indented tree. Executable nodes
+    /// are marked with `*` followed by one `<exec>` per executable.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn fmt_node<C: Context>(
+            dispatcher: &CommandDispatcher<C>,
+            f: &mut fmt::Formatter<'_>,
+            key: NodeKey,
+            depth: usize,
+        ) -> fmt::Result {
+            let node = &dispatcher.nodes[*key];
+            write!(f, "{:indent$}{}", "", node.argument, indent = depth * 2)?;
+            if !node.execs.is_empty() {
+                f.write_str(" *")?;
+                for _ in &node.execs {
+                    f.write_str(" <exec>")?;
+                }
+            }
+            writeln!(f)?;
+            for child in &node.children {
+                fmt_node(dispatcher, f, *child, depth + 1)?;
+            }
+            Ok(())
+        }
+
+        for child in &self.children {
+            fmt_node(self, f, *child, 0)?;
+        }
+        Ok(())
+    }
+}
+
+/// Result of `try_dispatch`.
+pub enum DispatchOutcome<'s, 'a, C: Context> {
+    /// No command matches the input.
+    NoMatch,
+    /// Some command matched, and is ready to run.
+    Matched(PendingExec<'s, 'a, C>),
+}
+
+/// A matched command, waiting for mutable access to the context to run.
+pub struct PendingExec<'s, 'a, C: Context> {
+    dispatcher: &'s CommandDispatcher<C>,
+    command: Cow<'a, str>,
+    executables: SmallVec<[NodeKey; 4]>,
+}
+
+impl<'s, 'a, C: Context> PendingExec<'s, 'a, C> {
+    /// Runs the matched executables like `dispatch` until one succeeds,
+    /// along with the middleware.
+    pub async fn run<'c>(
+        self,
+        errors: &'c mut Vec<C::Error>,
+        ctx: &mut C,
+    ) -> Result<C::Ok, &'c Vec<C::Error>> {
+        errors.clear();
+        let dispatcher = self.dispatcher;
+        let command = &*self.command;
+
+        let passed = dispatcher.before_middleware(ctx, command);
+        let mut result = Err(());
+        if passed == dispatcher.middleware.len() {
+            'executables: for node_key in self.executables {
+                for index in &dispatcher.nodes[*node_key].execs {
+                    match dispatcher.prepare_exec(*index).call(ctx, command).await {
+                        Ok(ok) => {
+                            result = Ok(ok);
+                            break 'executables;
+                        }
+                        Err(err) => errors.push(err),
+                    }
+                }
+            }
+        }
+        dispatcher.after_middleware(passed, ctx, command, result.is_ok());
+        match result {
+            Ok(ok) => Ok(ok),
+            Err(()) => Err(&*errors),
+        }
+    }
+}
+
+/// Read-only view of a node on the command graph, returned by `find`.
+pub struct NodeRef<'d, C: Context> {
+    dispatcher: &'d CommandDispatcher<C>,
+    key: NodeKey,
+}
+
+impl<'d, C: Context> NodeRef<'d, C> {
+    /// Returns the argument this node matches.
+    pub fn argument(&self) -> &'d Argument<C> {
+        &self.node().argument
+    }
+
+    /// Returns the children of this node, in the order dispatch tries them.
+    pub fn children(&self) -> impl Iterator<Item = NodeRef<'d, C>> + 'd {
+        let dispatcher = self.dispatcher;
+        self.node().children.iter().map(move |key| NodeRef {
+            dispatcher,
+            key: *key,
+        })
+    }
+
+    /// Returns whether some command can be executed at this node.
+    pub fn is_executable(&self) -> bool {
+        !self.node().execs.is_empty()
+    }
+
+    /// Returns the commands executable at this node.
+    pub fn commands(&self) -> impl Iterator<Item = &'d CommandSpec<C>> + 'd {
+        let commands = &self.dispatcher.commands;
+        self.node().execs.iter().map(move |index| &commands[*index])
+    }
+
+    fn node(&self) -> &'d Node<C> {
+        &self.dispatcher.nodes[*self.key]
+    }
+}
+
+/// Splits `line` into commands at each `;` outside of quoted arguments,
+/// in which `\"` does not end the quote, like with `StringArg`.
+fn split_commands(line: &str) -> impl Iterator<Item = &str> {
+    let mut rest = Some(line);
+    std::iter::from_fn(move || {
+        let line = rest?;
+        let mut in_string = false;
+        let mut escaped = false;
+        for (i, c) in line.char_indices() {
+            if in_string {
+                match c {
+                    _ if escaped => escaped = false,
+                    '\\' => escaped = true,
+                    '"' => in_string = false,
+                    _ => (),
+                }
+            } else if c == '"' {
+                in_string = true;
+            } else if c == ';' {
+                rest = Some(&line[i + 1..]);
+                return Some(&line[..i]);
+            }
+        }
+        rest = None;
+        Some(line)
+    })
+}
+
+/// Symbols of the input tokens looked up while matching one command,
+/// keyed by the length of the input remaining in front of the token.
+///
+/// Each token is thus looked up at most once, no matter how many sibling
+/// literals it is compared against.
+struct Tokens<'s> {
+    symbols: &'s HashMap<Cow<'static, str>, u32>,
+    seen: SmallVec<[(usize, Option<u32>); 8]>,
+}
+
+impl<'s> Tokens<'s> {
+    fn new(symbols: &'s HashMap<Cow<'static, str>, u32>) -> Self {
+        Self {
+            symbols,
+            seen: SmallVec::new(),
+        }
+    }
+
+    /// Consumes the next token of `input`, returning whether it is the
+    /// literal interned as `symbol`.
+    fn matches(&mut self, symbol: Option<u32>, input: &mut &str) -> bool {
+        let remaining = input.len();
+        let token = input.advance_until(" ");
+        let interned = match self.seen.iter().find(|(len, _)| *len == remaining) {
+            Some((_, interned)) => *interned,
+            None => {
+                let interned = self.symbols.get(token).copied();
+                self.seen.push((remaining, interned));
+                interned
+            }
+        };
+        symbol.is_some() && interned == symbol
+    }
+}
+
+/// Bounded map from inputs to the executable node they matched, evicting
+/// the least recently used input once full.
+struct MatchCache {
+    capacity: usize,
+    /// Matched node of each input, along with the tick it was last used at.
+    entries: HashMap<String, (NodeKey, u64)>,
+    tick: u64,
+}
+
+impl MatchCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::with_capacity(capacity),
+            tick: 0,
+        }
+    }
+
+    fn get(&mut self, input: &str) -> Option<NodeKey> {
+        self.tick += 1;
+        let tick = self.tick;
+        self.entries.get_mut(input).map(|(key, used)| {
+            *used = tick;
+            *key
+        })
+    }
+
+    fn insert(&mut self, input: &str, key: NodeKey) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(input) {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(input, _)| input.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.tick += 1;
+        self.entries.insert(input.to_owned(), (key, self.tick));
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
     }
 }
 
@@ -166,7 +2365,11 @@ where
 struct Node<C: Context> {
     children: SmallVec<[NodeKey; 4]>,
     argument: Argument<C>,
-    execs: Vec<Exec<C>>,
+    /// Symbol id of a literal argument.
+    symbol: Option<u32>,
+    /// Indices into `CommandDispatcher::commands` of the commands executable here.
+    execs: Vec<usize>,
+    enabled: bool,
 }
 
 impl<C: Context> From<Argument<C>> for Node<C> {
@@ -174,7 +2377,9 @@ impl<C: Context> From<Argument<C>> for Node<C> {
         Node {
             children: Default::default(),
             argument,
+            symbol: None,
             execs: Vec::new(),
+            enabled: true,
         }
     }
 }
@@ -1,14 +1,44 @@
 mod command;
+#[cfg(feature = "serde")]
+mod data;
 mod dispatcher;
 mod parser;
 
-pub use command::{Argument, Command, CommandSpec};
-pub use dispatcher::CommandDispatcher;
-pub use lieutenant_macros::{command, provider};
-pub use parser::{parsers, ArgumentChecker, ArgumentKind, ArgumentParser, ParserUtil, Provider};
+pub use command::{
+    Argument, Command, CommandPath, CommandSpec, Exec, ExecMut, Executable, FromExtract,
+    ParserChain, Trailing, Values,
+};
+#[cfg(feature = "serde")]
+pub use data::{ArgumentFactory, ArgumentRegistry, HandlerRegistry};
+pub use dispatcher::{
+    AliasError, ArgSpan, CommandDispatcher, DispatchError, DispatchFailure, DispatchOutcome,
+    DispatchSuccess, DispatchTrace, InputContext, Middleware, NodeRef, ParseAnalysis, PendingExec,
+    RegisterError, RegisterWarning, TreeDiff, Utf8OrDispatchError,
+};
+#[cfg(feature = "serde")]
+pub use dispatcher::{ArgumentDescriptor, CommandTableEntry};
+pub use lieutenant_macros::{command, command_spec, provider, Command, FromExtract};
+pub use parser::{
+    parsers, ArgumentChecker, ArgumentKind, ArgumentParser, BoxedParser, Completion, ParserUtil,
+    Provider,
+};
 
 /// Denotes a type that may be passed to commands as input.
 pub trait Context: Send + Sync + 'static {
     type Error: std::error::Error + Send + Sync;
     type Ok;
+
+    /// Returns whether this context holds the given permission node,
+    /// such as `admin.stop`. Defaults to granting every permission.
+    fn has_permission(&self, _permission: &str) -> bool {
+        true
+    }
+}
+
+/// A context which commands can write feedback messages to, such as
+/// `Teleported to 1 2 3`, for the dispatcher to collect instead of the
+/// command sending them itself.
+pub trait Feedback: Context {
+    /// Returns the messages written so far.
+    fn feedback(&mut self) -> &mut Vec<String>;
 }
@@ -5,27 +5,135 @@ use std::pin::Pin;
 
 pub trait ParserUtil {
     /// Advances the pointer until the given pattern and returns head and leaving the tail.
-    fn advance_until<'a, 'b>(&'a mut self, pat: &'b str) -> &'a str;
+    fn advance_until<'a>(&'a mut self, pat: &str) -> &'a str;
+
+    /// Advances the pointer past a single bracket- and quote-aware value,
+    /// such as `{"a": [1, 2]}` or `"a string"`, and returns it. Values not
+    /// starting with a bracket or quote end at the next space, like with
+    /// `advance_until(" ")`.
+    ///
+    /// Returns `None`, leaving the pointer untouched, if brackets or quotes
+    /// are left unbalanced.
+    fn advance_balanced(&mut self) -> Option<&str>;
 }
 
 impl ParserUtil for &str {
     #[inline]
-    fn advance_until<'a, 'b>(&'a mut self, pat: &'b str) -> &'a str {
+    fn advance_until<'a>(&'a mut self, pat: &str) -> &'a str {
         let head = self.split(pat).next().unwrap_or("");
         *self = &self[(head.len() + pat.len()).min(self.len())..];
         head
     }
+
+    fn advance_balanced(&mut self) -> Option<&str> {
+        match self.chars().next() {
+            Some('{') | Some('[') | Some('"') => (),
+            _ => return Some(self.advance_until(" ")),
+        }
+
+        let mut depth = 0usize;
+        let mut in_string = false;
+        let mut escaped = false;
+        let mut end = None;
+        for (i, c) in self.char_indices() {
+            if in_string {
+                match c {
+                    _ if escaped => escaped = false,
+                    '\\' => escaped = true,
+                    '"' => in_string = false,
+                    _ => (),
+                }
+            } else {
+                match c {
+                    '"' => in_string = true,
+                    '{' | '[' => depth += 1,
+                    '}' | ']' => depth = depth.checked_sub(1)?,
+                    _ => (),
+                }
+            }
+
+            if depth == 0 && !in_string {
+                end = Some(i + c.len_utf8());
+                break;
+            }
+        }
+
+        let end = end?;
+        let head = &self[..end];
+        let tail = &self[end..];
+        *self = tail.strip_prefix(' ').unwrap_or(tail);
+        Some(head)
+    }
+}
+
+/// A suggested completion for the word being typed.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Completion {
+    /// Text replacing the word being typed.
+    pub text: String,
+    /// Extra information shown beside the completion by rich clients,
+    /// such as a player's display name.
+    pub tooltip: Option<String>,
+}
+
+impl Completion {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            tooltip: None,
+        }
+    }
+
+    pub fn with_tooltip(mut self, tooltip: impl Into<String>) -> Self {
+        self.tooltip = Some(tooltip.into());
+        self
+    }
+
+    /// Scores how well `text` completes the word `partial`, for ranking
+    /// completions with `CommandDispatcher::with_ranking`. Completions
+    /// starting with `partial` rank first, then those containing it, then
+    /// those containing its characters in order, such as `stop` for `sp`.
+    pub fn rank(partial: &str, text: &str) -> Option<u32> {
+        if text.starts_with(partial) {
+            Some(3)
+        } else if text.contains(partial) {
+            Some(2)
+        } else {
+            let mut chars = text.chars();
+            partial.chars().all(|c| chars.any(|t| t == c)).then_some(1)
+        }
+    }
+}
+
+impl From<String> for Completion {
+    fn from(text: String) -> Self {
+        Self::new(text)
+    }
+}
+
+impl From<&str> for Completion {
+    fn from(text: &str) -> Self {
+        Self::new(text)
+    }
 }
 
 pub trait Provider<C: Context> {
     type Output: Sized;
-    fn provide<'a>(ctx: &C) -> Pin<Box<dyn Future<Output = Result<Self::Output, C::Error>> + Send + Sync + 'a>>;
+    #[allow(clippy::type_complexity)]
+    fn provide<'a>(
+        ctx: &C,
+    ) -> Pin<Box<dyn Future<Output = Result<Self::Output, C::Error>> + Send + Sync + 'a>>;
 }
 
 pub trait ArgumentChecker<C: Context>: Any + Send + Sync + 'static {
+    /// Returns whether `input` starts with a valid value of this argument,
+    /// advancing `input` past it.
+    ///
+    /// Checkers may await here, such as to look a player up in a database;
+    /// dispatch awaits each checker before trying the next argument.
     fn satisfies<'a, 'b>(
-        &self,
-        ctx: &C,
+        &'a self,
+        ctx: &'a C,
         input: &'a mut &'b str,
     ) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>>;
     /// Returns whether this `ArgumentChecker` will perform
@@ -40,14 +148,61 @@ pub trait ArgumentChecker<C: Context>: Any + Send + Sync + 'static {
         Self: Sized;
 
     fn box_clone(&self) -> Box<dyn ArgumentChecker<C>>;
+
+    /// Returns completions for `partial`, the incomplete word being typed
+    /// for this argument. Each completion replaces `partial` in full.
+    fn complete<'a>(
+        &'a self,
+        _ctx: &'a C,
+        _partial: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Vec<Completion>> + Send + 'a>> {
+        Box::pin(async { Vec::new() })
+    }
+
+    /// Returns the identifier of the Brigadier parser, such as
+    /// `brigadier:integer`, and its properties, such as `{"min": 0}`, which
+    /// Minecraft clients use to validate this argument themselves.
+    ///
+    /// Defaults to a single word string.
+    #[cfg(feature = "serde")]
+    fn brigadier(&self) -> (&'static str, Option<serde_json::Value>) {
+        (
+            "brigadier:string",
+            Some(serde_json::json!({ "type": "word" })),
+        )
+    }
+
+    /// Returns whether this argument consumes the rest of the input, so
+    /// that no argument may follow it.
+    fn is_greedy(&self) -> bool {
+        false
+    }
+
+    /// Returns a short name for the type of this argument, such as `i32`,
+    /// as shown in labels like `<x:i32>`.
+    ///
+    /// Defaults to the name of the checker type without its `Checker`
+    /// suffix, such as `StringArg` for `StringArgChecker`.
+    fn type_label(&self) -> &'static str {
+        let name = short_type_name(std::any::type_name::<Self>());
+        name.strip_suffix("Checker").unwrap_or(name)
+    }
+}
+
+/// Returns the name of a type without its path or generic arguments, such
+/// as `Flags` for `lieutenant::parsers::Flags<Spec>`.
+fn short_type_name(name: &'static str) -> &'static str {
+    let name = name.split('<').next().unwrap_or(name);
+    name.rsplit("::").next().unwrap_or(name)
 }
 
 pub trait ArgumentParser<C: Context>: Send + Sync + 'static {
     type Output: Send + Sync;
 
+    #[allow(clippy::type_complexity)]
     fn parse<'a, 'b>(
-        &self,
-        ctx: &mut C,
+        &'a self,
+        ctx: &'a mut C,
         input: &'a mut &'b str,
     ) -> Pin<Box<dyn Future<Output = Result<Self::Output, C::Error>> + Send + Sync + 'a>>;
     fn default() -> Self
@@ -55,6 +210,77 @@ pub trait ArgumentParser<C: Context>: Send + Sync + 'static {
         Self: Sized;
 }
 
+/// An `ArgumentParser` with its output type erased, so that parsers of
+/// different types can be stored together, such as to build commands from
+/// a description loaded at runtime.
+pub struct BoxedParser<C: Context> {
+    parser: Box<dyn ErasedParser<C>>,
+}
+
+impl<C: Context> BoxedParser<C> {
+    pub fn new<P>(parser: P) -> Self
+    where
+        P: ArgumentParser<C>,
+        P::Output: 'static,
+    {
+        Self {
+            parser: Box::new(parser),
+        }
+    }
+
+    /// Boxes the default parser of the argument kind `K`.
+    pub fn of<K>() -> Self
+    where
+        K: ArgumentKind<C> + 'static,
+    {
+        Self::new(K::Parser::default())
+    }
+
+    /// Parses the next argument like the boxed parser, returning its output
+    /// to be downcast by the caller.
+    #[allow(clippy::type_complexity)]
+    pub fn parse<'a, 'b>(
+        &'a self,
+        ctx: &'a mut C,
+        input: &'a mut &'b str,
+    ) -> Pin<
+        Box<dyn Future<Output = Result<Box<dyn Any + Send + Sync>, C::Error>> + Send + Sync + 'a>,
+    > {
+        self.parser.parse_erased(ctx, input)
+    }
+}
+
+trait ErasedParser<C: Context>: Send + Sync {
+    #[allow(clippy::type_complexity)]
+    fn parse_erased<'a, 'b>(
+        &'a self,
+        ctx: &'a mut C,
+        input: &'a mut &'b str,
+    ) -> Pin<
+        Box<dyn Future<Output = Result<Box<dyn Any + Send + Sync>, C::Error>> + Send + Sync + 'a>,
+    >;
+}
+
+impl<C, P> ErasedParser<C> for P
+where
+    C: Context,
+    P: ArgumentParser<C>,
+    P::Output: 'static,
+{
+    fn parse_erased<'a, 'b>(
+        &'a self,
+        ctx: &'a mut C,
+        input: &'a mut &'b str,
+    ) -> Pin<
+        Box<dyn Future<Output = Result<Box<dyn Any + Send + Sync>, C::Error>> + Send + Sync + 'a>,
+    > {
+        Box::pin(async move {
+            let output = self.parse(ctx, input).await?;
+            Ok(Box::new(output) as Box<dyn Any + Send + Sync>)
+        })
+    }
+}
+
 pub trait ArgumentKind<C: Context>: Sized + Send + Sync {
     type Checker: ArgumentChecker<C>;
     type Parser: ArgumentParser<C, Output = Self>;
@@ -86,8 +312,8 @@ pub mod parsers {
         T: FromStr + Clone + Send + Sync + 'static,
     {
         fn satisfies<'a, 'b>(
-            &self,
-            _ctx: &C,
+            &'a self,
+            _ctx: &'a C,
             input: &'a mut &'b str,
         ) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>> {
             Box::pin(async move {
@@ -110,6 +336,41 @@ pub mod parsers {
         fn box_clone(&self) -> Box<dyn ArgumentChecker<C>> {
             Box::new(self.clone())
         }
+
+        fn type_label(&self) -> &'static str {
+            short_type_name(std::any::type_name::<T>())
+        }
+
+        #[cfg(feature = "serde")]
+        fn brigadier(&self) -> (&'static str, Option<serde_json::Value>) {
+            use std::any::TypeId;
+
+            let id = TypeId::of::<T>();
+            let parser = if id == TypeId::of::<bool>() {
+                "brigadier:bool"
+            } else if id == TypeId::of::<i8>()
+                || id == TypeId::of::<i16>()
+                || id == TypeId::of::<i32>()
+                || id == TypeId::of::<u8>()
+                || id == TypeId::of::<u16>()
+            {
+                "brigadier:integer"
+            } else if id == TypeId::of::<i64>() || id == TypeId::of::<u32>() {
+                "brigadier:long"
+            } else if id == TypeId::of::<f32>() {
+                "brigadier:float"
+            } else if id == TypeId::of::<f64>() {
+                "brigadier:double"
+            } else if id == TypeId::of::<ResourceLocation>() {
+                "minecraft:resource_location"
+            } else {
+                return (
+                    "brigadier:string",
+                    Some(serde_json::json!({ "type": "word" })),
+                );
+            };
+            (parser, None)
+        }
     }
 
     #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -135,8 +396,8 @@ pub mod parsers {
         type Output = T;
 
         fn parse<'a, 'b>(
-            &self,
-            _ctx: &mut C,
+            &'a self,
+            _ctx: &'a mut C,
             input: &'a mut &'b str,
         ) -> Pin<Box<dyn Future<Output = Result<Self::Output, C::Error>> + Send + Sync + 'a>>
         {
@@ -154,49 +415,4170 @@ pub mod parsers {
         }
     }
 
-    macro_rules! from_str_argument_kind {
-        ($($ty:ty,)*) => {
-            $(
-                impl <C> ArgumentKind<C> for $ty
-                where
-                    C: Context,
-                    C::Error: From<<$ty as FromStr>::Err>,
-                {
-                    type Checker = FromStrChecker<Self>;
-                    type Parser = FromStrParser<Self>;
+    /// Parser for optional arguments, yielding `None` once the input is exhausted.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    pub struct OptionParser<P> {
+        parser: P,
+    }
+
+    impl<C, P> ArgumentParser<C> for OptionParser<P>
+    where
+        C: Context,
+        P: ArgumentParser<C>,
+    {
+        type Output = Option<P::Output>;
+
+        fn parse<'a, 'b>(
+            &'a self,
+            ctx: &'a mut C,
+            input: &'a mut &'b str,
+        ) -> Pin<Box<dyn Future<Output = Result<Self::Output, C::Error>> + Send + Sync + 'a>>
+        {
+            Box::pin(async move {
+                if input.is_empty() {
+                    Ok(None)
+                } else {
+                    Ok(Some(self.parser.parse(ctx, input).await?))
                 }
-            )*
+            })
+        }
+
+        fn default() -> Self
+        where
+            Self: Sized,
+        {
+            Self {
+                parser: P::default(),
+            }
         }
     }
 
-    from_str_argument_kind!(
-        i8,
-        i16,
-        i32,
-        i64,
-        i128,
-        isize,
-        u8,
-        u16,
-        u32,
-        u64,
-        usize,
-        f32,
-        f64,
-        u128,
-        String,
-        bool,
-        char,
-        NonZeroI8,
-        NonZeroI16,
-        NonZeroI32,
-        NonZeroI64,
-        NonZeroIsize,
-        NonZeroU8,
-        NonZeroU16,
-        NonZeroU32,
-        NonZeroU64,
-        NonZeroUsize,
-        PathBuf,
-    );
+    impl<C, T> ArgumentKind<C> for Option<T>
+    where
+        C: Context,
+        T: ArgumentKind<C>,
+    {
+        type Checker = T::Checker;
+        type Parser = OptionParser<T::Parser>;
+    }
+
+    /// A list of values within a single word, separated by `SEP`,
+    /// such as `alice,bob,carol`. Empty values are rejected.
+    #[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+    pub struct Separated<T, const SEP: char = ','>(pub Vec<T>);
+
+    impl<T, const SEP: char> Separated<T, SEP> {
+        pub fn into_inner(self) -> Vec<T> {
+            self.0
+        }
+    }
+
+    impl<T, const SEP: char> std::ops::Deref for Separated<T, SEP> {
+        type Target = Vec<T>;
+
+        fn deref(&self) -> &Self::Target {
+            &self.0
+        }
+    }
+
+    impl<T, const SEP: char> std::ops::DerefMut for Separated<T, SEP> {
+        fn deref_mut(&mut self) -> &mut Self::Target {
+            &mut self.0
+        }
+    }
+
+    impl<T, const SEP: char> From<Separated<T, SEP>> for Vec<T> {
+        fn from(separated: Separated<T, SEP>) -> Self {
+            separated.0
+        }
+    }
+
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    pub struct SeparatedChecker<K, const SEP: char> {
+        checker: K,
+    }
+
+    impl<C, K, const SEP: char> ArgumentChecker<C> for SeparatedChecker<K, SEP>
+    where
+        C: Context,
+        K: ArgumentChecker<C> + Clone,
+    {
+        fn satisfies<'a, 'b>(
+            &'a self,
+            ctx: &'a C,
+            input: &'a mut &'b str,
+        ) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>> {
+            Box::pin(async move {
+                let head = input.advance_until(" ");
+                for mut piece in head.split(SEP) {
+                    if piece.is_empty()
+                        || !self.checker.satisfies(ctx, &mut piece).await
+                        || !piece.is_empty()
+                    {
+                        return false;
+                    }
+                }
+                true
+            })
+        }
+
+        fn equals(&self, other: &dyn Any) -> bool {
+            other
+                .downcast_ref::<Self>()
+                .map(|other| self.checker.equals(&other.checker))
+                .unwrap_or(false)
+        }
+
+        fn default() -> Self
+        where
+            Self: Sized,
+        {
+            Self {
+                checker: K::default(),
+            }
+        }
+
+        fn box_clone(&self) -> Box<dyn ArgumentChecker<C>> {
+            Box::new(self.clone())
+        }
+    }
+
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    pub struct SeparatedParser<P, const SEP: char> {
+        parser: P,
+    }
+
+    impl<C, P, const SEP: char> ArgumentParser<C> for SeparatedParser<P, SEP>
+    where
+        C: Context,
+        P: ArgumentParser<C>,
+    {
+        type Output = Separated<P::Output, SEP>;
+
+        fn parse<'a, 'b>(
+            &'a self,
+            ctx: &'a mut C,
+            input: &'a mut &'b str,
+        ) -> Pin<Box<dyn Future<Output = Result<Self::Output, C::Error>> + Send + Sync + 'a>>
+        {
+            Box::pin(async move {
+                let head = input.advance_until(" ");
+                let mut values = Vec::new();
+                for mut piece in head.split(SEP) {
+                    values.push(self.parser.parse(ctx, &mut piece).await?);
+                }
+                Ok(Separated(values))
+            })
+        }
+
+        fn default() -> Self
+        where
+            Self: Sized,
+        {
+            Self {
+                parser: P::default(),
+            }
+        }
+    }
+
+    impl<C, T, const SEP: char> ArgumentKind<C> for Separated<T, SEP>
+    where
+        C: Context,
+        T: ArgumentKind<C>,
+        T::Checker: Clone,
+    {
+        type Checker = SeparatedChecker<T::Checker, SEP>;
+        type Parser = SeparatedParser<T::Parser, SEP>;
+    }
+
+    /// Three values, such as coordinates, given either as three words
+    /// (`1 2 3`) or as a single comma-separated word (`1,2,3`).
+    #[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+    pub struct Coordinates<T> {
+        pub x: T,
+        pub y: T,
+        pub z: T,
+    }
+
+    /// Splits the three pieces of a `Coordinates` off the start of `input`.
+    fn coordinate_pieces<'b>(input: &mut &'b str) -> Option<[&'b str; 3]> {
+        fn word(s: &str) -> (&str, &str) {
+            match s.find(' ') {
+                Some(index) => (&s[..index], &s[index + 1..]),
+                None => (s, ""),
+            }
+        }
+
+        let (head, rest) = word(input);
+        let (pieces, rest): (Vec<&str>, &str) = if head.contains(',') {
+            (head.split(',').collect(), rest)
+        } else {
+            let (y, rest) = word(rest);
+            let (z, rest) = word(rest);
+            (vec![head, y, z], rest)
+        };
+
+        match pieces[..] {
+            [x, y, z] if !x.is_empty() && !y.is_empty() && !z.is_empty() => {
+                *input = rest;
+                Some([x, y, z])
+            }
+            _ => None,
+        }
+    }
+
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    pub struct CoordinatesChecker<K> {
+        checker: K,
+    }
+
+    impl<C, K> ArgumentChecker<C> for CoordinatesChecker<K>
+    where
+        C: Context,
+        K: ArgumentChecker<C> + Clone,
+    {
+        fn satisfies<'a, 'b>(
+            &'a self,
+            ctx: &'a C,
+            input: &'a mut &'b str,
+        ) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>> {
+            Box::pin(async move {
+                let pieces = match coordinate_pieces(input) {
+                    Some(pieces) => pieces,
+                    None => return false,
+                };
+                for mut piece in pieces.iter().copied() {
+                    if !self.checker.satisfies(ctx, &mut piece).await || !piece.is_empty() {
+                        return false;
+                    }
+                }
+                true
+            })
+        }
+
+        fn equals(&self, other: &dyn Any) -> bool {
+            other
+                .downcast_ref::<Self>()
+                .map(|other| self.checker.equals(&other.checker))
+                .unwrap_or(false)
+        }
+
+        fn default() -> Self
+        where
+            Self: Sized,
+        {
+            Self {
+                checker: K::default(),
+            }
+        }
+
+        fn box_clone(&self) -> Box<dyn ArgumentChecker<C>> {
+            Box::new(self.clone())
+        }
+
+        #[cfg(feature = "serde")]
+        fn brigadier(&self) -> (&'static str, Option<serde_json::Value>) {
+            match self.checker.brigadier().0 {
+                "brigadier:integer" | "brigadier:long" => ("minecraft:block_pos", None),
+                _ => ("minecraft:vec3", None),
+            }
+        }
+    }
+
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    pub struct CoordinatesParser<P> {
+        parser: P,
+    }
+
+    impl<C, P> ArgumentParser<C> for CoordinatesParser<P>
+    where
+        C: Context,
+        P: ArgumentParser<C>,
+    {
+        type Output = Coordinates<P::Output>;
+
+        fn parse<'a, 'b>(
+            &'a self,
+            ctx: &'a mut C,
+            input: &'a mut &'b str,
+        ) -> Pin<Box<dyn Future<Output = Result<Self::Output, C::Error>> + Send + Sync + 'a>>
+        {
+            Box::pin(async move {
+                // Malformed coordinates are rejected by the checker, and
+                // fail to parse as `P` as well.
+                let [mut x, mut y, mut z] =
+                    coordinate_pieces(input).unwrap_or_else(|| [std::mem::take(input), "", ""]);
+                Ok(Coordinates {
+                    x: self.parser.parse(ctx, &mut x).await?,
+                    y: self.parser.parse(ctx, &mut y).await?,
+                    z: self.parser.parse(ctx, &mut z).await?,
+                })
+            })
+        }
+
+        fn default() -> Self
+        where
+            Self: Sized,
+        {
+            Self {
+                parser: P::default(),
+            }
+        }
+    }
+
+    impl<C, T> ArgumentKind<C> for Coordinates<T>
+    where
+        C: Context,
+        T: ArgumentKind<C>,
+        T::Checker: Clone,
+    {
+        type Checker = CoordinatesChecker<T::Checker>;
+        type Parser = CoordinatesParser<T::Parser>;
+    }
+
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    pub struct ArrayChecker<K, const N: usize> {
+        checker: K,
+    }
+
+    impl<C, K, const N: usize> ArgumentChecker<C> for ArrayChecker<K, N>
+    where
+        C: Context,
+        K: ArgumentChecker<C> + Clone,
+    {
+        fn satisfies<'a, 'b>(
+            &'a self,
+            ctx: &'a C,
+            input: &'a mut &'b str,
+        ) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>> {
+            Box::pin(async move {
+                for _ in 0..N {
+                    if input.is_empty() || !self.checker.satisfies(ctx, input).await {
+                        return false;
+                    }
+                }
+                true
+            })
+        }
+
+        fn equals(&self, other: &dyn Any) -> bool {
+            other
+                .downcast_ref::<Self>()
+                .map(|other| self.checker.equals(&other.checker))
+                .unwrap_or(false)
+        }
+
+        fn default() -> Self
+        where
+            Self: Sized,
+        {
+            Self {
+                checker: K::default(),
+            }
+        }
+
+        fn box_clone(&self) -> Box<dyn ArgumentChecker<C>> {
+            Box::new(self.clone())
+        }
+    }
+
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    pub struct ArrayParser<P, const N: usize> {
+        parser: P,
+    }
+
+    impl<C, P, const N: usize> ArgumentParser<C> for ArrayParser<P, N>
+    where
+        C: Context,
+        P: ArgumentParser<C>,
+    {
+        type Output = [P::Output; N];
+
+        fn parse<'a, 'b>(
+            &'a self,
+            ctx: &'a mut C,
+            input: &'a mut &'b str,
+        ) -> Pin<Box<dyn Future<Output = Result<Self::Output, C::Error>> + Send + Sync + 'a>>
+        {
+            Box::pin(async move {
+                let mut values = Vec::with_capacity(N);
+                for _ in 0..N {
+                    values.push(self.parser.parse(ctx, input).await?);
+                }
+                match std::convert::TryFrom::try_from(values) {
+                    Ok(values) => Ok(values),
+                    Err(_) => unreachable!("parsed exactly N values"),
+                }
+            })
+        }
+
+        fn default() -> Self
+        where
+            Self: Sized,
+        {
+            Self {
+                parser: P::default(),
+            }
+        }
+    }
+
+    /// `N` values of `T` given as consecutive words, such as the three
+    /// integers of `setblock 1 2 3`.
+    impl<C, T, const N: usize> ArgumentKind<C> for [T; N]
+    where
+        C: Context,
+        T: ArgumentKind<C>,
+        T::Checker: Clone,
+    {
+        type Checker = ArrayChecker<T::Checker, N>;
+        type Parser = ArrayParser<T::Parser, N>;
+    }
+
+    pub use self::either::{Either, EitherChecker, EitherParser};
+
+    mod either {
+        use super::*;
+        use std::fmt;
+
+        /// A value matched like `A` if possible, and like `B` otherwise,
+        /// such as a player name or a selector.
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+        pub enum Either<A, B> {
+            A(A),
+            B(B),
+        }
+
+        impl<A, B> Either<A, B> {
+            /// Returns the value if it matched like `A`.
+            pub fn left(self) -> Option<A> {
+                match self {
+                    Either::A(a) => Some(a),
+                    Either::B(_) => None,
+                }
+            }
+
+            /// Returns the value if it matched like `B`.
+            pub fn right(self) -> Option<B> {
+                match self {
+                    Either::A(_) => None,
+                    Either::B(b) => Some(b),
+                }
+            }
+
+            pub fn as_ref(&self) -> Either<&A, &B> {
+                match self {
+                    Either::A(a) => Either::A(a),
+                    Either::B(b) => Either::B(b),
+                }
+            }
+
+            pub fn map_a<T>(self, f: impl FnOnce(A) -> T) -> Either<T, B> {
+                match self {
+                    Either::A(a) => Either::A(f(a)),
+                    Either::B(b) => Either::B(b),
+                }
+            }
+
+            pub fn map_b<T>(self, f: impl FnOnce(B) -> T) -> Either<A, T> {
+                match self {
+                    Either::A(a) => Either::A(a),
+                    Either::B(b) => Either::B(f(b)),
+                }
+            }
+        }
+
+        impl<T> Either<T, T> {
+            /// Returns the value, whichever way it matched.
+            pub fn into_inner(self) -> T {
+                match self {
+                    Either::A(value) | Either::B(value) => value,
+                }
+            }
+        }
+
+        impl<A, B> From<Result<A, B>> for Either<A, B> {
+            fn from(result: Result<A, B>) -> Self {
+                match result {
+                    Ok(a) => Either::A(a),
+                    Err(b) => Either::B(b),
+                }
+            }
+        }
+
+        impl<A, B> From<Either<A, B>> for Result<A, B> {
+            fn from(either: Either<A, B>) -> Self {
+                match either {
+                    Either::A(a) => Ok(a),
+                    Either::B(b) => Err(b),
+                }
+            }
+        }
+
+        impl<A: fmt::Display, B: fmt::Display> fmt::Display for Either<A, B> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                match self {
+                    Either::A(a) => a.fmt(f),
+                    Either::B(b) => b.fmt(f),
+                }
+            }
+        }
+
+        #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+        pub struct EitherChecker<A, B> {
+            a: A,
+            b: B,
+        }
+
+        impl<C, A, B> ArgumentChecker<C> for EitherChecker<A, B>
+        where
+            C: Context,
+            A: ArgumentChecker<C> + Clone,
+            B: ArgumentChecker<C> + Clone,
+        {
+            fn satisfies<'a, 'b>(
+                &'a self,
+                ctx: &'a C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>> {
+                Box::pin(async move {
+                    let mut attempt = *input;
+                    if self.a.satisfies(ctx, &mut attempt).await {
+                        *input = attempt;
+                        true
+                    } else {
+                        self.b.satisfies(ctx, input).await
+                    }
+                })
+            }
+
+            fn equals(&self, other: &dyn Any) -> bool {
+                other
+                    .downcast_ref::<Self>()
+                    .map(|other| self.a.equals(&other.a) && self.b.equals(&other.b))
+                    .unwrap_or(false)
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                Self {
+                    a: A::default(),
+                    b: B::default(),
+                }
+            }
+
+            fn box_clone(&self) -> Box<dyn ArgumentChecker<C>> {
+                Box::new(self.clone())
+            }
+
+            fn complete<'a>(
+                &'a self,
+                ctx: &'a C,
+                partial: &'a str,
+            ) -> Pin<Box<dyn Future<Output = Vec<Completion>> + Send + 'a>> {
+                Box::pin(async move {
+                    let mut completions = self.a.complete(ctx, partial).await;
+                    completions.extend(self.b.complete(ctx, partial).await);
+                    completions
+                })
+            }
+        }
+
+        /// Parses like `A` if possible, and like `B` otherwise.
+        #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+        pub struct EitherParser<A, B> {
+            a: A,
+            b: B,
+        }
+
+        impl<C, A, B> ArgumentParser<C> for EitherParser<A, B>
+        where
+            C: Context,
+            A: ArgumentParser<C>,
+            B: ArgumentParser<C>,
+        {
+            type Output = Either<A::Output, B::Output>;
+
+            fn parse<'a, 'b>(
+                &'a self,
+                ctx: &'a mut C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = Result<Self::Output, C::Error>> + Send + Sync + 'a>>
+            {
+                Box::pin(async move {
+                    let mut attempt = *input;
+                    if let Ok(a) = self.a.parse(ctx, &mut attempt).await {
+                        *input = attempt;
+                        return Ok(Either::A(a));
+                    }
+                    Ok(Either::B(self.b.parse(ctx, input).await?))
+                })
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                Self {
+                    a: A::default(),
+                    b: B::default(),
+                }
+            }
+        }
+
+        impl<C, A, B> ArgumentKind<C> for Either<A, B>
+        where
+            C: Context,
+            A: ArgumentKind<C>,
+            B: ArgumentKind<C>,
+            A::Checker: Clone,
+            B::Checker: Clone,
+        {
+            type Checker = EitherChecker<A::Checker, B::Checker>;
+            type Parser = EitherParser<A::Parser, B::Parser>;
+        }
+    }
+
+    /// A value matched and parsed like `T`, without consuming any input,
+    /// so that the following argument reads the same word again.
+    #[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+    pub struct Peek<T>(pub T);
+
+    impl<T> Peek<T> {
+        pub fn into_inner(self) -> T {
+            self.0
+        }
+    }
+
+    impl<T> std::ops::Deref for Peek<T> {
+        type Target = T;
+
+        fn deref(&self) -> &Self::Target {
+            &self.0
+        }
+    }
+
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    pub struct PeekChecker<K> {
+        checker: K,
+    }
+
+    impl<C, K> ArgumentChecker<C> for PeekChecker<K>
+    where
+        C: Context,
+        K: ArgumentChecker<C> + Clone,
+    {
+        fn satisfies<'a, 'b>(
+            &'a self,
+            ctx: &'a C,
+            input: &'a mut &'b str,
+        ) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>> {
+            Box::pin(async move {
+                let mut peeked = *input;
+                self.checker.satisfies(ctx, &mut peeked).await
+            })
+        }
+
+        fn equals(&self, other: &dyn Any) -> bool {
+            other
+                .downcast_ref::<Self>()
+                .map(|other| self.checker.equals(&other.checker))
+                .unwrap_or(false)
+        }
+
+        fn default() -> Self
+        where
+            Self: Sized,
+        {
+            Self {
+                checker: K::default(),
+            }
+        }
+
+        fn box_clone(&self) -> Box<dyn ArgumentChecker<C>> {
+            Box::new(self.clone())
+        }
+
+        #[cfg(feature = "serde")]
+        fn brigadier(&self) -> (&'static str, Option<serde_json::Value>) {
+            self.checker.brigadier()
+        }
+    }
+
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    pub struct PeekParser<P> {
+        parser: P,
+    }
+
+    impl<C, P> ArgumentParser<C> for PeekParser<P>
+    where
+        C: Context,
+        P: ArgumentParser<C>,
+    {
+        type Output = Peek<P::Output>;
+
+        fn parse<'a, 'b>(
+            &'a self,
+            ctx: &'a mut C,
+            input: &'a mut &'b str,
+        ) -> Pin<Box<dyn Future<Output = Result<Self::Output, C::Error>> + Send + Sync + 'a>>
+        {
+            Box::pin(async move {
+                let mut peeked = *input;
+                Ok(Peek(self.parser.parse(ctx, &mut peeked).await?))
+            })
+        }
+
+        fn default() -> Self
+        where
+            Self: Sized,
+        {
+            Self {
+                parser: P::default(),
+            }
+        }
+    }
+
+    impl<C, T> ArgumentKind<C> for Peek<T>
+    where
+        C: Context,
+        T: ArgumentKind<C>,
+        T::Checker: Clone,
+    {
+        type Checker = PeekChecker<T::Checker>;
+        type Parser = PeekParser<T::Parser>;
+    }
+
+    macro_rules! from_str_argument_kind {
+        ($($ty:ty,)*) => {
+            $(
+                impl <C> ArgumentKind<C> for $ty
+                where
+                    C: Context,
+                    C::Error: From<<$ty as FromStr>::Err>,
+                {
+                    type Checker = FromStrChecker<Self>;
+                    type Parser = FromStrParser<Self>;
+                }
+            )*
+        }
+    }
+
+    from_str_argument_kind!(
+        i8,
+        i16,
+        i32,
+        i64,
+        i128,
+        isize,
+        u8,
+        u16,
+        u32,
+        u64,
+        usize,
+        f32,
+        f64,
+        u128,
+        String,
+        bool,
+        char,
+        NonZeroI8,
+        NonZeroI16,
+        NonZeroI32,
+        NonZeroI64,
+        NonZeroIsize,
+        NonZeroU8,
+        NonZeroU16,
+        NonZeroU32,
+        NonZeroU64,
+        NonZeroUsize,
+        PathBuf,
+    );
+    pub use self::selector::{
+        InvalidSelector, Selector, SelectorChecker, SelectorKind, SelectorParser,
+    };
+
+    mod selector {
+        use super::*;
+        use std::fmt;
+
+        /// The set of entities an entity selector starts from.
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+        pub enum SelectorKind {
+            /// `@a`
+            AllPlayers,
+            /// `@p`
+            NearestPlayer,
+            /// `@r`
+            RandomPlayer,
+            /// `@s`
+            Executor,
+            /// `@e`
+            AllEntities,
+        }
+
+        impl SelectorKind {
+            const ALL: [SelectorKind; 5] = [
+                SelectorKind::AllPlayers,
+                SelectorKind::NearestPlayer,
+                SelectorKind::RandomPlayer,
+                SelectorKind::Executor,
+                SelectorKind::AllEntities,
+            ];
+
+            /// Returns the selector's written form, such as `@a`.
+            pub fn as_str(self) -> &'static str {
+                match self {
+                    SelectorKind::AllPlayers => "@a",
+                    SelectorKind::NearestPlayer => "@p",
+                    SelectorKind::RandomPlayer => "@r",
+                    SelectorKind::Executor => "@s",
+                    SelectorKind::AllEntities => "@e",
+                }
+            }
+        }
+
+        /// An entity selector, such as `@a` or `@e[type=zombie,limit=1]`.
+        #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+        pub struct Selector {
+            pub kind: SelectorKind,
+            /// `key=value` filters in the order they were written. Values are
+            /// kept verbatim and may themselves contain brackets.
+            pub filters: Vec<(String, String)>,
+        }
+
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, thiserror::Error)]
+        #[error("invalid entity selector")]
+        pub struct InvalidSelector;
+
+        impl Selector {
+            /// Parses a selector from the start of `input`, advancing it past
+            /// the selector and a following space.
+            fn parse_prefix(input: &mut &str) -> Result<Self, InvalidSelector> {
+                let rest = input.strip_prefix('@').ok_or(InvalidSelector)?;
+                let kind = SelectorKind::ALL
+                    .iter()
+                    .copied()
+                    .find(|kind| rest.starts_with(&kind.as_str()[1..]))
+                    .ok_or(InvalidSelector)?;
+                let rest = &rest[1..];
+
+                let (filters, rest) = if rest.starts_with('[') {
+                    let mut tail = rest;
+                    let head = tail.advance_balanced().ok_or(InvalidSelector)?;
+                    (
+                        Self::parse_filters(&head[1..head.len() - 1])?,
+                        &rest[head.len()..],
+                    )
+                } else {
+                    (Vec::new(), rest)
+                };
+
+                *input = match rest.strip_prefix(' ') {
+                    Some(rest) => rest,
+                    None if rest.is_empty() => rest,
+                    None => return Err(InvalidSelector),
+                };
+                Ok(Selector { kind, filters })
+            }
+
+            fn parse_filters(filters: &str) -> Result<Vec<(String, String)>, InvalidSelector> {
+                if filters.trim().is_empty() {
+                    return Ok(Vec::new());
+                }
+
+                // Split on commas which are not nested in brackets or quotes.
+                let mut parsed = Vec::new();
+                let mut depth = 0usize;
+                let mut in_string = false;
+                let mut start = 0;
+                for (i, c) in filters.char_indices().chain(Some((filters.len(), ','))) {
+                    match c {
+                        '"' => in_string = !in_string,
+                        '{' | '[' if !in_string => depth += 1,
+                        '}' | ']' if !in_string => depth = depth.saturating_sub(1),
+                        ',' if !in_string && depth == 0 => {
+                            let filter = &filters[start..i];
+                            let index = filter.find('=').ok_or(InvalidSelector)?;
+                            let key = filter[..index].trim();
+                            if key.is_empty() {
+                                return Err(InvalidSelector);
+                            }
+                            parsed.push((key.to_owned(), filter[index + 1..].trim().to_owned()));
+                            start = i + 1;
+                        }
+                        _ => (),
+                    }
+                }
+                Ok(parsed)
+            }
+        }
+
+        impl FromStr for Selector {
+            type Err = InvalidSelector;
+
+            fn from_str(mut s: &str) -> Result<Self, Self::Err> {
+                let selector = Self::parse_prefix(&mut s)?;
+                if s.is_empty() {
+                    Ok(selector)
+                } else {
+                    Err(InvalidSelector)
+                }
+            }
+        }
+
+        impl fmt::Display for Selector {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.kind.as_str())?;
+                if !self.filters.is_empty() {
+                    f.write_str("[")?;
+                    for (i, (key, value)) in self.filters.iter().enumerate() {
+                        if i > 0 {
+                            f.write_str(",")?;
+                        }
+                        write!(f, "{}={}", key, value)?;
+                    }
+                    f.write_str("]")?;
+                }
+                Ok(())
+            }
+        }
+
+        #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+        pub struct SelectorChecker;
+
+        impl<C> ArgumentChecker<C> for SelectorChecker
+        where
+            C: Context,
+        {
+            fn satisfies<'a, 'b>(
+                &'a self,
+                _ctx: &'a C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>> {
+                Box::pin(async move { Selector::parse_prefix(input).is_ok() })
+            }
+
+            fn equals(&self, other: &dyn Any) -> bool {
+                other.downcast_ref::<Self>().is_some()
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                SelectorChecker
+            }
+
+            fn box_clone(&self) -> Box<dyn ArgumentChecker<C>> {
+                Box::new(*self)
+            }
+
+            fn complete<'a>(
+                &'a self,
+                _ctx: &'a C,
+                partial: &'a str,
+            ) -> Pin<Box<dyn Future<Output = Vec<Completion>> + Send + 'a>> {
+                Box::pin(async move {
+                    SelectorKind::ALL
+                        .iter()
+                        .map(|kind| kind.as_str())
+                        .filter(|kind| kind.starts_with(partial))
+                        .map(Completion::from)
+                        .collect()
+                })
+            }
+        }
+
+        #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+        pub struct SelectorParser;
+
+        impl<C> ArgumentParser<C> for SelectorParser
+        where
+            C: Context,
+            C::Error: From<InvalidSelector>,
+        {
+            type Output = Selector;
+
+            fn parse<'a, 'b>(
+                &'a self,
+                _ctx: &'a mut C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = Result<Self::Output, C::Error>> + Send + Sync + 'a>>
+            {
+                Box::pin(async move { Ok(Selector::parse_prefix(input)?) })
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                SelectorParser
+            }
+        }
+
+        impl<C> ArgumentKind<C> for Selector
+        where
+            C: Context,
+            C::Error: From<InvalidSelector>,
+        {
+            type Checker = SelectorChecker;
+            type Parser = SelectorParser;
+        }
+    }
+
+    pub use self::coord::{Coord, InvalidCoord};
+
+    mod coord {
+        use super::*;
+
+        /// A world coordinate, either absolute (`12.5`) or relative to the
+        /// position of the sender (`~`, `~-3`). Both must be finite, so `NaN`
+        /// and `inf` are rejected.
+        #[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+        pub enum Coord {
+            Absolute(f64),
+            Relative(f64),
+        }
+
+        impl Coord {
+            /// Returns the absolute coordinate, resolving relative
+            /// coordinates against `origin`.
+            pub fn resolve(self, origin: f64) -> f64 {
+                match self {
+                    Coord::Absolute(value) => value,
+                    Coord::Relative(offset) => origin + offset,
+                }
+            }
+        }
+
+        #[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+        #[error("invalid coordinate: {0}")]
+        pub struct InvalidCoord(pub String);
+
+        impl FromStr for Coord {
+            type Err = InvalidCoord;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let invalid = || InvalidCoord(s.to_owned());
+                let finite = |value: &str| {
+                    value
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|value| value.is_finite())
+                        .ok_or_else(invalid)
+                };
+                match s.strip_prefix('~') {
+                    Some("") => Ok(Coord::Relative(0.0)),
+                    Some(offset) => finite(offset).map(Coord::Relative),
+                    None => finite(s).map(Coord::Absolute),
+                }
+            }
+        }
+
+        impl<C> ArgumentKind<C> for Coord
+        where
+            C: Context,
+            C::Error: From<InvalidCoord>,
+        {
+            type Checker = FromStrChecker<Self>;
+            type Parser = FromStrParser<Self>;
+        }
+    }
+
+    pub use self::resource::{InvalidResourceLocation, ResourceLocation};
+
+    mod resource {
+        use super::*;
+        use std::fmt;
+
+        /// A namespaced identifier such as `minecraft:stone`, in the
+        /// `minecraft` namespace if none is given, as in `stone`.
+        ///
+        /// Namespaces may contain lowercase letters, digits, `_`, `.` and
+        /// `-`; paths may additionally contain `/`.
+        #[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub struct ResourceLocation {
+            pub namespace: String,
+            pub path: String,
+        }
+
+        impl ResourceLocation {
+            pub const DEFAULT_NAMESPACE: &'static str = "minecraft";
+        }
+
+        impl fmt::Display for ResourceLocation {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}:{}", self.namespace, self.path)
+            }
+        }
+
+        #[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+        #[error("invalid resource location: {0}")]
+        pub struct InvalidResourceLocation(pub String);
+
+        impl FromStr for ResourceLocation {
+            type Err = InvalidResourceLocation;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                fn valid(s: &str, slash: bool) -> bool {
+                    s.chars().all(|c| {
+                        matches!(c, 'a'..='z' | '0'..='9' | '_' | '.' | '-') || (slash && c == '/')
+                    })
+                }
+
+                let (namespace, path) = match s.find(':') {
+                    Some(index) => (&s[..index], &s[index + 1..]),
+                    None => (Self::DEFAULT_NAMESPACE, s),
+                };
+                if namespace.is_empty()
+                    || path.is_empty()
+                    || !valid(namespace, false)
+                    || !valid(path, true)
+                {
+                    return Err(InvalidResourceLocation(s.to_owned()));
+                }
+                Ok(ResourceLocation {
+                    namespace: namespace.to_owned(),
+                    path: path.to_owned(),
+                })
+            }
+        }
+
+        impl<C> ArgumentKind<C> for ResourceLocation
+        where
+            C: Context,
+            C::Error: From<InvalidResourceLocation>,
+        {
+            type Checker = FromStrChecker<Self>;
+            type Parser = FromStrParser<Self>;
+        }
+    }
+
+    pub use self::size::{ByteSize, InvalidByteSize};
+
+    mod size {
+        use super::*;
+
+        /// A size in bytes, given as a number followed by an optional unit:
+        /// `512`, `512B`, `512K`, `10MB`, `2G` and so on.
+        ///
+        /// Units are powers of 1024 by default, or of 1000 if `DECIMAL`.
+        /// Sizes overflowing a `u64` are rejected.
+        #[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub struct ByteSize<const DECIMAL: bool = false>(pub u64);
+
+        impl<const DECIMAL: bool> ByteSize<DECIMAL> {
+            pub fn bytes(self) -> u64 {
+                self.0
+            }
+        }
+
+        #[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+        #[error("invalid size: {0}")]
+        pub struct InvalidByteSize(pub String);
+
+        impl<const DECIMAL: bool> FromStr for ByteSize<DECIMAL> {
+            type Err = InvalidByteSize;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let invalid = || InvalidByteSize(s.to_owned());
+
+                let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+                let (number, unit) = s.split_at(split);
+                let number: u64 = number.parse().map_err(|_| invalid())?;
+
+                let exponent = match unit.to_ascii_uppercase().as_str() {
+                    "" | "B" => 0,
+                    "K" | "KB" => 1,
+                    "M" | "MB" => 2,
+                    "G" | "GB" => 3,
+                    _ => return Err(invalid()),
+                };
+                let base: u64 = if DECIMAL { 1000 } else { 1024 };
+                base.checked_pow(exponent)
+                    .and_then(|multiplier| number.checked_mul(multiplier))
+                    .map(ByteSize)
+                    .ok_or_else(invalid)
+            }
+        }
+
+        impl<C, const DECIMAL: bool> ArgumentKind<C> for ByteSize<DECIMAL>
+        where
+            C: Context,
+            C::Error: From<InvalidByteSize>,
+        {
+            type Checker = FromStrChecker<Self>;
+            type Parser = FromStrParser<Self>;
+        }
+    }
+
+    pub use self::string::{
+        GreedyString, GreedyStringChecker, GreedyStringParser, InvalidString, MultilineString,
+        MultilineStringParser, RestTokens, RestTokensChecker, RestTokensParser, StringArg,
+        StringArgChecker, StringArgParser,
+    };
+
+    mod string {
+        use super::*;
+        use std::borrow::Cow;
+        use std::fmt;
+
+        /// A string given either as a bare word (`hello`) or quoted
+        /// (`"hello world"`), recording which form was used so that it can
+        /// be written back the same way. Within quotes, `\"` and `\\`
+        /// escape a quote and a backslash.
+        #[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+        pub struct StringArg {
+            pub value: String,
+            pub was_quoted: bool,
+        }
+
+        impl StringArg {
+            /// Parses a string argument off the start of `input` like
+            /// `StringArg` does, borrowing it from `input` unless it contains
+            /// escapes, such as for parsing without allocating.
+            pub fn borrowed<'b>(input: &mut &'b str) -> Option<Cow<'b, str>> {
+                Self::split(input).map(|(value, _)| value)
+            }
+
+            /// Parses a string argument off the start of `input`.
+            fn parse(input: &mut &str) -> Option<Self> {
+                Self::split(input).map(|(value, was_quoted)| Self {
+                    value: value.into_owned(),
+                    was_quoted,
+                })
+            }
+
+            /// Splits a string argument off the start of `input`, returning
+            /// its value along with whether it was quoted.
+            fn split<'b>(input: &mut &'b str) -> Option<(Cow<'b, str>, bool)> {
+                let quoted = match input.strip_prefix('"') {
+                    Some(quoted) => quoted,
+                    None => {
+                        let rest: &'b str = input;
+                        let value = rest.split(' ').next().unwrap_or("");
+                        *input = &rest[(value.len() + 1).min(rest.len())..];
+                        if value.is_empty() || value.contains('"') {
+                            return None;
+                        }
+                        return Some((Cow::Borrowed(value), false));
+                    }
+                };
+
+                // The value is borrowed up to the first escape.
+                let mut value = Cow::Borrowed("");
+                let mut chars = quoted.char_indices();
+                while let Some((i, c)) = chars.next() {
+                    match c {
+                        '\\' => value.to_mut().push(chars.next()?.1),
+                        '"' => {
+                            let tail = &quoted[i + 1..];
+                            *input = match tail.strip_prefix(' ') {
+                                Some(tail) => tail,
+                                None if tail.is_empty() => tail,
+                                None => return None,
+                            };
+                            return Some((value, true));
+                        }
+                        c => match &mut value {
+                            Cow::Owned(value) => value.push(c),
+                            Cow::Borrowed(_) => value = Cow::Borrowed(&quoted[..i + c.len_utf8()]),
+                        },
+                    }
+                }
+                None
+            }
+        }
+
+        /// Writes the string back in the form it was given in.
+        impl fmt::Display for StringArg {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                if !self.was_quoted {
+                    return f.write_str(&self.value);
+                }
+                f.write_str("\"")?;
+                for c in self.value.chars() {
+                    if c == '"' || c == '\\' {
+                        f.write_str("\\")?;
+                    }
+                    write!(f, "{}", c)?;
+                }
+                f.write_str("\"")
+            }
+        }
+
+        #[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+        #[error("invalid string: {0}")]
+        pub struct InvalidString(pub String);
+
+        #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+        pub struct StringArgChecker;
+
+        impl<C> ArgumentChecker<C> for StringArgChecker
+        where
+            C: Context,
+        {
+            fn satisfies<'a, 'b>(
+                &'a self,
+                _ctx: &'a C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>> {
+                Box::pin(async move { StringArg::parse(input).is_some() })
+            }
+
+            fn equals(&self, other: &dyn Any) -> bool {
+                other.downcast_ref::<Self>().is_some()
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                StringArgChecker
+            }
+
+            fn box_clone(&self) -> Box<dyn ArgumentChecker<C>> {
+                Box::new(*self)
+            }
+
+            #[cfg(feature = "serde")]
+            fn brigadier(&self) -> (&'static str, Option<serde_json::Value>) {
+                (
+                    "brigadier:string",
+                    Some(serde_json::json!({ "type": "phrase" })),
+                )
+            }
+        }
+
+        #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+        pub struct StringArgParser;
+
+        impl<C> ArgumentParser<C> for StringArgParser
+        where
+            C: Context,
+            C::Error: From<InvalidString>,
+        {
+            type Output = StringArg;
+
+            fn parse<'a, 'b>(
+                &'a self,
+                _ctx: &'a mut C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = Result<Self::Output, C::Error>> + Send + Sync + 'a>>
+            {
+                Box::pin(async move {
+                    let start = *input;
+                    Ok(StringArg::parse(input).ok_or_else(|| InvalidString(start.to_owned()))?)
+                })
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                StringArgParser
+            }
+        }
+
+        impl<C> ArgumentKind<C> for StringArg
+        where
+            C: Context,
+            C::Error: From<InvalidString>,
+        {
+            type Checker = StringArgChecker;
+            type Parser = StringArgParser;
+        }
+
+        /// The rest of the line, such as the message of `say <message>`.
+        /// No argument may follow it.
+        #[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+        pub struct GreedyString(pub String);
+
+        impl std::ops::Deref for GreedyString {
+            type Target = str;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for GreedyString {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+        pub struct GreedyStringChecker;
+
+        impl<C> ArgumentChecker<C> for GreedyStringChecker
+        where
+            C: Context,
+        {
+            fn satisfies<'a, 'b>(
+                &'a self,
+                _ctx: &'a C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>> {
+                Box::pin(async move { !std::mem::take(input).is_empty() })
+            }
+
+            fn equals(&self, other: &dyn Any) -> bool {
+                other.downcast_ref::<Self>().is_some()
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                GreedyStringChecker
+            }
+
+            fn box_clone(&self) -> Box<dyn ArgumentChecker<C>> {
+                Box::new(*self)
+            }
+
+            #[cfg(feature = "serde")]
+            fn brigadier(&self) -> (&'static str, Option<serde_json::Value>) {
+                (
+                    "brigadier:string",
+                    Some(serde_json::json!({ "type": "greedy" })),
+                )
+            }
+
+            fn is_greedy(&self) -> bool {
+                true
+            }
+        }
+
+        #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+        pub struct GreedyStringParser;
+
+        impl<C> ArgumentParser<C> for GreedyStringParser
+        where
+            C: Context,
+        {
+            type Output = GreedyString;
+
+            fn parse<'a, 'b>(
+                &'a self,
+                _ctx: &'a mut C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = Result<Self::Output, C::Error>> + Send + Sync + 'a>>
+            {
+                Box::pin(async move { Ok(GreedyString(std::mem::take(input).to_owned())) })
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                GreedyStringParser
+            }
+        }
+
+        impl<C> ArgumentKind<C> for GreedyString
+        where
+            C: Context,
+        {
+            type Checker = GreedyStringChecker;
+            type Parser = GreedyStringParser;
+        }
+
+        /// The rest of the line like `GreedyString`, with `\n` escapes
+        /// turned into line breaks, such as for multi-line bodies typed on a
+        /// single line. `\\` escapes a backslash; other backslashes are
+        /// kept as is.
+        #[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+        pub struct MultilineString(pub String);
+
+        impl MultilineString {
+            fn unescape(input: &str) -> Self {
+                let mut value = String::with_capacity(input.len());
+                let mut chars = input.chars();
+                while let Some(c) = chars.next() {
+                    if c != '\\' {
+                        value.push(c);
+                        continue;
+                    }
+                    match chars.next() {
+                        Some('n') => value.push('\n'),
+                        Some('\\') => value.push('\\'),
+                        Some(c) => {
+                            value.push('\\');
+                            value.push(c);
+                        }
+                        None => value.push('\\'),
+                    }
+                }
+                Self(value)
+            }
+        }
+
+        impl std::ops::Deref for MultilineString {
+            type Target = str;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for MultilineString {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+        pub struct MultilineStringParser;
+
+        impl<C> ArgumentParser<C> for MultilineStringParser
+        where
+            C: Context,
+        {
+            type Output = MultilineString;
+
+            fn parse<'a, 'b>(
+                &'a self,
+                _ctx: &'a mut C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = Result<Self::Output, C::Error>> + Send + Sync + 'a>>
+            {
+                Box::pin(async move { Ok(MultilineString::unescape(std::mem::take(input))) })
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                MultilineStringParser
+            }
+        }
+
+        impl<C> ArgumentKind<C> for MultilineString
+        where
+            C: Context,
+        {
+            type Checker = GreedyStringChecker;
+            type Parser = MultilineStringParser;
+        }
+
+        /// The rest of the line split into words, where quoted words may
+        /// contain spaces, such as `a b "c d"`. No argument may follow it.
+        #[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+        pub struct RestTokens(pub Vec<String>);
+
+        impl RestTokens {
+            fn parse(input: &mut &str) -> Option<Self> {
+                let mut tokens = Vec::new();
+                loop {
+                    *input = input.trim_start_matches(' ');
+                    if input.is_empty() {
+                        return Some(Self(tokens));
+                    }
+                    tokens.push(StringArg::parse(input)?.value);
+                }
+            }
+        }
+
+        impl std::ops::Deref for RestTokens {
+            type Target = [String];
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+        pub struct RestTokensChecker;
+
+        impl<C> ArgumentChecker<C> for RestTokensChecker
+        where
+            C: Context,
+        {
+            fn satisfies<'a, 'b>(
+                &'a self,
+                _ctx: &'a C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>> {
+                Box::pin(async move { RestTokens::parse(input).is_some() })
+            }
+
+            fn equals(&self, other: &dyn Any) -> bool {
+                other.downcast_ref::<Self>().is_some()
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                RestTokensChecker
+            }
+
+            fn box_clone(&self) -> Box<dyn ArgumentChecker<C>> {
+                Box::new(*self)
+            }
+
+            #[cfg(feature = "serde")]
+            fn brigadier(&self) -> (&'static str, Option<serde_json::Value>) {
+                (
+                    "brigadier:string",
+                    Some(serde_json::json!({ "type": "greedy" })),
+                )
+            }
+
+            fn is_greedy(&self) -> bool {
+                true
+            }
+        }
+
+        #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+        pub struct RestTokensParser;
+
+        impl<C> ArgumentParser<C> for RestTokensParser
+        where
+            C: Context,
+            C::Error: From<InvalidString>,
+        {
+            type Output = RestTokens;
+
+            fn parse<'a, 'b>(
+                &'a self,
+                _ctx: &'a mut C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = Result<Self::Output, C::Error>> + Send + Sync + 'a>>
+            {
+                Box::pin(async move {
+                    let start = *input;
+                    Ok(RestTokens::parse(input).ok_or_else(|| InvalidString(start.to_owned()))?)
+                })
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                RestTokensParser
+            }
+        }
+
+        impl<C> ArgumentKind<C> for RestTokens
+        where
+            C: Context,
+            C::Error: From<InvalidString>,
+        {
+            type Checker = RestTokensChecker;
+            type Parser = RestTokensParser;
+        }
+    }
+
+    pub use self::token::{AnyToken, AnyTokenChecker, AnyTokenParser};
+
+    mod token {
+        use super::*;
+        use std::fmt;
+
+        /// Any single token, taken as is. Useful as a fallback next to
+        /// literals, which are always tried first.
+        #[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+        pub struct AnyToken(pub String);
+
+        impl fmt::Display for AnyToken {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        impl From<AnyToken> for String {
+            fn from(token: AnyToken) -> Self {
+                token.0
+            }
+        }
+
+        #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+        pub struct AnyTokenChecker;
+
+        impl<C> ArgumentChecker<C> for AnyTokenChecker
+        where
+            C: Context,
+        {
+            fn satisfies<'a, 'b>(
+                &'a self,
+                _ctx: &'a C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>> {
+                Box::pin(async move { !input.advance_until(" ").is_empty() })
+            }
+
+            fn equals(&self, other: &dyn Any) -> bool {
+                other.downcast_ref::<Self>().is_some()
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                AnyTokenChecker
+            }
+
+            fn box_clone(&self) -> Box<dyn ArgumentChecker<C>> {
+                Box::new(*self)
+            }
+        }
+
+        #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+        pub struct AnyTokenParser;
+
+        impl<C> ArgumentParser<C> for AnyTokenParser
+        where
+            C: Context,
+        {
+            type Output = AnyToken;
+
+            fn parse<'a, 'b>(
+                &'a self,
+                _ctx: &'a mut C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = Result<Self::Output, C::Error>> + Send + Sync + 'a>>
+            {
+                Box::pin(async move { Ok(AnyToken(input.advance_until(" ").to_owned())) })
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                AnyTokenParser
+            }
+        }
+
+        impl<C> ArgumentKind<C> for AnyToken
+        where
+            C: Context,
+        {
+            type Checker = AnyTokenChecker;
+            type Parser = AnyTokenParser;
+        }
+    }
+
+    pub use self::until::{Delimiter, MissingDelimiter, Until, UntilChecker, UntilParser};
+
+    mod until {
+        use super::*;
+
+        /// Names the character ending an `Until` argument.
+        pub trait Delimiter: Send + Sync + 'static {
+            /// The delimiter, such as `:`.
+            const DELIMITER: char;
+        }
+
+        /// Everything up to, but not including, the delimiter named by
+        /// `D`, which may span several words. The delimiter is left for the
+        /// following arguments, such as the literal `:` in
+        /// `mail send <recipient> : <body>`, which takes `alice: hello`.
+        pub struct Until<D> {
+            pub value: String,
+            _delimiter: PhantomData<D>,
+        }
+
+        impl<D: Delimiter> Until<D> {
+            pub fn into_inner(self) -> String {
+                self.value
+            }
+
+            /// Splits the value off the start of `input`, leaving the
+            /// delimiter. Returns `None` if there is no delimiter, or
+            /// nothing before it.
+            fn parse_prefix<'b>(input: &mut &'b str) -> Option<&'b str> {
+                let rest: &'b str = input;
+                let end = rest.find(D::DELIMITER).filter(|end| *end > 0)?;
+                *input = &rest[end..];
+                Some(&rest[..end])
+            }
+        }
+
+        impl<D> Clone for Until<D> {
+            fn clone(&self) -> Self {
+                Self {
+                    value: self.value.clone(),
+                    _delimiter: PhantomData,
+                }
+            }
+        }
+
+        impl<D> std::fmt::Debug for Until<D> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_tuple("Until").field(&self.value).finish()
+            }
+        }
+
+        impl<D> PartialEq for Until<D> {
+            fn eq(&self, other: &Self) -> bool {
+                self.value == other.value
+            }
+        }
+
+        impl<D> Eq for Until<D> {}
+
+        #[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+        #[error("missing delimiter in `{0}`")]
+        pub struct MissingDelimiter(pub String);
+
+        pub struct UntilChecker<D> {
+            _delimiter: PhantomData<D>,
+        }
+
+        impl<D> Clone for UntilChecker<D> {
+            fn clone(&self) -> Self {
+                Self {
+                    _delimiter: PhantomData,
+                }
+            }
+        }
+
+        impl<C, D> ArgumentChecker<C> for UntilChecker<D>
+        where
+            C: Context,
+            D: Delimiter,
+        {
+            fn satisfies<'a, 'b>(
+                &'a self,
+                _ctx: &'a C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>> {
+                Box::pin(async move { Until::<D>::parse_prefix(input).is_some() })
+            }
+
+            fn equals(&self, other: &dyn Any) -> bool {
+                other.downcast_ref::<Self>().is_some()
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                Self {
+                    _delimiter: PhantomData,
+                }
+            }
+
+            fn box_clone(&self) -> Box<dyn ArgumentChecker<C>> {
+                Box::new(self.clone())
+            }
+        }
+
+        pub struct UntilParser<D> {
+            _delimiter: PhantomData<D>,
+        }
+
+        impl<C, D> ArgumentParser<C> for UntilParser<D>
+        where
+            C: Context,
+            C::Error: From<MissingDelimiter>,
+            D: Delimiter,
+        {
+            type Output = Until<D>;
+
+            fn parse<'a, 'b>(
+                &'a self,
+                _ctx: &'a mut C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = Result<Self::Output, C::Error>> + Send + Sync + 'a>>
+            {
+                Box::pin(async move {
+                    let start = *input;
+                    let value = Until::<D>::parse_prefix(input)
+                        .ok_or_else(|| MissingDelimiter(start.to_owned()))?;
+                    Ok(Until {
+                        value: value.to_owned(),
+                        _delimiter: PhantomData,
+                    })
+                })
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                Self {
+                    _delimiter: PhantomData,
+                }
+            }
+        }
+
+        impl<C, D> ArgumentKind<C> for Until<D>
+        where
+            C: Context,
+            C::Error: From<MissingDelimiter>,
+            D: Delimiter,
+        {
+            type Checker = UntilChecker<D>;
+            type Parser = UntilParser<D>;
+        }
+    }
+
+    pub use self::variant::{EnumArgument, EnumChecker, EnumParser, InvalidVariant};
+
+    mod variant {
+        use super::*;
+
+        /// An enum given by the name of one of its variants, or one of their
+        /// aliases, such as `creative` or `c`.
+        ///
+        /// Use `EnumChecker<Self>` and `EnumParser<Self>` as its argument
+        /// kind. Since the names are part of the type, checkers of enums
+        /// with different aliases never compare equal.
+        pub trait EnumArgument: Clone + Send + Sync + 'static {
+            /// Canonical name of each variant.
+            const VARIANTS: &'static [(&'static str, Self)];
+            /// Other names accepted for variants, such as `c` for `creative`.
+            const ALIASES: &'static [(&'static str, Self)] = &[];
+            /// Whether aliases are suggested as completions along with the
+            /// canonical names.
+            const COMPLETE_ALIASES: bool = false;
+            /// Whether variants may also be given by their index in
+            /// `VARIANTS`, such as `1` for the second variant.
+            const ORDINALS: bool = false;
+
+            /// Finds the variant named `name`, canonically, by an alias or,
+            /// if enabled, by its ordinal.
+            fn from_name(name: &str) -> Option<Self> {
+                Self::VARIANTS
+                    .iter()
+                    .chain(Self::ALIASES)
+                    .find(|(variant, _)| *variant == name)
+                    .or_else(|| {
+                        if !Self::ORDINALS {
+                            return None;
+                        }
+                        Self::VARIANTS.get(name.parse::<usize>().ok()?)
+                    })
+                    .map(|(_, value)| value.clone())
+            }
+        }
+
+        #[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+        #[error("unknown variant: {0}")]
+        pub struct InvalidVariant(pub String);
+
+        pub struct EnumChecker<T> {
+            _phantom: PhantomData<T>,
+        }
+
+        impl<T> Clone for EnumChecker<T> {
+            fn clone(&self) -> Self {
+                Self {
+                    _phantom: PhantomData,
+                }
+            }
+        }
+
+        impl<C, T> ArgumentChecker<C> for EnumChecker<T>
+        where
+            C: Context,
+            T: EnumArgument,
+        {
+            fn satisfies<'a, 'b>(
+                &'a self,
+                _ctx: &'a C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>> {
+                Box::pin(async move { T::from_name(input.advance_until(" ")).is_some() })
+            }
+
+            fn equals(&self, other: &dyn Any) -> bool {
+                other.downcast_ref::<Self>().is_some()
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                Self {
+                    _phantom: PhantomData,
+                }
+            }
+
+            fn box_clone(&self) -> Box<dyn ArgumentChecker<C>> {
+                Box::new(self.clone())
+            }
+
+            fn complete<'a>(
+                &'a self,
+                _ctx: &'a C,
+                partial: &'a str,
+            ) -> Pin<Box<dyn Future<Output = Vec<Completion>> + Send + 'a>> {
+                Box::pin(async move {
+                    let aliases = if T::COMPLETE_ALIASES { T::ALIASES } else { &[] };
+                    T::VARIANTS
+                        .iter()
+                        .chain(aliases)
+                        .map(|(name, _)| *name)
+                        .filter(|name| name.starts_with(partial))
+                        .map(Completion::from)
+                        .collect()
+                })
+            }
+        }
+
+        pub struct EnumParser<T> {
+            _phantom: PhantomData<T>,
+        }
+
+        impl<C, T> ArgumentParser<C> for EnumParser<T>
+        where
+            C: Context,
+            C::Error: From<InvalidVariant>,
+            T: EnumArgument,
+        {
+            type Output = T;
+
+            fn parse<'a, 'b>(
+                &'a self,
+                _ctx: &'a mut C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = Result<Self::Output, C::Error>> + Send + Sync + 'a>>
+            {
+                Box::pin(async move {
+                    let name = input.advance_until(" ");
+                    Ok(T::from_name(name).ok_or_else(|| InvalidVariant(name.to_owned()))?)
+                })
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                Self {
+                    _phantom: PhantomData,
+                }
+            }
+        }
+    }
+
+    pub use self::recursion::{
+        recursive, InvalidExpression, Recursive, RecursiveArgument, RecursiveChecker,
+        RecursiveParser, RecursiveRef,
+    };
+
+    mod recursion {
+        use super::*;
+        use std::sync::Arc;
+
+        type Grammar<T> =
+            dyn for<'i> Fn(&RecursiveRef<'_, T>, &mut &'i str) -> Option<T> + Send + Sync;
+
+        /// A parser which may refer to itself, such as for expressions
+        /// nested in parentheses, created with `recursive`.
+        pub struct Recursive<T> {
+            max_depth: usize,
+            grammar: Arc<Grammar<T>>,
+        }
+
+        impl<T> Clone for Recursive<T> {
+            fn clone(&self) -> Self {
+                Self {
+                    max_depth: self.max_depth,
+                    grammar: Arc::clone(&self.grammar),
+                }
+            }
+        }
+
+        /// Creates a parser from `grammar`, which is given a handle to parse
+        /// nested input with `grammar` itself. Input nested more than
+        /// `max_depth` levels deep fails to parse.
+        pub fn recursive<T, F>(max_depth: usize, grammar: F) -> Recursive<T>
+        where
+            F: for<'i> Fn(&RecursiveRef<'_, T>, &mut &'i str) -> Option<T> + Send + Sync + 'static,
+        {
+            Recursive {
+                max_depth,
+                grammar: Arc::new(grammar),
+            }
+        }
+
+        impl<T> Recursive<T> {
+            /// Parses the start of `input`, advancing it past what was parsed.
+            pub fn parse(&self, input: &mut &str) -> Option<T> {
+                RecursiveRef {
+                    recursive: self,
+                    depth: 0,
+                }
+                .parse(input)
+            }
+        }
+
+        /// Handle through which a grammar parses nested input.
+        pub struct RecursiveRef<'r, T> {
+            recursive: &'r Recursive<T>,
+            depth: usize,
+        }
+
+        impl<'r, T> RecursiveRef<'r, T> {
+            /// Parses nested input with the grammar, one level deeper.
+            pub fn parse(&self, input: &mut &str) -> Option<T> {
+                if self.depth >= self.recursive.max_depth {
+                    return None;
+                }
+                let nested = RecursiveRef {
+                    recursive: self.recursive,
+                    depth: self.depth + 1,
+                };
+                (self.recursive.grammar)(&nested, input)
+            }
+        }
+
+        /// A type parsed from a recursive grammar, such as an arithmetic
+        /// expression. The grammar may parse across spaces, but must end
+        /// at the end of a word.
+        pub trait RecursiveArgument: Sized + Send + Sync + 'static {
+            fn grammar() -> Recursive<Self>;
+        }
+
+        /// Parses the argument at the start of `input` with `grammar`,
+        /// advancing past it and the space following it.
+        fn parse_argument<T>(grammar: &Recursive<T>, input: &mut &str) -> Option<T> {
+            let mut rest = *input;
+            let value = grammar.parse(&mut rest)?;
+            if !rest.is_empty() && !rest.starts_with(' ') {
+                return None;
+            }
+            *input = rest.get(1..).unwrap_or("");
+            Some(value)
+        }
+
+        #[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+        #[error("invalid expression: {0}")]
+        pub struct InvalidExpression(pub String);
+
+        pub struct RecursiveChecker<T> {
+            grammar: Recursive<T>,
+        }
+
+        impl<T> Clone for RecursiveChecker<T> {
+            fn clone(&self) -> Self {
+                Self {
+                    grammar: self.grammar.clone(),
+                }
+            }
+        }
+
+        impl<C, T> ArgumentChecker<C> for RecursiveChecker<T>
+        where
+            C: Context,
+            T: RecursiveArgument,
+        {
+            fn satisfies<'a, 'b>(
+                &'a self,
+                _ctx: &'a C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>> {
+                Box::pin(async move { parse_argument(&self.grammar, input).is_some() })
+            }
+
+            fn equals(&self, other: &dyn Any) -> bool {
+                other.downcast_ref::<Self>().is_some()
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                Self {
+                    grammar: T::grammar(),
+                }
+            }
+
+            fn box_clone(&self) -> Box<dyn ArgumentChecker<C>> {
+                Box::new(self.clone())
+            }
+        }
+
+        pub struct RecursiveParser<T> {
+            grammar: Recursive<T>,
+        }
+
+        impl<C, T> ArgumentParser<C> for RecursiveParser<T>
+        where
+            C: Context,
+            C::Error: From<InvalidExpression>,
+            T: RecursiveArgument,
+        {
+            type Output = T;
+
+            fn parse<'a, 'b>(
+                &'a self,
+                _ctx: &'a mut C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = Result<Self::Output, C::Error>> + Send + Sync + 'a>>
+            {
+                Box::pin(async move {
+                    let original = *input;
+                    Ok(parse_argument(&self.grammar, input)
+                        .ok_or_else(|| InvalidExpression(original.to_owned()))?)
+                })
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                Self {
+                    grammar: T::grammar(),
+                }
+            }
+        }
+    }
+
+    pub use self::sub::{SubCommand, SubCommandChecker, SubCommandParser, SubDispatcher};
+
+    mod sub {
+        use super::*;
+        use crate::CommandDispatcher;
+        use std::sync::Arc;
+
+        /// Names the dispatcher which a `SubCommand` is matched against.
+        pub trait SubDispatcher<C: Context>: Send + Sync + 'static {
+            /// Returns the dispatcher, typically held by the context.
+            fn dispatcher(ctx: &C) -> Arc<CommandDispatcher<C>>;
+        }
+
+        /// The rest of the line, which must be a complete command of the
+        /// dispatcher named by `S`, as in `sudo <player> <command>`.
+        pub struct SubCommand<S> {
+            command: String,
+            _spec: PhantomData<S>,
+        }
+
+        impl<S> SubCommand<S> {
+            pub fn command(&self) -> &str {
+                &self.command
+            }
+
+            /// Dispatches the command to the dispatcher named by `S`.
+            pub async fn dispatch<'c, C>(
+                &self,
+                errors: &'c mut Vec<C::Error>,
+                ctx: &mut C,
+            ) -> Result<C::Ok, &'c Vec<C::Error>>
+            where
+                C: Context,
+                S: SubDispatcher<C>,
+            {
+                let dispatcher = S::dispatcher(ctx);
+                dispatcher
+                    .dispatch(&mut Vec::new(), errors, ctx, &self.command)
+                    .await
+            }
+        }
+
+        impl<S> Clone for SubCommand<S> {
+            fn clone(&self) -> Self {
+                Self {
+                    command: self.command.clone(),
+                    _spec: PhantomData,
+                }
+            }
+        }
+
+        impl<S> std::fmt::Debug for SubCommand<S> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_tuple("SubCommand").field(&self.command).finish()
+            }
+        }
+
+        pub struct SubCommandChecker<S> {
+            _spec: PhantomData<S>,
+        }
+
+        impl<S> Clone for SubCommandChecker<S> {
+            fn clone(&self) -> Self {
+                Self { _spec: PhantomData }
+            }
+        }
+
+        impl<C, S> ArgumentChecker<C> for SubCommandChecker<S>
+        where
+            C: Context,
+            S: SubDispatcher<C>,
+        {
+            fn satisfies<'a, 'b>(
+                &'a self,
+                ctx: &'a C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>> {
+                Box::pin(async move {
+                    let command = std::mem::take(input);
+                    S::dispatcher(ctx).is_complete(ctx, command).await
+                })
+            }
+
+            fn equals(&self, other: &dyn Any) -> bool {
+                other.downcast_ref::<Self>().is_some()
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                Self { _spec: PhantomData }
+            }
+
+            fn box_clone(&self) -> Box<dyn ArgumentChecker<C>> {
+                Box::new(self.clone())
+            }
+
+            fn complete<'a>(
+                &'a self,
+                ctx: &'a C,
+                partial: &'a str,
+            ) -> Pin<Box<dyn Future<Output = Vec<Completion>> + Send + 'a>> {
+                Box::pin(async move { S::dispatcher(ctx).suggestions(ctx, partial).await })
+            }
+
+            fn is_greedy(&self) -> bool {
+                true
+            }
+        }
+
+        pub struct SubCommandParser<S> {
+            _spec: PhantomData<S>,
+        }
+
+        impl<C, S> ArgumentParser<C> for SubCommandParser<S>
+        where
+            C: Context,
+            S: SubDispatcher<C>,
+        {
+            type Output = SubCommand<S>;
+
+            fn parse<'a, 'b>(
+                &'a self,
+                _ctx: &'a mut C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = Result<Self::Output, C::Error>> + Send + Sync + 'a>>
+            {
+                Box::pin(async move {
+                    Ok(SubCommand {
+                        command: std::mem::take(input).to_owned(),
+                        _spec: PhantomData,
+                    })
+                })
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                Self { _spec: PhantomData }
+            }
+        }
+
+        impl<C, S> ArgumentKind<C> for SubCommand<S>
+        where
+            C: Context,
+            S: SubDispatcher<C>,
+        {
+            type Checker = SubCommandChecker<S>;
+            type Parser = SubCommandParser<S>;
+        }
+    }
+
+    pub use self::flags::{
+        Flag, FlagSpec, Flags, FlagsChecker, FlagsParser, InvalidFlags, Present, PresentChecker,
+        PresentParser, Switch,
+    };
+
+    mod flags {
+        use super::*;
+        use std::collections::BTreeMap;
+
+        /// A flag accepted by a `Flags` argument.
+        #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+        pub struct Flag {
+            /// Long name, written as `--name`.
+            pub long: &'static str,
+            /// Optional short name, written as `-n`.
+            pub short: Option<char>,
+            /// Whether the flag is followed by a value, as in `--count 3`.
+            pub takes_value: bool,
+        }
+
+        impl Flag {
+            /// A boolean flag, such as `--force`.
+            pub const fn switch(long: &'static str) -> Self {
+                Self {
+                    long,
+                    short: None,
+                    takes_value: false,
+                }
+            }
+
+            /// A flag followed by a value, such as `--count 3` or `--count=3`.
+            pub const fn value(long: &'static str) -> Self {
+                Self {
+                    long,
+                    short: None,
+                    takes_value: true,
+                }
+            }
+
+            pub const fn short(mut self, short: char) -> Self {
+                self.short = Some(short);
+                self
+            }
+        }
+
+        /// Declares the set of flags accepted by a `Flags` argument.
+        pub trait FlagSpec: Send + Sync + 'static {
+            const FLAGS: &'static [Flag];
+            /// Whether unknown flags are skipped rather than failing the match.
+            const IGNORE_UNKNOWN: bool = false;
+        }
+
+        /// Flags declared by `S`, given in any order, such as
+        /// `--count 3 --force`. Parsing stops at the first word which
+        /// is not a flag, leaving it to the following arguments.
+        pub struct Flags<S> {
+            values: BTreeMap<&'static str, Option<String>>,
+            _spec: PhantomData<S>,
+        }
+
+        impl<S: FlagSpec> Flags<S> {
+            /// Returns whether the flag with the given long name was given.
+            pub fn is_set(&self, long: &str) -> bool {
+                self.values.contains_key(long)
+            }
+
+            /// Returns the value given to the flag with the given long name.
+            pub fn value(&self, long: &str) -> Option<&str> {
+                self.values.get(long)?.as_deref()
+            }
+
+            /// Parses the value given to the flag with the given long name.
+            pub fn get<T: FromStr>(&self, long: &str) -> Option<Result<T, T::Err>> {
+                self.value(long).map(T::from_str)
+            }
+
+            /// Parses flags from the start of `input`, advancing past them.
+            fn parse_prefix(input: &mut &str) -> Option<Self> {
+                let mut values = BTreeMap::new();
+                let mut rest = *input;
+
+                loop {
+                    let (head, tail) = match rest.find(' ') {
+                        Some(index) => (&rest[..index], &rest[index + 1..]),
+                        None => (rest, ""),
+                    };
+                    let (flag, inline_value) = match Self::find(head) {
+                        Some(found) => found,
+                        None => break,
+                    };
+                    rest = tail;
+
+                    let flag = match flag {
+                        Some(flag) => flag,
+                        None if S::IGNORE_UNKNOWN => continue,
+                        None => return None,
+                    };
+
+                    let value = match (flag.takes_value, inline_value) {
+                        (true, Some(value)) => Some(value.to_owned()),
+                        (true, None) if !rest.is_empty() => {
+                            let value = rest.advance_until(" ").to_owned();
+                            Some(value)
+                        }
+                        (false, None) => None,
+                        _ => return None,
+                    };
+                    values.insert(flag.long, value);
+                }
+
+                *input = rest;
+                Some(Self {
+                    values,
+                    _spec: PhantomData,
+                })
+            }
+
+            /// Looks up the flag written as `word`, along with any value given
+            /// with `=`. Returns `None` if `word` is not a flag at all, and
+            /// `Some((None, _))` if it is an unknown flag.
+            fn find(word: &str) -> Option<(Option<&'static Flag>, Option<&str>)> {
+                let (name, value) = match word.find('=') {
+                    Some(index) => (&word[..index], Some(&word[index + 1..])),
+                    None => (word, None),
+                };
+
+                let flag = if let Some(long) = name.strip_prefix("--") {
+                    if !long.starts_with(|c: char| c.is_alphabetic()) {
+                        return None;
+                    }
+                    S::FLAGS.iter().find(|flag| flag.long == long)
+                } else if let Some(short) = name.strip_prefix('-') {
+                    let mut chars = short.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) if c.is_alphabetic() => {
+                            S::FLAGS.iter().find(|flag| flag.short == Some(c))
+                        }
+                        (Some(c), Some(_)) if c.is_alphabetic() => None,
+                        _ => return None,
+                    }
+                } else {
+                    return None;
+                };
+                Some((flag, value))
+            }
+        }
+
+        impl<S> Clone for Flags<S> {
+            fn clone(&self) -> Self {
+                Self {
+                    values: self.values.clone(),
+                    _spec: PhantomData,
+                }
+            }
+        }
+
+        impl<S> std::fmt::Debug for Flags<S> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_map().entries(self.values.iter()).finish()
+            }
+        }
+
+        impl<S> PartialEq for Flags<S> {
+            fn eq(&self, other: &Self) -> bool {
+                self.values == other.values
+            }
+        }
+
+        impl<S> Eq for Flags<S> {}
+
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, thiserror::Error)]
+        #[error("invalid flags")]
+        pub struct InvalidFlags;
+
+        pub struct FlagsChecker<S> {
+            _spec: PhantomData<S>,
+        }
+
+        impl<S> Clone for FlagsChecker<S> {
+            fn clone(&self) -> Self {
+                Self { _spec: PhantomData }
+            }
+        }
+
+        impl<C, S> ArgumentChecker<C> for FlagsChecker<S>
+        where
+            C: Context,
+            S: FlagSpec,
+        {
+            fn satisfies<'a, 'b>(
+                &'a self,
+                _ctx: &'a C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>> {
+                Box::pin(async move { Flags::<S>::parse_prefix(input).is_some() })
+            }
+
+            fn equals(&self, other: &dyn Any) -> bool {
+                other.downcast_ref::<Self>().is_some()
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                Self { _spec: PhantomData }
+            }
+
+            fn box_clone(&self) -> Box<dyn ArgumentChecker<C>> {
+                Box::new(self.clone())
+            }
+
+            fn complete<'a>(
+                &'a self,
+                _ctx: &'a C,
+                partial: &'a str,
+            ) -> Pin<Box<dyn Future<Output = Vec<Completion>> + Send + 'a>> {
+                Box::pin(async move {
+                    S::FLAGS
+                        .iter()
+                        .map(|flag| format!("--{}", flag.long))
+                        .filter(|flag| flag.starts_with(partial))
+                        .map(Completion::from)
+                        .collect()
+                })
+            }
+        }
+
+        pub struct FlagsParser<S> {
+            _spec: PhantomData<S>,
+        }
+
+        impl<C, S> ArgumentParser<C> for FlagsParser<S>
+        where
+            C: Context,
+            C::Error: From<InvalidFlags>,
+            S: FlagSpec,
+        {
+            type Output = Flags<S>;
+
+            fn parse<'a, 'b>(
+                &'a self,
+                _ctx: &'a mut C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = Result<Self::Output, C::Error>> + Send + Sync + 'a>>
+            {
+                Box::pin(async move { Ok(Flags::parse_prefix(input).ok_or(InvalidFlags)?) })
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                Self { _spec: PhantomData }
+            }
+        }
+
+        impl<C, S> ArgumentKind<C> for Flags<S>
+        where
+            C: Context,
+            C::Error: From<InvalidFlags>,
+            S: FlagSpec,
+        {
+            type Checker = FlagsChecker<S>;
+            type Parser = FlagsParser<S>;
+        }
+
+        /// Names the literal matched by a `Present` argument.
+        pub trait Switch: Send + Sync + 'static {
+            /// The literal, such as `--force`.
+            const NAME: &'static str;
+        }
+
+        /// Whether the literal named by `S` was given. The literal is
+        /// consumed when present; otherwise the argument matches without
+        /// consuming anything.
+        ///
+        /// Declare it with an empty default, as in `delete [force=]`, so
+        /// that the command also runs when the input ends before it.
+        pub struct Present<S> {
+            present: bool,
+            _spec: PhantomData<S>,
+        }
+
+        impl<S: Switch> Present<S> {
+            pub fn is_present(&self) -> bool {
+                self.present
+            }
+
+            /// Consumes `S::NAME` from the start of `input` if it is there.
+            fn parse_prefix(input: &mut &str) -> Self {
+                let word = match input.find(' ') {
+                    Some(index) => &input[..index],
+                    None => input,
+                };
+                let present = word == S::NAME;
+                if present {
+                    input.advance_until(" ");
+                }
+                Self {
+                    present,
+                    _spec: PhantomData,
+                }
+            }
+        }
+
+        impl<S> Clone for Present<S> {
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+
+        impl<S> Copy for Present<S> {}
+
+        impl<S> std::fmt::Debug for Present<S> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_tuple("Present").field(&self.present).finish()
+            }
+        }
+
+        impl<S> PartialEq for Present<S> {
+            fn eq(&self, other: &Self) -> bool {
+                self.present == other.present
+            }
+        }
+
+        impl<S> Eq for Present<S> {}
+
+        impl<S> From<Present<S>> for bool {
+            fn from(present: Present<S>) -> Self {
+                present.present
+            }
+        }
+
+        pub struct PresentChecker<S> {
+            _spec: PhantomData<S>,
+        }
+
+        impl<S> Clone for PresentChecker<S> {
+            fn clone(&self) -> Self {
+                Self { _spec: PhantomData }
+            }
+        }
+
+        impl<C, S> ArgumentChecker<C> for PresentChecker<S>
+        where
+            C: Context,
+            S: Switch,
+        {
+            fn satisfies<'a, 'b>(
+                &'a self,
+                _ctx: &'a C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>> {
+                Box::pin(async move {
+                    Present::<S>::parse_prefix(input);
+                    true
+                })
+            }
+
+            fn equals(&self, other: &dyn Any) -> bool {
+                other.downcast_ref::<Self>().is_some()
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                Self { _spec: PhantomData }
+            }
+
+            fn box_clone(&self) -> Box<dyn ArgumentChecker<C>> {
+                Box::new(self.clone())
+            }
+
+            fn complete<'a>(
+                &'a self,
+                _ctx: &'a C,
+                partial: &'a str,
+            ) -> Pin<Box<dyn Future<Output = Vec<Completion>> + Send + 'a>> {
+                Box::pin(async move {
+                    if S::NAME.starts_with(partial) {
+                        vec![Completion::from(S::NAME)]
+                    } else {
+                        vec![]
+                    }
+                })
+            }
+        }
+
+        pub struct PresentParser<S> {
+            _spec: PhantomData<S>,
+        }
+
+        impl<C, S> ArgumentParser<C> for PresentParser<S>
+        where
+            C: Context,
+            S: Switch,
+        {
+            type Output = Present<S>;
+
+            fn parse<'a, 'b>(
+                &'a self,
+                _ctx: &'a mut C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = Result<Self::Output, C::Error>> + Send + Sync + 'a>>
+            {
+                Box::pin(async move { Ok(Present::parse_prefix(input)) })
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                Self { _spec: PhantomData }
+            }
+        }
+
+        impl<C, S> ArgumentKind<C> for Present<S>
+        where
+            C: Context,
+            S: Switch,
+        {
+            type Checker = PresentChecker<S>;
+            type Parser = PresentParser<S>;
+        }
+    }
+
+    pub use self::path::{
+        CurrentDir, InvalidPath, PathRoot, SandboxedPath, SandboxedPathChecker, SandboxedPathParser,
+    };
+
+    mod path {
+        use super::*;
+        use std::path::{Component, Path};
+
+        /// Directory which a `SandboxedPath` is relative to.
+        pub trait PathRoot: Send + Sync + 'static {
+            fn root() -> PathBuf;
+        }
+
+        /// The current working directory.
+        pub struct CurrentDir;
+
+        impl PathRoot for CurrentDir {
+            fn root() -> PathBuf {
+                PathBuf::from(".")
+            }
+        }
+
+        /// A relative path which cannot escape the directory `R`: absolute
+        /// paths and `..` components, as in `../../etc/passwd`, fail to match.
+        pub struct SandboxedPath<R = CurrentDir> {
+            path: PathBuf,
+            _phantom: PhantomData<R>,
+        }
+
+        impl<R: PathRoot> SandboxedPath<R> {
+            /// Returns the path as given, relative to the root.
+            pub fn relative(&self) -> &Path {
+                &self.path
+            }
+
+            /// Returns the path joined onto the root.
+            pub fn resolve(&self) -> PathBuf {
+                R::root().join(&self.path)
+            }
+
+            pub fn into_inner(self) -> PathBuf {
+                self.path
+            }
+        }
+
+        impl<R> std::fmt::Debug for SandboxedPath<R> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                self.path.fmt(f)
+            }
+        }
+
+        impl<R> FromStr for SandboxedPath<R> {
+            type Err = InvalidPath;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let path = PathBuf::from(s);
+                if s.is_empty() || !is_sandboxed(&path) {
+                    return Err(InvalidPath(s.to_owned()));
+                }
+                Ok(Self {
+                    path,
+                    _phantom: PhantomData,
+                })
+            }
+        }
+
+        fn is_sandboxed(path: &Path) -> bool {
+            path.components()
+                .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+        }
+
+        #[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+        #[error("invalid path: {0}")]
+        pub struct InvalidPath(pub String);
+
+        pub struct SandboxedPathChecker<R> {
+            _phantom: PhantomData<R>,
+        }
+
+        impl<R> Clone for SandboxedPathChecker<R> {
+            fn clone(&self) -> Self {
+                Self {
+                    _phantom: PhantomData,
+                }
+            }
+        }
+
+        impl<C, R> ArgumentChecker<C> for SandboxedPathChecker<R>
+        where
+            C: Context,
+            R: PathRoot,
+        {
+            fn satisfies<'a, 'b>(
+                &'a self,
+                _ctx: &'a C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>> {
+                Box::pin(
+                    async move { SandboxedPath::<R>::from_str(input.advance_until(" ")).is_ok() },
+                )
+            }
+
+            fn equals(&self, other: &dyn Any) -> bool {
+                other.downcast_ref::<Self>().is_some()
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                Self {
+                    _phantom: PhantomData,
+                }
+            }
+
+            fn box_clone(&self) -> Box<dyn ArgumentChecker<C>> {
+                Box::new(self.clone())
+            }
+
+            /// Lists the entries of the directory being typed, relative to the root.
+            fn complete<'a>(
+                &'a self,
+                _ctx: &'a C,
+                partial: &'a str,
+            ) -> Pin<Box<dyn Future<Output = Vec<Completion>> + Send + 'a>> {
+                Box::pin(async move {
+                    let (dir, name) = match partial.rfind('/') {
+                        Some(index) => (&partial[..=index], &partial[index + 1..]),
+                        None => ("", partial),
+                    };
+                    if !is_sandboxed(Path::new(dir)) {
+                        return Vec::new();
+                    }
+
+                    let entries = match std::fs::read_dir(R::root().join(dir)) {
+                        Ok(entries) => entries,
+                        Err(_) => return Vec::new(),
+                    };
+                    let mut completions: Vec<Completion> = entries
+                        .filter_map(Result::ok)
+                        .filter_map(|entry| {
+                            let file_name = entry.file_name().into_string().ok()?;
+                            if !file_name.starts_with(name) {
+                                return None;
+                            }
+                            let suffix = match entry.file_type() {
+                                Ok(file_type) if file_type.is_dir() => "/",
+                                _ => "",
+                            };
+                            Some(format!("{}{}{}", dir, file_name, suffix).into())
+                        })
+                        .collect();
+                    completions.sort_by(|a, b| a.text.cmp(&b.text));
+                    completions
+                })
+            }
+        }
+
+        pub struct SandboxedPathParser<R> {
+            _phantom: PhantomData<R>,
+        }
+
+        impl<C, R> ArgumentParser<C> for SandboxedPathParser<R>
+        where
+            C: Context,
+            C::Error: From<InvalidPath>,
+            R: PathRoot,
+        {
+            type Output = SandboxedPath<R>;
+
+            fn parse<'a, 'b>(
+                &'a self,
+                _ctx: &'a mut C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = Result<Self::Output, C::Error>> + Send + Sync + 'a>>
+            {
+                Box::pin(async move { Ok(input.advance_until(" ").parse()?) })
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                Self {
+                    _phantom: PhantomData,
+                }
+            }
+        }
+
+        impl<C, R> ArgumentKind<C> for SandboxedPath<R>
+        where
+            C: Context,
+            C::Error: From<InvalidPath>,
+            R: PathRoot,
+        {
+            type Checker = SandboxedPathChecker<R>;
+            type Parser = SandboxedPathParser<R>;
+        }
+    }
+
+    pub use self::range::{
+        BoundedInt, BoundedIntChecker, InvalidRange, OpenRange, OpenRangeParser, RangeBound,
+        RangeChecker, RangeParser,
+    };
+
+    mod range {
+        use super::*;
+        use std::ops::RangeInclusive;
+
+        /// Integer type which ranges such as `1..10` may be parsed over.
+        pub trait RangeBound: FromStr + Copy + Ord + Send + Sync + 'static {
+            const MIN: Self;
+            const MAX: Self;
+
+            /// Returns the value one less than `self`, if any.
+            fn predecessor(self) -> Option<Self>;
+        }
+
+        macro_rules! range_bound {
+            ($($ty:ty),* $(,)?) => {
+                $(
+                    impl RangeBound for $ty {
+                        const MIN: Self = <$ty>::MIN;
+                        const MAX: Self = <$ty>::MAX;
+
+                        fn predecessor(self) -> Option<Self> {
+                            self.checked_sub(1)
+                        }
+                    }
+                )*
+            };
+        }
+
+        range_bound!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+        /// A range such as `1..10` or `1..=10` which may leave out either end,
+        /// as in `..10`, `1..` or `..`. Missing ends are clamped to the bounds
+        /// of `T`. `RangeInclusive` itself only accepts ranges with both ends.
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        pub struct OpenRange<T>(pub RangeInclusive<T>);
+
+        impl<T> OpenRange<T> {
+            pub fn into_inner(self) -> RangeInclusive<T> {
+                self.0
+            }
+        }
+
+        impl<T> std::ops::Deref for OpenRange<T> {
+            type Target = RangeInclusive<T>;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        #[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+        #[error("invalid range: {0}")]
+        pub struct InvalidRange(pub String);
+
+        /// Parses `s` as a range of `T`; `..` is exclusive and `..=` inclusive.
+        fn parse_range<T: RangeBound>(s: &str, open: bool) -> Option<RangeInclusive<T>> {
+            let index = s.find("..")?;
+            let (start, end) = (&s[..index], &s[index + 2..]);
+            let (end, inclusive) = match end.strip_prefix('=') {
+                Some(end) => (end, true),
+                None => (end, false),
+            };
+
+            let bound = |s: &str, default: T| match s {
+                "" if open => Some(default),
+                s => s.parse().ok(),
+            };
+            let start = bound(start, T::MIN)?;
+            let end = match (bound(end, T::MAX)?, end.is_empty() || inclusive) {
+                (end, true) => end,
+                (end, false) => end.predecessor()?,
+            };
+
+            if start > end {
+                return None;
+            }
+            Some(start..=end)
+        }
+
+        pub struct RangeChecker<T, const OPEN: bool> {
+            _phantom: PhantomData<T>,
+        }
+
+        impl<T, const OPEN: bool> Clone for RangeChecker<T, OPEN> {
+            fn clone(&self) -> Self {
+                Self {
+                    _phantom: PhantomData,
+                }
+            }
+        }
+
+        impl<C, T, const OPEN: bool> ArgumentChecker<C> for RangeChecker<T, OPEN>
+        where
+            C: Context,
+            T: RangeBound,
+        {
+            fn satisfies<'a, 'b>(
+                &'a self,
+                _ctx: &'a C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>> {
+                Box::pin(async move { parse_range::<T>(input.advance_until(" "), OPEN).is_some() })
+            }
+
+            fn equals(&self, other: &dyn Any) -> bool {
+                other.downcast_ref::<Self>().is_some()
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                Self {
+                    _phantom: PhantomData,
+                }
+            }
+
+            fn box_clone(&self) -> Box<dyn ArgumentChecker<C>> {
+                Box::new(self.clone())
+            }
+        }
+
+        pub struct RangeParser<T, const OPEN: bool> {
+            _phantom: PhantomData<T>,
+        }
+
+        impl<C, T, const OPEN: bool> ArgumentParser<C> for RangeParser<T, OPEN>
+        where
+            C: Context,
+            C::Error: From<InvalidRange>,
+            T: RangeBound,
+        {
+            type Output = RangeInclusive<T>;
+
+            fn parse<'a, 'b>(
+                &'a self,
+                _ctx: &'a mut C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = Result<Self::Output, C::Error>> + Send + Sync + 'a>>
+            {
+                Box::pin(async move {
+                    let head = input.advance_until(" ");
+                    Ok(parse_range(head, OPEN).ok_or_else(|| InvalidRange(head.to_owned()))?)
+                })
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                Self {
+                    _phantom: PhantomData,
+                }
+            }
+        }
+
+        impl<C, T> ArgumentKind<C> for RangeInclusive<T>
+        where
+            C: Context,
+            C::Error: From<InvalidRange>,
+            T: RangeBound,
+        {
+            type Checker = RangeChecker<T, false>;
+            type Parser = RangeParser<T, false>;
+        }
+
+        /// Parser for `OpenRange`, wrapping the output of `RangeParser`.
+        pub struct OpenRangeParser<T> {
+            parser: RangeParser<T, true>,
+        }
+
+        impl<C, T> ArgumentParser<C> for OpenRangeParser<T>
+        where
+            C: Context,
+            C::Error: From<InvalidRange>,
+            T: RangeBound,
+        {
+            type Output = OpenRange<T>;
+
+            fn parse<'a, 'b>(
+                &'a self,
+                ctx: &'a mut C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = Result<Self::Output, C::Error>> + Send + Sync + 'a>>
+            {
+                Box::pin(async move { Ok(OpenRange(self.parser.parse(ctx, input).await?)) })
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                Self {
+                    parser: <RangeParser<T, true> as ArgumentParser<C>>::default(),
+                }
+            }
+        }
+
+        impl<C, T> ArgumentKind<C> for OpenRange<T>
+        where
+            C: Context,
+            C::Error: From<InvalidRange>,
+            T: RangeBound,
+        {
+            type Checker = RangeChecker<T, true>;
+            type Parser = OpenRangeParser<T>;
+        }
+
+        /// An integer between `MIN` and `MAX`, inclusive.
+        #[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub struct BoundedInt<const MIN: i32, const MAX: i32>(pub i32);
+
+        impl<const MIN: i32, const MAX: i32> BoundedInt<MIN, MAX> {
+            pub fn into_inner(self) -> i32 {
+                self.0
+            }
+        }
+
+        impl<const MIN: i32, const MAX: i32> FromStr for BoundedInt<MIN, MAX> {
+            type Err = InvalidRange;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s.parse() {
+                    Ok(value) if (MIN..=MAX).contains(&value) => Ok(BoundedInt(value)),
+                    _ => Err(InvalidRange(s.to_owned())),
+                }
+            }
+        }
+
+        #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+        pub struct BoundedIntChecker<const MIN: i32, const MAX: i32>;
+
+        impl<C, const MIN: i32, const MAX: i32> ArgumentChecker<C> for BoundedIntChecker<MIN, MAX>
+        where
+            C: Context,
+        {
+            fn satisfies<'a, 'b>(
+                &'a self,
+                _ctx: &'a C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>> {
+                Box::pin(async move {
+                    BoundedInt::<MIN, MAX>::from_str(input.advance_until(" ")).is_ok()
+                })
+            }
+
+            fn equals(&self, other: &dyn Any) -> bool {
+                other.downcast_ref::<Self>().is_some()
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                BoundedIntChecker
+            }
+
+            fn box_clone(&self) -> Box<dyn ArgumentChecker<C>> {
+                Box::new(*self)
+            }
+
+            #[cfg(feature = "serde")]
+            fn brigadier(&self) -> (&'static str, Option<serde_json::Value>) {
+                (
+                    "brigadier:integer",
+                    Some(serde_json::json!({ "min": MIN, "max": MAX })),
+                )
+            }
+        }
+
+        impl<C, const MIN: i32, const MAX: i32> ArgumentKind<C> for BoundedInt<MIN, MAX>
+        where
+            C: Context,
+            C::Error: From<InvalidRange>,
+        {
+            type Checker = BoundedIntChecker<MIN, MAX>;
+            type Parser = FromStrParser<Self>;
+        }
+    }
+
+    pub use self::dependent::{
+        Dependent, DependentArgument, DependentChecker, DependentParser, InvalidDependent,
+    };
+
+    mod dependent {
+        use super::*;
+
+        /// Two words where the second is parsed depending on the value of
+        /// the first, such as a resource kind followed by an id valid for
+        /// that kind.
+        pub trait DependentArgument: Send + Sync + 'static {
+            type First: FromStr + Send + Sync;
+            type Second: Send + Sync;
+
+            /// Parses the second word given the first, or returns `None` if
+            /// it is not valid after `first`.
+            fn and_then(first: &Self::First, second: &str) -> Option<Self::Second>;
+        }
+
+        /// Argument parsed by a `DependentArgument`.
+        pub struct Dependent<D: DependentArgument> {
+            pub first: D::First,
+            pub second: D::Second,
+        }
+
+        impl<D: DependentArgument> Dependent<D> {
+            fn parse(input: &mut &str) -> Option<Self> {
+                let first = input.advance_until(" ").parse().ok()?;
+                let second = D::and_then(&first, input.advance_until(" "))?;
+                Some(Self { first, second })
+            }
+        }
+
+        impl<D> std::fmt::Debug for Dependent<D>
+        where
+            D: DependentArgument,
+            D::First: std::fmt::Debug,
+            D::Second: std::fmt::Debug,
+        {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_struct("Dependent")
+                    .field("first", &self.first)
+                    .field("second", &self.second)
+                    .finish()
+            }
+        }
+
+        #[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+        #[error("invalid argument: {0}")]
+        pub struct InvalidDependent(pub String);
+
+        pub struct DependentChecker<D> {
+            _phantom: PhantomData<D>,
+        }
+
+        impl<D> Clone for DependentChecker<D> {
+            fn clone(&self) -> Self {
+                Self {
+                    _phantom: PhantomData,
+                }
+            }
+        }
+
+        impl<C, D> ArgumentChecker<C> for DependentChecker<D>
+        where
+            C: Context,
+            D: DependentArgument,
+        {
+            fn satisfies<'a, 'b>(
+                &'a self,
+                _ctx: &'a C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>> {
+                Box::pin(async move { Dependent::<D>::parse(input).is_some() })
+            }
+
+            fn equals(&self, other: &dyn Any) -> bool {
+                other.downcast_ref::<Self>().is_some()
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                Self {
+                    _phantom: PhantomData,
+                }
+            }
+
+            fn box_clone(&self) -> Box<dyn ArgumentChecker<C>> {
+                Box::new(self.clone())
+            }
+        }
+
+        pub struct DependentParser<D> {
+            _phantom: PhantomData<D>,
+        }
+
+        impl<C, D> ArgumentParser<C> for DependentParser<D>
+        where
+            C: Context,
+            C::Error: From<InvalidDependent>,
+            D: DependentArgument,
+        {
+            type Output = Dependent<D>;
+
+            fn parse<'a, 'b>(
+                &'a self,
+                _ctx: &'a mut C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = Result<Self::Output, C::Error>> + Send + Sync + 'a>>
+            {
+                Box::pin(async move {
+                    let start = *input;
+                    Ok(
+                        Dependent::parse(input)
+                            .ok_or_else(|| InvalidDependent(start.to_owned()))?,
+                    )
+                })
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                Self {
+                    _phantom: PhantomData,
+                }
+            }
+        }
+
+        impl<C, D> ArgumentKind<C> for Dependent<D>
+        where
+            C: Context,
+            C::Error: From<InvalidDependent>,
+            D: DependentArgument,
+        {
+            type Checker = DependentChecker<D>;
+            type Parser = DependentParser<D>;
+        }
+    }
+
+    #[cfg(feature = "chrono")]
+    pub use self::time::{ChronoArgument, ChronoChecker, ChronoParser};
+
+    #[cfg(feature = "chrono")]
+    mod time {
+        use super::*;
+        use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+
+        /// A `chrono` type which can be parsed from a single argument word.
+        pub trait ChronoArgument: Sized + Send + Sync + 'static {
+            /// Example value, suggested as a completion.
+            const EXAMPLE: &'static str;
+            /// Accepted formats, shown as the tooltip of the example.
+            const FORMAT: &'static str;
+
+            fn parse(s: &str) -> chrono::ParseResult<Self>;
+        }
+
+        impl ChronoArgument for NaiveTime {
+            const EXAMPLE: &'static str = "12:00";
+            const FORMAT: &'static str = "HH:MM or HH:MM:SS";
+
+            fn parse(s: &str) -> chrono::ParseResult<Self> {
+                NaiveTime::parse_from_str(s, "%H:%M")
+                    .or_else(|_| NaiveTime::parse_from_str(s, "%H:%M:%S"))
+            }
+        }
+
+        impl ChronoArgument for NaiveDate {
+            const EXAMPLE: &'static str = "2020-01-01";
+            const FORMAT: &'static str = "YYYY-MM-DD";
+
+            fn parse(s: &str) -> chrono::ParseResult<Self> {
+                NaiveDate::parse_from_str(s, "%Y-%m-%d")
+            }
+        }
+
+        impl ChronoArgument for NaiveDateTime {
+            const EXAMPLE: &'static str = "2020-01-01T12:00:00";
+            const FORMAT: &'static str = "ISO 8601 date and time without offset";
+
+            fn parse(s: &str) -> chrono::ParseResult<Self> {
+                NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f")
+            }
+        }
+
+        impl ChronoArgument for DateTime<FixedOffset> {
+            const EXAMPLE: &'static str = "2020-01-01T12:00:00+00:00";
+            const FORMAT: &'static str = "ISO 8601 date and time with offset";
+
+            fn parse(s: &str) -> chrono::ParseResult<Self> {
+                DateTime::parse_from_rfc3339(s)
+            }
+        }
+
+        impl ChronoArgument for DateTime<Utc> {
+            const EXAMPLE: &'static str = "2020-01-01T12:00:00Z";
+            const FORMAT: &'static str = "ISO 8601 date and time with offset";
+
+            fn parse(s: &str) -> chrono::ParseResult<Self> {
+                DateTime::parse_from_rfc3339(s).map(|time| time.with_timezone(&Utc))
+            }
+        }
+
+        pub struct ChronoChecker<T> {
+            _phantom: PhantomData<T>,
+        }
+
+        impl<T> Clone for ChronoChecker<T> {
+            fn clone(&self) -> Self {
+                Self {
+                    _phantom: PhantomData,
+                }
+            }
+        }
+
+        impl<C, T> ArgumentChecker<C> for ChronoChecker<T>
+        where
+            C: Context,
+            T: ChronoArgument,
+        {
+            fn satisfies<'a, 'b>(
+                &'a self,
+                _ctx: &'a C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>> {
+                Box::pin(async move { T::parse(input.advance_until(" ")).is_ok() })
+            }
+
+            fn equals(&self, other: &dyn Any) -> bool {
+                other.downcast_ref::<Self>().is_some()
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                Self {
+                    _phantom: PhantomData,
+                }
+            }
+
+            fn box_clone(&self) -> Box<dyn ArgumentChecker<C>> {
+                Box::new(self.clone())
+            }
+
+            fn complete<'a>(
+                &'a self,
+                _ctx: &'a C,
+                partial: &'a str,
+            ) -> Pin<Box<dyn Future<Output = Vec<Completion>> + Send + 'a>> {
+                Box::pin(async move {
+                    if T::EXAMPLE.starts_with(partial) {
+                        vec![Completion::new(T::EXAMPLE).with_tooltip(T::FORMAT)]
+                    } else {
+                        Vec::new()
+                    }
+                })
+            }
+        }
+
+        pub struct ChronoParser<T> {
+            _phantom: PhantomData<T>,
+        }
+
+        impl<C, T> ArgumentParser<C> for ChronoParser<T>
+        where
+            C: Context,
+            C::Error: From<chrono::ParseError>,
+            T: ChronoArgument,
+        {
+            type Output = T;
+
+            fn parse<'a, 'b>(
+                &'a self,
+                _ctx: &'a mut C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = Result<Self::Output, C::Error>> + Send + Sync + 'a>>
+            {
+                Box::pin(async move { Ok(T::parse(input.advance_until(" "))?) })
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                Self {
+                    _phantom: PhantomData,
+                }
+            }
+        }
+
+        macro_rules! chrono_argument_kind {
+            ($($ty:ty),* $(,)?) => {
+                $(
+                    impl<C> ArgumentKind<C> for $ty
+                    where
+                        C: Context,
+                        C::Error: From<chrono::ParseError>,
+                    {
+                        type Checker = ChronoChecker<$ty>;
+                        type Parser = ChronoParser<$ty>;
+                    }
+                )*
+            };
+        }
+
+        chrono_argument_kind!(
+            NaiveTime,
+            NaiveDate,
+            NaiveDateTime,
+            DateTime<FixedOffset>,
+            DateTime<Utc>,
+        );
+    }
+
+    #[cfg(feature = "bitflags")]
+    pub use self::bits::{BitFlags, BitFlagsChecker, BitFlagsParser, InvalidBitFlags};
+
+    #[cfg(feature = "bitflags")]
+    mod bits {
+        use super::*;
+        use bitflags::Flags;
+
+        /// A set of `bitflags` flags given by name, separated by commas,
+        /// such as `build,pvp`. Names are matched case-insensitively.
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+        pub struct BitFlags<T>(pub T);
+
+        impl<T> BitFlags<T> {
+            pub fn into_inner(self) -> T {
+                self.0
+            }
+        }
+
+        impl<T> std::ops::Deref for BitFlags<T> {
+            type Target = T;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        fn parse_flag<T: Flags>(name: &str) -> Option<&'static bitflags::Flag<T>> {
+            T::FLAGS
+                .iter()
+                .find(|flag| !flag.name().is_empty() && flag.name().eq_ignore_ascii_case(name))
+        }
+
+        fn parse_flags<T: Flags + Copy>(s: &str) -> Option<T> {
+            s.split(',').try_fold(T::empty(), |bits, name| {
+                parse_flag::<T>(name).map(|flag| bits.union(*flag.value()))
+            })
+        }
+
+        #[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+        #[error("invalid flags: {0}")]
+        pub struct InvalidBitFlags(pub String);
+
+        pub struct BitFlagsChecker<T> {
+            _phantom: PhantomData<T>,
+        }
+
+        impl<T> Clone for BitFlagsChecker<T> {
+            fn clone(&self) -> Self {
+                Self {
+                    _phantom: PhantomData,
+                }
+            }
+        }
+
+        impl<C, T> ArgumentChecker<C> for BitFlagsChecker<T>
+        where
+            C: Context,
+            T: Flags + Copy + Send + Sync + 'static,
+        {
+            fn satisfies<'a, 'b>(
+                &'a self,
+                _ctx: &'a C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>> {
+                Box::pin(async move { parse_flags::<T>(input.advance_until(" ")).is_some() })
+            }
+
+            fn equals(&self, other: &dyn Any) -> bool {
+                other.downcast_ref::<Self>().is_some()
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                Self {
+                    _phantom: PhantomData,
+                }
+            }
+
+            fn box_clone(&self) -> Box<dyn ArgumentChecker<C>> {
+                Box::new(self.clone())
+            }
+
+            /// Suggests the flags not yet given.
+            fn complete<'a>(
+                &'a self,
+                _ctx: &'a C,
+                partial: &'a str,
+            ) -> Pin<Box<dyn Future<Output = Vec<Completion>> + Send + 'a>> {
+                Box::pin(async move {
+                    let (chosen, name) = match partial.rfind(',') {
+                        Some(index) => (&partial[..index], &partial[index + 1..]),
+                        None => ("", partial),
+                    };
+                    let chosen: T = match chosen {
+                        "" => T::empty(),
+                        chosen => match parse_flags(chosen) {
+                            Some(chosen) => chosen,
+                            None => return Vec::new(),
+                        },
+                    };
+                    let prefix = &partial[..partial.len() - name.len()];
+
+                    T::FLAGS
+                        .iter()
+                        .filter(|flag| !flag.name().is_empty() && !chosen.contains(*flag.value()))
+                        .map(|flag| flag.name().to_ascii_lowercase())
+                        .filter(|flag| flag.starts_with(&name.to_ascii_lowercase()))
+                        .map(|flag| Completion::new(format!("{}{}", prefix, flag)))
+                        .collect()
+                })
+            }
+        }
+
+        pub struct BitFlagsParser<T> {
+            _phantom: PhantomData<T>,
+        }
+
+        impl<C, T> ArgumentParser<C> for BitFlagsParser<T>
+        where
+            C: Context,
+            C::Error: From<InvalidBitFlags>,
+            T: Flags + Copy + Send + Sync + 'static,
+        {
+            type Output = BitFlags<T>;
+
+            fn parse<'a, 'b>(
+                &'a self,
+                _ctx: &'a mut C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = Result<Self::Output, C::Error>> + Send + Sync + 'a>>
+            {
+                Box::pin(async move {
+                    let head = input.advance_until(" ");
+                    let bits = parse_flags(head).ok_or_else(|| InvalidBitFlags(head.to_owned()))?;
+                    Ok(BitFlags(bits))
+                })
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                Self {
+                    _phantom: PhantomData,
+                }
+            }
+        }
+
+        impl<C, T> ArgumentKind<C> for BitFlags<T>
+        where
+            C: Context,
+            C::Error: From<InvalidBitFlags>,
+            T: Flags + Copy + Send + Sync + 'static,
+        {
+            type Checker = BitFlagsChecker<T>;
+            type Parser = BitFlagsParser<T>;
+        }
+    }
+
+    #[cfg(feature = "rust_decimal")]
+    pub use self::decimal::{
+        DecimalArgument, DecimalChecker, DecimalParser, FixedDecimal, InvalidDecimal,
+    };
+
+    #[cfg(feature = "rust_decimal")]
+    mod decimal {
+        use super::*;
+        use rust_decimal::Decimal;
+
+        /// A decimal amount, such as money, given with at most `DIGITS`
+        /// fractional digits: `FixedDecimal<2>` accepts `19.99` but not `19.999`.
+        #[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub struct FixedDecimal<const DIGITS: u32>(pub Decimal);
+
+        impl<const DIGITS: u32> FixedDecimal<DIGITS> {
+            pub fn into_inner(self) -> Decimal {
+                self.0
+            }
+        }
+
+        impl<const DIGITS: u32> std::ops::Deref for FixedDecimal<DIGITS> {
+            type Target = Decimal;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        /// A type which can be parsed from a decimal argument word.
+        pub trait DecimalArgument: Sized + Send + Sync + 'static {
+            /// Maximum number of fractional digits accepted.
+            const DIGITS: u32;
+
+            fn from_decimal(decimal: Decimal) -> Self;
+
+            /// Parses `s`, rejecting more than `DIGITS` significant
+            /// fractional digits.
+            fn parse(s: &str) -> Option<Self> {
+                let decimal = s.parse::<Decimal>().ok()?;
+                if decimal.normalize().scale() > Self::DIGITS {
+                    return None;
+                }
+                Some(Self::from_decimal(decimal))
+            }
+        }
+
+        impl DecimalArgument for Decimal {
+            /// The largest scale a `Decimal` supports.
+            const DIGITS: u32 = 28;
+
+            fn from_decimal(decimal: Decimal) -> Self {
+                decimal
+            }
+        }
+
+        impl<const DIGITS: u32> DecimalArgument for FixedDecimal<DIGITS> {
+            const DIGITS: u32 = DIGITS;
+
+            fn from_decimal(decimal: Decimal) -> Self {
+                FixedDecimal(decimal)
+            }
+        }
+
+        #[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+        #[error("invalid decimal: {0}")]
+        pub struct InvalidDecimal(pub String);
+
+        pub struct DecimalChecker<T> {
+            _phantom: PhantomData<T>,
+        }
+
+        impl<T> Clone for DecimalChecker<T> {
+            fn clone(&self) -> Self {
+                Self {
+                    _phantom: PhantomData,
+                }
+            }
+        }
+
+        impl<C, T> ArgumentChecker<C> for DecimalChecker<T>
+        where
+            C: Context,
+            T: DecimalArgument,
+        {
+            fn satisfies<'a, 'b>(
+                &'a self,
+                _ctx: &'a C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>> {
+                Box::pin(async move { T::parse(input.advance_until(" ")).is_some() })
+            }
+
+            fn equals(&self, other: &dyn Any) -> bool {
+                other.downcast_ref::<Self>().is_some()
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                Self {
+                    _phantom: PhantomData,
+                }
+            }
+
+            fn box_clone(&self) -> Box<dyn ArgumentChecker<C>> {
+                Box::new(self.clone())
+            }
+        }
+
+        pub struct DecimalParser<T> {
+            _phantom: PhantomData<T>,
+        }
+
+        impl<C, T> ArgumentParser<C> for DecimalParser<T>
+        where
+            C: Context,
+            C::Error: From<InvalidDecimal>,
+            T: DecimalArgument,
+        {
+            type Output = T;
+
+            fn parse<'a, 'b>(
+                &'a self,
+                _ctx: &'a mut C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = Result<Self::Output, C::Error>> + Send + Sync + 'a>>
+            {
+                Box::pin(async move {
+                    let word = input.advance_until(" ");
+                    Ok(T::parse(word).ok_or_else(|| InvalidDecimal(word.to_owned()))?)
+                })
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                Self {
+                    _phantom: PhantomData,
+                }
+            }
+        }
+
+        impl<C> ArgumentKind<C> for Decimal
+        where
+            C: Context,
+            C::Error: From<InvalidDecimal>,
+        {
+            type Checker = DecimalChecker<Self>;
+            type Parser = DecimalParser<Self>;
+        }
+
+        impl<C, const DIGITS: u32> ArgumentKind<C> for FixedDecimal<DIGITS>
+        where
+            C: Context,
+            C::Error: From<InvalidDecimal>,
+        {
+            type Checker = DecimalChecker<Self>;
+            type Parser = DecimalParser<Self>;
+        }
+    }
+
+    #[cfg(feature = "semver")]
+    pub use self::version::{InvalidVersion, SemverArgument, SemverChecker, SemverParser};
+
+    #[cfg(feature = "semver")]
+    mod version {
+        use super::*;
+        use semver::{Version, VersionReq};
+
+        /// A type which can be parsed from a semantic version argument.
+        pub trait SemverArgument: Sized + Send + Sync + 'static {
+            /// Advances past the words making up the argument and returns them.
+            fn advance<'b>(input: &mut &'b str) -> &'b str {
+                let s: &'b str = input;
+                let end = s.find(' ').unwrap_or(s.len());
+                *input = &s[(end + 1).min(s.len())..];
+                &s[..end]
+            }
+
+            fn parse(s: &str) -> Option<Self>;
+        }
+
+        impl SemverArgument for Version {
+            fn parse(s: &str) -> Option<Self> {
+                Version::parse(s).ok()
+            }
+        }
+
+        impl SemverArgument for VersionReq {
+            /// Requirements may list several comparators, such as
+            /// `>=1.0, <2.0`, continuing for as long as a word ends with a comma.
+            fn advance<'b>(input: &mut &'b str) -> &'b str {
+                let s: &'b str = input;
+                let mut end = 0;
+                for word in s.split(' ') {
+                    end += word.len();
+                    if !word.ends_with(',') || end >= s.len() {
+                        break;
+                    }
+                    end += 1;
+                }
+                *input = &s[(end + 1).min(s.len())..];
+                &s[..end]
+            }
+
+            fn parse(s: &str) -> Option<Self> {
+                VersionReq::parse(s).ok()
+            }
+        }
+
+        #[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+        #[error("invalid version: {0}")]
+        pub struct InvalidVersion(pub String);
+
+        pub struct SemverChecker<T> {
+            _phantom: PhantomData<T>,
+        }
+
+        impl<T> Clone for SemverChecker<T> {
+            fn clone(&self) -> Self {
+                Self {
+                    _phantom: PhantomData,
+                }
+            }
+        }
+
+        impl<C, T> ArgumentChecker<C> for SemverChecker<T>
+        where
+            C: Context,
+            T: SemverArgument,
+        {
+            fn satisfies<'a, 'b>(
+                &'a self,
+                _ctx: &'a C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>> {
+                Box::pin(async move { T::parse(T::advance(input)).is_some() })
+            }
+
+            fn equals(&self, other: &dyn Any) -> bool {
+                other.downcast_ref::<Self>().is_some()
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                Self {
+                    _phantom: PhantomData,
+                }
+            }
+
+            fn box_clone(&self) -> Box<dyn ArgumentChecker<C>> {
+                Box::new(self.clone())
+            }
+        }
+
+        pub struct SemverParser<T> {
+            _phantom: PhantomData<T>,
+        }
+
+        impl<C, T> ArgumentParser<C> for SemverParser<T>
+        where
+            C: Context,
+            C::Error: From<InvalidVersion>,
+            T: SemverArgument,
+        {
+            type Output = T;
+
+            fn parse<'a, 'b>(
+                &'a self,
+                _ctx: &'a mut C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = Result<Self::Output, C::Error>> + Send + Sync + 'a>>
+            {
+                Box::pin(async move {
+                    let words = T::advance(input);
+                    Ok(T::parse(words).ok_or_else(|| InvalidVersion(words.to_owned()))?)
+                })
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                Self {
+                    _phantom: PhantomData,
+                }
+            }
+        }
+
+        impl<C> ArgumentKind<C> for Version
+        where
+            C: Context,
+            C::Error: From<InvalidVersion>,
+        {
+            type Checker = SemverChecker<Self>;
+            type Parser = SemverParser<Self>;
+        }
+
+        impl<C> ArgumentKind<C> for VersionReq
+        where
+            C: Context,
+            C::Error: From<InvalidVersion>,
+        {
+            type Checker = SemverChecker<Self>;
+            type Parser = SemverParser<Self>;
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    pub use self::json::{JsonChecker, JsonParser};
+
+    #[cfg(feature = "serde")]
+    mod json {
+        use super::*;
+        use serde_json::Value;
+
+        /// Checker for inline JSON arguments, which may span several
+        /// space-separated words as long as brackets and quotes balance.
+        #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+        pub struct JsonChecker;
+
+        impl<C> ArgumentChecker<C> for JsonChecker
+        where
+            C: Context,
+        {
+            fn satisfies<'a, 'b>(
+                &'a self,
+                _ctx: &'a C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>> {
+                Box::pin(async move {
+                    match input.advance_balanced() {
+                        Some(head) => serde_json::from_str::<Value>(head).is_ok(),
+                        None => false,
+                    }
+                })
+            }
+
+            fn equals(&self, other: &dyn Any) -> bool {
+                other.downcast_ref::<Self>().is_some()
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                JsonChecker
+            }
+
+            fn box_clone(&self) -> Box<dyn ArgumentChecker<C>> {
+                Box::new(*self)
+            }
+        }
+
+        #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+        pub struct JsonParser;
+
+        impl<C> ArgumentParser<C> for JsonParser
+        where
+            C: Context,
+            C::Error: From<serde_json::Error>,
+        {
+            type Output = Value;
+
+            fn parse<'a, 'b>(
+                &'a self,
+                _ctx: &'a mut C,
+                input: &'a mut &'b str,
+            ) -> Pin<Box<dyn Future<Output = Result<Self::Output, C::Error>> + Send + Sync + 'a>>
+            {
+                Box::pin(async move {
+                    // An unbalanced value fails to parse as JSON as well.
+                    let head = match input.advance_balanced() {
+                        Some(head) => head,
+                        None => std::mem::take(input),
+                    };
+                    Ok(serde_json::from_str(head)?)
+                })
+            }
+
+            fn default() -> Self
+            where
+                Self: Sized,
+            {
+                JsonParser
+            }
+        }
+
+        impl<C> ArgumentKind<C> for Value
+        where
+            C: Context,
+            C::Error: From<serde_json::Error>,
+        {
+            type Checker = JsonChecker;
+            type Parser = JsonParser;
+        }
+    }
 }
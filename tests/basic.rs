@@ -1,6 +1,6 @@
-use lieutenant::{command, provider, CommandDispatcher, Context};
-use thiserror::Error;
+use lieutenant::{command, CommandDispatcher, Context};
 use std::num;
+use thiserror::Error;
 
 #[derive(Debug, Error, PartialEq)]
 enum Error {
@@ -10,8 +10,7 @@ enum Error {
     ParsingInt,
 }
 
-impl From<num::ParseIntError> for Error
-{
+impl From<num::ParseIntError> for Error {
     fn from(_: num::ParseIntError) -> Self {
         Error::ParsingInt
     }
@@ -23,7 +22,6 @@ impl From<std::convert::Infallible> for Error {
     }
 }
 
-
 #[test]
 fn basic_command() {
     #[derive(Debug, PartialEq, Eq)]
@@ -38,7 +36,7 @@ fn basic_command() {
     async fn test(ctx: &mut State, x: i32) -> Result<(), Error> {
         *ctx = State(x);
         Ok(())
-    };
+    }
 
     let dispatcher = CommandDispatcher::default().with(test);
 
@@ -62,10 +60,12 @@ fn basic_command_with_provider() {
         type Ok = ();
     }
 
+    #[allow(dead_code)]
     struct Value;
 
     // #[provider]
-    async fn provde_value(ctx: &State) -> Result<Value, Error> {
+    #[allow(dead_code)]
+    async fn provde_value(_ctx: &State) -> Result<Value, Error> {
         Ok(Value)
     }
 
@@ -73,7 +73,7 @@ fn basic_command_with_provider() {
     async fn test(ctx: &mut State, x: i32) -> Result<(), Error> {
         *ctx = State(x);
         Ok(())
-    };
+    }
 
     let dispatcher = CommandDispatcher::default().with(test);
 
@@ -114,7 +114,7 @@ fn basic_command_parralel() {
         Timer::after(Duration::from_secs(1)).await;
 
         Ok(())
-    };
+    }
 
     let dispatcher = CommandDispatcher::default().with(test);
 
@@ -154,7 +154,7 @@ fn error_handling() {
         } else {
             Err(Error::Custom("Not zero".into()))
         }
-    };
+    }
 
     let dispatcher = CommandDispatcher::default().with(test);
 
@@ -303,7 +303,11 @@ fn command_macro() {
     }
 
     #[command(usage = "bar <player> <x>")]
-    async fn foo_a_player_then_bar_an_x(state: &mut State, x: i32, player: String) -> Result<(), Error> {
+    async fn foo_a_player_then_bar_an_x(
+        state: &mut State,
+        x: i32,
+        player: String,
+    ) -> Result<(), Error> {
         state.player.push_str(&player);
         state.x = x + 1;
         Ok(())
@@ -451,3 +455,327 @@ fn help_command() {
     // assert!(ctx.usages.is_empty());
     // assert!(ctx.descriptions.is_empty());
 }
+
+#[test]
+fn optional_argument_default() {
+    struct State {
+        home: String,
+    }
+
+    impl Context for State {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "home [name=\"home\"]")]
+    async fn home(state: &mut State, name: String) -> Result<(), Error> {
+        state.home = name;
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(home);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+
+    let mut state = State {
+        home: String::new(),
+    };
+
+    assert!(
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut state, "home")).is_ok()
+    );
+    assert_eq!(state.home, "home");
+
+    assert!(
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut state, "home beach"))
+            .is_ok()
+    );
+    assert_eq!(state.home, "beach");
+}
+
+#[test]
+fn optional_argument_without_default() {
+    struct State {
+        count: Option<u32>,
+    }
+
+    impl Context for State {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "spawn [count]")]
+    async fn spawn(state: &mut State, count: Option<u32>) -> Result<(), Error> {
+        state.count = count;
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(spawn);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+
+    let mut state = State { count: Some(0) };
+
+    assert!(
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut state, "spawn")).is_ok()
+    );
+    assert_eq!(state.count, None);
+
+    assert!(
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut state, "spawn 3")).is_ok()
+    );
+    assert_eq!(state.count, Some(3));
+}
+
+#[test]
+fn integer_edge_cases() {
+    #[derive(Default)]
+    struct State {
+        small: Option<i8>,
+        large: Option<u64>,
+    }
+
+    impl Context for State {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "small <value>")]
+    async fn small(state: &mut State, value: i8) -> Result<(), Error> {
+        state.small = Some(value);
+        Ok(())
+    }
+
+    #[command(usage = "large <value>")]
+    async fn large(state: &mut State, value: u64) -> Result<(), Error> {
+        state.large = Some(value);
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(small).with(large);
+
+    let mut errors = Vec::new();
+    for value in &[
+        "0",
+        "127",
+        "128",
+        "-128",
+        "-129",
+        "+5",
+        "007",
+        "-0",
+        "-",
+        "+",
+        "1_000",
+        "0x10",
+        "١",
+        "18446744073709551615",
+        "18446744073709551616",
+    ] {
+        let mut state = State::default();
+        let input = format!("small {}", value);
+        let _ =
+            smol::block_on(dispatcher.dispatch(&mut Vec::new(), &mut errors, &mut state, &input));
+        assert_eq!(state.small, value.parse().ok(), "{}", input);
+
+        let input = format!("large {}", value);
+        let _ =
+            smol::block_on(dispatcher.dispatch(&mut Vec::new(), &mut errors, &mut state, &input));
+        assert_eq!(state.large, value.parse().ok(), "{}", input);
+    }
+}
+
+#[test]
+fn trailing_optional_chain() {
+    #[derive(Default)]
+    struct State {
+        spawn: Option<(String, [Option<i32>; 3])>,
+    }
+
+    impl Context for State {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "spawn [world=\"overworld\"] [x] [y] [z]")]
+    async fn spawn(
+        state: &mut State,
+        world: String,
+        x: Option<i32>,
+        y: Option<i32>,
+        z: Option<i32>,
+    ) -> Result<(), Error> {
+        state.spawn = Some((world, [x, y, z]));
+        Ok(())
+    }
+
+    let mut dispatcher = CommandDispatcher::default();
+    assert!(dispatcher.register(spawn).is_ok());
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut dispatch = |input| {
+        let mut state = State::default();
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut state, input))
+            .ok()
+            .and(state.spawn)
+    };
+
+    let overworld = String::from("overworld");
+    let nether = String::from("nether");
+    assert_eq!(dispatch("spawn"), Some((overworld, [None; 3])));
+    assert_eq!(dispatch("spawn nether"), Some((nether.clone(), [None; 3])));
+    assert_eq!(
+        dispatch("spawn nether 1"),
+        Some((nether.clone(), [Some(1), None, None]))
+    );
+    assert_eq!(
+        dispatch("spawn nether 1 2 3"),
+        Some((nether, [Some(1), Some(2), Some(3)]))
+    );
+    assert_eq!(dispatch("spawn nether 1 2 3 4"), None);
+}
+
+#[test]
+fn trailing_optional_group() {
+    #[derive(Default)]
+    struct State {
+        spawn: Option<(String, Option<[i32; 3]>)>,
+    }
+
+    impl Context for State {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    // Coordinates are given all at once or not at all.
+    #[command(usage = "spawn [world=\"overworld\"] [pos]")]
+    async fn spawn(state: &mut State, world: String, pos: Option<[i32; 3]>) -> Result<(), Error> {
+        state.spawn = Some((world, pos));
+        Ok(())
+    }
+
+    let mut dispatcher = CommandDispatcher::default();
+    assert!(dispatcher.register(spawn).is_ok());
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut dispatch = |input| {
+        let mut state = State::default();
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut state, input))
+            .ok()
+            .and(state.spawn)
+    };
+
+    let nether = String::from("nether");
+    assert_eq!(dispatch("spawn"), Some((String::from("overworld"), None)));
+    assert_eq!(dispatch("spawn nether"), Some((nether.clone(), None)));
+    assert_eq!(
+        dispatch("spawn nether 1 2 3"),
+        Some((nether, Some([1, 2, 3])))
+    );
+    assert_eq!(dispatch("spawn nether 1"), None);
+    assert_eq!(dispatch("spawn nether 1 2"), None);
+}
+
+#[test]
+fn command_permissions() {
+    struct State;
+
+    impl Context for State {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "stop", permission = "admin.stop")]
+    async fn stop(_state: &mut State) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(
+        usage = "ban <player>",
+        permission = "admin.ban",
+        permission = "moderation.ban"
+    )]
+    async fn ban(_state: &mut State, _player: String) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "help")]
+    async fn help(_state: &mut State) -> Result<(), Error> {
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(stop).with(ban).with(help);
+
+    let permissions: Vec<_> = dispatcher
+        .commands()
+        .flat_map(|command| command.permissions.iter())
+        .collect();
+    assert_eq!(
+        permissions,
+        vec!["admin.stop", "admin.ban", "moderation.ban"]
+    );
+}
+
+#[test]
+fn deprecated_command() {
+    use lieutenant::CommandPath;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct State {
+        stopped: bool,
+    }
+
+    impl Context for State {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "halt", deprecated = "stop")]
+    async fn halt(state: &mut State) -> Result<(), Error> {
+        state.stopped = true;
+        Ok(())
+    }
+
+    #[command(usage = "stop")]
+    async fn stop(state: &mut State) -> Result<(), Error> {
+        state.stopped = true;
+        Ok(())
+    }
+
+    let mut dispatcher = CommandDispatcher::default().with(halt).with(stop);
+    let warnings = Arc::new(Mutex::new(Vec::new()));
+    {
+        let warnings = Arc::clone(&warnings);
+        dispatcher.on_deprecated(move |path, replacement| {
+            warnings
+                .lock()
+                .unwrap()
+                .push((path.clone(), replacement.to_owned()));
+        });
+    }
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+
+    let mut state = State::default();
+    assert!(
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut state, "stop")).is_ok()
+    );
+    assert!(state.stopped);
+    assert!(warnings.lock().unwrap().is_empty());
+
+    let mut state = State::default();
+    assert!(
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut state, "halt")).is_ok()
+    );
+    assert!(state.stopped);
+    assert_eq!(
+        *warnings.lock().unwrap(),
+        vec![(CommandPath(vec!["halt".into()]), "stop".to_owned())]
+    );
+}
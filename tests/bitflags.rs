@@ -0,0 +1,83 @@
+#![cfg(feature = "bitflags")]
+
+use bitflags::bitflags;
+use lieutenant::parsers::{BitFlags, InvalidBitFlags};
+use lieutenant::{command, CommandDispatcher, Context};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+enum Error {
+    #[error(transparent)]
+    Flags(#[from] InvalidBitFlags),
+}
+
+bitflags! {
+    #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+    struct RegionFlags: u8 {
+        const BUILD = 1;
+        const PVP = 1 << 1;
+        const MOBS = 1 << 2;
+    }
+}
+
+#[derive(Default)]
+struct State {
+    flags: Option<RegionFlags>,
+}
+
+impl Context for State {
+    type Error = Error;
+    type Ok = ();
+}
+
+#[command(usage = "region flags <flags>")]
+async fn region_flags(state: &mut State, flags: BitFlags<RegionFlags>) -> Result<(), Error> {
+    state.flags = Some(flags.into_inner());
+    Ok(())
+}
+
+#[test]
+fn parse_flag_names() {
+    let dispatcher = CommandDispatcher::default().with(region_flags);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut dispatch = |input| {
+        let mut state = State::default();
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut state, input))
+            .ok()
+            .and(state.flags)
+    };
+
+    assert_eq!(
+        dispatch("region flags build,pvp"),
+        Some(RegionFlags::BUILD | RegionFlags::PVP)
+    );
+    assert_eq!(dispatch("region flags MOBS"), Some(RegionFlags::MOBS));
+    for input in &[
+        "region flags build,unknown",
+        "region flags build,",
+        "region flags",
+    ] {
+        assert_eq!(dispatch(input), None);
+    }
+}
+
+#[test]
+fn complete_remaining_flags() {
+    let dispatcher = CommandDispatcher::default().with(region_flags);
+
+    let suggestions = |input| -> Vec<String> {
+        smol::block_on(dispatcher.suggestions(&State::default(), input))
+            .into_iter()
+            .map(|completion| completion.text)
+            .collect()
+    };
+
+    assert_eq!(suggestions("region flags "), vec!["build", "pvp", "mobs"]);
+    assert_eq!(
+        suggestions("region flags build,"),
+        vec!["build,pvp", "build,mobs"]
+    );
+    assert_eq!(suggestions("region flags pvp,m"), vec!["pvp,mobs"]);
+}
@@ -0,0 +1,103 @@
+#![cfg(feature = "chrono")]
+
+use chrono::{DateTime, FixedOffset, NaiveTime, TimeZone};
+use lieutenant::{command, CommandDispatcher, Context};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+enum Error {
+    #[error("invalid time: {0}")]
+    Time(#[from] chrono::ParseError),
+}
+
+#[derive(Default)]
+struct State {
+    time: Option<NaiveTime>,
+    date_time: Option<DateTime<FixedOffset>>,
+}
+
+impl Context for State {
+    type Error = Error;
+    type Ok = ();
+}
+
+#[command(usage = "schedule <time>")]
+async fn schedule(state: &mut State, time: NaiveTime) -> Result<(), Error> {
+    state.time = Some(time);
+    Ok(())
+}
+
+#[command(usage = "schedule at <date_time>")]
+async fn schedule_at(state: &mut State, date_time: DateTime<FixedOffset>) -> Result<(), Error> {
+    state.date_time = Some(date_time);
+    Ok(())
+}
+
+#[test]
+fn parse_times() {
+    let dispatcher = CommandDispatcher::default()
+        .with(schedule)
+        .with(schedule_at);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut state = State::default();
+
+    assert!(smol::block_on(dispatcher.dispatch(
+        &mut nodes,
+        &mut errors,
+        &mut state,
+        "schedule 14:30"
+    ))
+    .is_ok());
+    assert_eq!(state.time, NaiveTime::from_hms_opt(14, 30, 0));
+
+    assert!(smol::block_on(dispatcher.dispatch(
+        &mut nodes,
+        &mut errors,
+        &mut state,
+        "schedule at 2020-06-01T14:30:00+02:00"
+    ))
+    .is_ok());
+    assert_eq!(
+        state.date_time,
+        FixedOffset::east_opt(2 * 3600)
+            .unwrap()
+            .with_ymd_and_hms(2020, 6, 1, 14, 30, 0)
+            .single()
+    );
+}
+
+#[test]
+fn reject_invalid_times() {
+    let dispatcher = CommandDispatcher::default()
+        .with(schedule)
+        .with(schedule_at);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut state = State::default();
+
+    for input in &[
+        "schedule 25:99",
+        "schedule noon",
+        "schedule at 2020-13-01T00:00:00Z",
+    ] {
+        assert!(
+            smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut state, input))
+                .is_err()
+        );
+    }
+    assert_eq!(state.time, None);
+    assert_eq!(state.date_time, None);
+}
+
+#[test]
+fn time_format_hint() {
+    let dispatcher = CommandDispatcher::default().with(schedule);
+
+    let completions = smol::block_on(dispatcher.suggestions(&State::default(), "schedule "));
+    assert!(completions
+        .iter()
+        .any(|completion| completion.tooltip.as_deref() == Some("HH:MM or HH:MM:SS")));
+}
@@ -0,0 +1,7 @@
+#[test]
+fn command_spec_macro() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/command_spec.rs");
+    t.pass("tests/ui/command_spec_collect.rs");
+    t.compile_fail("tests/ui/command_spec_arity.rs");
+}
@@ -0,0 +1,67 @@
+#![cfg(feature = "rust_decimal")]
+
+use lieutenant::parsers::{FixedDecimal, InvalidDecimal};
+use lieutenant::{command, CommandDispatcher, Context};
+use rust_decimal::Decimal;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+enum Error {
+    #[error(transparent)]
+    Decimal(#[from] InvalidDecimal),
+    #[error("failed to parse string")]
+    Infallible(#[from] std::convert::Infallible),
+}
+
+#[derive(Default)]
+struct State {
+    paid: Option<(String, Decimal)>,
+    rate: Option<Decimal>,
+}
+
+impl Context for State {
+    type Error = Error;
+    type Ok = ();
+}
+
+#[command(usage = "pay <player> <amount>")]
+async fn pay(state: &mut State, player: String, amount: FixedDecimal<2>) -> Result<(), Error> {
+    state.paid = Some((player, amount.into_inner()));
+    Ok(())
+}
+
+#[command(usage = "rate <rate>")]
+async fn set_rate(state: &mut State, rate: Decimal) -> Result<(), Error> {
+    state.rate = Some(rate);
+    Ok(())
+}
+
+#[test]
+fn parse_amounts() {
+    let dispatcher = CommandDispatcher::default().with(pay).with(set_rate);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut dispatch = |input| {
+        let mut state = State::default();
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut state, input))
+            .ok()
+            .map(|_| state)
+    };
+
+    let state = dispatch("pay alice 19.99").unwrap();
+    assert_eq!(
+        state.paid,
+        Some(("alice".to_owned(), Decimal::new(1999, 2)))
+    );
+    let state = dispatch("pay alice 20").unwrap();
+    assert_eq!(state.paid, Some(("alice".to_owned(), Decimal::new(20, 0))));
+
+    assert!(dispatch("pay alice 19.999").is_none());
+    assert!(dispatch("pay alice abc").is_none());
+    assert!(dispatch("pay alice 19.99.9").is_none());
+
+    let state = dispatch("rate 0.00125").unwrap();
+    assert_eq!(state.rate, Some(Decimal::new(125, 5)));
+    assert!(dispatch("rate abc").is_none());
+}
@@ -0,0 +1,6 @@
+#[test]
+fn derive_command() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/derive_command.rs");
+    t.compile_fail("tests/ui/derive_command_no_parser.rs");
+}
@@ -0,0 +1,2773 @@
+use lieutenant::{
+    command, AliasError, Argument, Command, CommandDispatcher, CommandPath, CommandSpec, Context,
+    DispatchError, DispatchFailure, DispatchOutcome, DispatchTrace, Executable, Feedback,
+    InputContext, Middleware, ParseAnalysis, ParserChain, RegisterError, RegisterWarning, TreeDiff,
+    Utf8OrDispatchError,
+};
+use std::num;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+enum Error {
+    #[error("failed to parse int")]
+    ParsingInt,
+}
+
+impl From<num::ParseIntError> for Error {
+    fn from(_: num::ParseIntError) -> Self {
+        Error::ParsingInt
+    }
+}
+
+impl From<std::convert::Infallible> for Error {
+    fn from(_: std::convert::Infallible) -> Self {
+        panic!()
+    }
+}
+
+struct State;
+
+impl Context for State {
+    type Error = Error;
+    type Ok = ();
+}
+
+#[test]
+fn debug_command_tree() {
+    #[command(usage = "tp <x> <y> <z>")]
+    async fn tp(_ctx: &mut State, _x: i32, _y: i32, _z: i32) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "tp <target>")]
+    async fn tp_target(_ctx: &mut State, _target: String) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "stop")]
+    async fn stop(_ctx: &mut State) -> Result<(), Error> {
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default()
+        .with(tp)
+        .with(tp_target)
+        .with(stop);
+
+    assert_eq!(
+        format!("{:?}", dispatcher),
+        "tp\n  <x>\n    <y>\n      <z> * <exec>\n  <target> * <exec>\nstop * <exec>\n"
+    );
+}
+
+#[test]
+fn dot_export() {
+    #[command(usage = "tp <target>")]
+    async fn tp(_ctx: &mut State, _target: String) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "stop")]
+    async fn stop(_ctx: &mut State) -> Result<(), Error> {
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(tp).with(stop);
+    let dot = dispatcher.to_dot();
+
+    assert!(dot.starts_with("digraph commands {\n"));
+    assert!(dot.ends_with("}\n"));
+    assert!(dot.contains("n0 [label=\"tp\", shape=box];"));
+    assert!(dot.contains("n1 [label=\"<target>\", shape=ellipse, peripheries=2];"));
+    assert!(dot.contains("n2 [label=\"stop\", shape=box, peripheries=2];"));
+    assert!(dot.contains("root -> n0;"));
+    assert!(dot.contains("root -> n2;"));
+    assert!(dot.contains("n0 -> n1;"));
+    assert!(!dot.contains("n0 -> n2;"));
+}
+
+#[test]
+fn dispatch_bytes() {
+    #[derive(Default)]
+    struct Counter(i32);
+
+    impl Context for Counter {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "add <x>")]
+    async fn add(ctx: &mut Counter, x: i32) -> Result<(), Error> {
+        ctx.0 += x;
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(add);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut counter = Counter::default();
+
+    assert!(smol::block_on(dispatcher.dispatch_bytes(
+        &mut nodes,
+        &mut errors,
+        &mut counter,
+        b"add 5"
+    ))
+    .is_ok());
+    assert_eq!(counter.0, 5);
+
+    assert!(matches!(
+        smol::block_on(dispatcher.dispatch_bytes(
+            &mut nodes,
+            &mut errors,
+            &mut counter,
+            b"add \xff"
+        )),
+        Err(Utf8OrDispatchError::Utf8(_))
+    ));
+    assert!(matches!(
+        smol::block_on(dispatcher.dispatch_bytes(
+            &mut nodes,
+            &mut errors,
+            &mut counter,
+            b"add five"
+        )),
+        Err(Utf8OrDispatchError::Dispatch(_))
+    ));
+    assert_eq!(counter.0, 5);
+}
+
+#[test]
+fn pure_literal_commands() {
+    #[derive(Default)]
+    struct Last(&'static str);
+
+    impl Context for Last {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "server <name>")]
+    async fn server(last: &mut Last, _name: String) -> Result<(), Error> {
+        last.0 = "server";
+        Ok(())
+    }
+
+    #[command(usage = "server reload")]
+    async fn reload(last: &mut Last) -> Result<(), Error> {
+        last.0 = "reload";
+        Ok(())
+    }
+
+    #[command(usage = "stop")]
+    async fn stop(last: &mut Last) -> Result<(), Error> {
+        last.0 = "stop";
+        Ok(())
+    }
+
+    let mut dispatcher = CommandDispatcher::default()
+        .with(server)
+        .with(reload)
+        .with(stop);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut dispatch = |dispatcher: &CommandDispatcher<Last>, input| {
+        let mut last = Last::default();
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut last, input))
+            .ok()
+            .map(|_| last.0)
+    };
+
+    assert_eq!(dispatch(&dispatcher, "server reload"), Some("reload"));
+    assert_eq!(dispatch(&dispatcher, "server lobby"), Some("server"));
+    assert_eq!(dispatch(&dispatcher, "stop"), Some("stop"));
+    assert_eq!(dispatch(&dispatcher, "stop now"), None);
+
+    dispatcher.set_enabled("stop", false);
+    assert_eq!(dispatch(&dispatcher, "stop"), None);
+
+    #[command(usage = "stop")]
+    async fn halt(last: &mut Last) -> Result<(), Error> {
+        last.0 = "halt";
+        Ok(())
+    }
+
+    dispatcher.replace("stop", halt).unwrap();
+    assert_eq!(dispatch(&dispatcher, "stop"), Some("halt"));
+}
+
+#[test]
+fn failing_pure_literal_command_falls_back() {
+    struct Calls(u32);
+
+    impl Context for Calls {
+        type Error = Error;
+        type Ok = u32;
+    }
+
+    #[command(usage = "stop")]
+    async fn stop(calls: &mut Calls) -> Result<u32, Error> {
+        calls.0 += 1;
+        Err(Error::ParsingInt)
+    }
+
+    #[command(usage = "<word>")]
+    async fn word(_calls: &mut Calls, _word: String) -> Result<u32, Error> {
+        Ok(2)
+    }
+
+    let mut errors = Vec::new();
+    let mut calls = Calls(0);
+    for dispatcher in [
+        CommandDispatcher::default().with(stop).with(word),
+        CommandDispatcher::default()
+            .with(stop)
+            .with(word)
+            .with_match_cache(4),
+    ] {
+        for _ in 0..2 {
+            let result = smol::block_on(dispatcher.dispatch(
+                &mut Vec::new(),
+                &mut errors,
+                &mut calls,
+                "stop",
+            ));
+            assert_eq!(result, Ok(2));
+            assert_eq!(errors, [Error::ParsingInt]);
+        }
+    }
+    assert_eq!(calls.0, 4);
+}
+
+#[test]
+fn prioritized_parsers_outrank_pure_literal_commands() {
+    struct Server;
+
+    impl Context for Server {
+        type Error = Error;
+        type Ok = &'static str;
+    }
+
+    #[command(usage = "server <name> <motd>", priority = 1)]
+    async fn motd(
+        _server: &mut Server,
+        _name: String,
+        _motd: String,
+    ) -> Result<&'static str, Error> {
+        Ok("motd")
+    }
+
+    #[command(usage = "server reload all")]
+    async fn reload(_server: &mut Server) -> Result<&'static str, Error> {
+        Ok("reload")
+    }
+
+    let dispatcher = CommandDispatcher::default().with(motd).with(reload);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let dispatched = smol::block_on(dispatcher.dispatch(
+        &mut nodes,
+        &mut errors,
+        &mut Server,
+        "server reload all",
+    ));
+    assert_eq!(dispatched, Ok("motd"));
+    let success =
+        smol::block_on(dispatcher.dispatch_full(&mut Server, "server reload all")).unwrap();
+    assert_eq!(success.ok, "motd");
+    assert_eq!(success.command.path().to_string(), "server <name> <motd>");
+}
+
+#[test]
+fn command_count() {
+    #[command(usage = "config reload")]
+    async fn reload(_ctx: &mut State) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "config set <key> <value>")]
+    async fn set(_ctx: &mut State, _key: String, _value: i32) -> Result<(), Error> {
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::<State>::default();
+    assert!(dispatcher.is_empty());
+    assert_eq!(dispatcher.len(), 0);
+
+    // Registering the same command again is a no-op.
+    let dispatcher = dispatcher.with(reload).with(set).with(reload);
+    assert!(!dispatcher.is_empty());
+    assert_eq!(dispatcher.len(), 2);
+}
+
+#[test]
+fn merge_shared_nodes() {
+    #[command(usage = "config reload")]
+    async fn reload(_ctx: &mut State) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "config set <key> <value>")]
+    async fn set(_ctx: &mut State, _key: String, _value: i32) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "config set <name> default")]
+    async fn set_default(_ctx: &mut State, _name: String) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "config set <key> <value> <ttl>")]
+    async fn set_ttl(_ctx: &mut State, _key: String, _value: i32, _ttl: i32) -> Result<(), Error> {
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default()
+        .with(reload)
+        .with(set)
+        .with(set_default)
+        .with(set_ttl);
+
+    assert_eq!(dispatcher.node_count(), 7);
+    assert_eq!(
+        format!("{:?}", dispatcher),
+        "config\n  reload * <exec>\n  set\n    <key>\n      default * <exec>\n      <value> * <exec>\n        <ttl> * <exec>\n"
+    );
+}
+
+#[test]
+fn find_node() {
+    #[command(usage = "config reload")]
+    async fn reload(_ctx: &mut State) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "config set <key> <value>", description = "Sets a value.")]
+    async fn set(_ctx: &mut State, _key: String, _value: i32) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "config set default")]
+    async fn set_default(_ctx: &mut State) -> Result<(), Error> {
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default()
+        .with(reload)
+        .with(set)
+        .with(set_default);
+
+    let node = dispatcher.find(&["config", "set"]).unwrap();
+    assert_eq!(node.argument().to_string(), "set");
+    assert!(!node.is_executable());
+    assert_eq!(
+        node.children()
+            .map(|child| child.argument().to_string())
+            .collect::<Vec<_>>(),
+        vec!["default", "<key>"]
+    );
+
+    let value = dispatcher
+        .find(&["config", "set", "<key>", "<value>"])
+        .unwrap();
+    assert!(value.is_executable());
+    assert_eq!(value.children().count(), 0);
+    assert_eq!(
+        value
+            .commands()
+            .map(|command| command.description.as_deref())
+            .collect::<Vec<_>>(),
+        vec![Some("Sets a value.")]
+    );
+
+    assert!(dispatcher.find(&["config", "get"]).is_none());
+    assert!(dispatcher.find(&[]).is_none());
+}
+
+#[test]
+fn replace_command() {
+    #[derive(Default)]
+    struct Counter(i32);
+
+    impl Context for Counter {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "add <x>")]
+    async fn add(ctx: &mut Counter, x: i32) -> Result<(), Error> {
+        ctx.0 += x;
+        Ok(())
+    }
+
+    #[command(usage = "add <x> <y>")]
+    async fn add_two(ctx: &mut Counter, x: i32, y: i32) -> Result<(), Error> {
+        ctx.0 += x + y;
+        Ok(())
+    }
+
+    #[command(usage = "add twice <x>")]
+    async fn add_twice(ctx: &mut Counter, x: i32) -> Result<(), Error> {
+        ctx.0 += 2 * x;
+        Ok(())
+    }
+
+    #[command(usage = "reset")]
+    async fn reset(ctx: &mut Counter) -> Result<(), Error> {
+        ctx.0 = 0;
+        Ok(())
+    }
+
+    let mut dispatcher = CommandDispatcher::default()
+        .with(add)
+        .with(add_two)
+        .with(reset);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut counter = Counter::default();
+    let mut dispatch = |dispatcher: &CommandDispatcher<Counter>, input| {
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut counter, input))
+            .ok()
+            .and(Some(0))
+    };
+
+    dispatcher.replace("add", add_twice).unwrap();
+    assert_eq!(dispatch(&dispatcher, "add 1"), None);
+    assert_eq!(dispatch(&dispatcher, "add 1 2"), None);
+    assert_eq!(dispatch(&dispatcher, "add twice 3"), Some(0));
+    assert_eq!(dispatch(&dispatcher, "reset"), Some(0));
+    assert_eq!(dispatcher.commands().count(), 2);
+    assert_eq!(dispatcher.node_count(), 4);
+
+    // An invalid replacement leaves the old command in place.
+    let mut empty = add.build();
+    empty.arguments.clear();
+    assert_eq!(
+        dispatcher.replace("add", empty),
+        Err(RegisterError::ExecutableRoot)
+    );
+    assert_eq!(dispatch(&dispatcher, "add twice 3"), Some(0));
+    assert_eq!(dispatcher.commands().count(), 2);
+    assert_eq!(
+        format!("{:?}", dispatcher),
+        "reset * <exec>\nadd\n  twice\n    <x> * <exec>\n"
+    );
+}
+
+#[test]
+fn override_command() {
+    #[derive(Default)]
+    struct Server {
+        stopped_by: Option<&'static str>,
+    }
+
+    impl Context for Server {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "stop")]
+    async fn stop(server: &mut Server) -> Result<(), Error> {
+        server.stopped_by = Some("built-in");
+        Ok(())
+    }
+
+    #[command(usage = "stop")]
+    async fn plugin_stop(server: &mut Server) -> Result<(), Error> {
+        server.stopped_by = Some("plugin");
+        Ok(())
+    }
+
+    #[command(usage = "save [world]")]
+    async fn save(_server: &mut Server, _world: Option<String>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "save")]
+    async fn save_all(_server: &mut Server) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "restart")]
+    async fn restart(_server: &mut Server) -> Result<(), Error> {
+        Ok(())
+    }
+
+    let mut dispatcher = CommandDispatcher::default().with(stop).with(save);
+    assert_eq!(dispatcher.register_override(plugin_stop), Ok(true));
+    assert!(dispatcher.warnings().is_empty());
+    assert_eq!(dispatcher.commands().count(), 2);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut server = Server::default();
+    assert!(
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut server, "stop")).is_ok()
+    );
+    assert_eq!(server.stopped_by, Some("plugin"));
+
+    // `save [world]` is still run when given a world.
+    assert_eq!(dispatcher.register_override(save_all), Ok(true));
+    assert_eq!(dispatcher.commands().count(), 3);
+    assert!(smol::block_on(dispatcher.dispatch(
+        &mut nodes,
+        &mut errors,
+        &mut server,
+        "save world"
+    ))
+    .is_ok());
+
+    assert_eq!(dispatcher.register_override(restart), Ok(false));
+}
+
+#[test]
+fn shadowed_command_warning() {
+    #[command(usage = "tp <x>")]
+    async fn tp(_ctx: &mut State, _x: i32) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "tp <target>")]
+    async fn tp_target(_ctx: &mut State, _target: String) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "tp <y> [z]")]
+    async fn tp_optional(_ctx: &mut State, _y: i32, _z: Option<i32>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    let mut dispatcher = CommandDispatcher::default().with(tp).with(tp_target);
+    assert!(dispatcher.warnings().is_empty());
+
+    dispatcher.register(tp).unwrap();
+    assert!(dispatcher.warnings().is_empty());
+
+    dispatcher.register(tp_optional).unwrap();
+    assert_eq!(
+        dispatcher.warnings(),
+        &[RegisterWarning::ShadowedCommand(CommandPath(vec![
+            "tp".into(),
+            "<y>".into()
+        ]))]
+    );
+}
+
+#[test]
+fn custom_separator() {
+    #[derive(Default)]
+    struct Position(i32, i32, i32);
+
+    impl Context for Position {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "tp <x> <y> <z>")]
+    async fn tp(ctx: &mut Position, x: i32, y: i32, z: i32) -> Result<(), Error> {
+        *ctx = Position(x, y, z);
+        Ok(())
+    }
+
+    let piped = CommandDispatcher::default().with(tp).with_separator('|');
+    let tabbed = CommandDispatcher::default().with(tp).with_separator('\t');
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut position = Position::default();
+
+    assert!(
+        smol::block_on(piped.dispatch(&mut nodes, &mut errors, &mut position, "tp|1|2|3")).is_ok()
+    );
+    assert_eq!((position.0, position.1, position.2), (1, 2, 3));
+
+    assert!(
+        smol::block_on(tabbed.dispatch(&mut nodes, &mut errors, &mut position, "tp\t4\t5\t6"))
+            .is_ok()
+    );
+    assert_eq!((position.0, position.1, position.2), (4, 5, 6));
+
+    assert!(smol::block_on(piped.check(&position, "tp|1|2|3")).is_ok());
+    assert_eq!(
+        smol::block_on(piped.check(&position, "tp|1|two|3")),
+        Err(DispatchError::ArgumentParseFailed {
+            context: InputContext::new("tp|1|two|3", 5),
+            argument: "y".into(),
+            message: None,
+        })
+    );
+    assert_eq!(
+        smol::block_on(piped.suggestions(&position, "t")),
+        vec![lieutenant::Completion::new("tp")]
+    );
+}
+
+#[test]
+fn custom_tokenizer() {
+    #[derive(Default)]
+    struct Position(i32, i32, i32);
+
+    impl Context for Position {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "tp <x> <y> <z>")]
+    async fn tp(ctx: &mut Position, x: i32, y: i32, z: i32) -> Result<(), Error> {
+        *ctx = Position(x, y, z);
+        Ok(())
+    }
+
+    // Lowercases the input and splits it on commas, ignoring anything
+    // after a pipe.
+    let dispatcher = CommandDispatcher::default()
+        .with(tp)
+        .with_tokenizer(Box::new(|input| {
+            let input = input.split('|').next().unwrap_or_default();
+            input
+                .split(',')
+                .map(str::trim)
+                .filter(|token| !token.is_empty())
+                .map(|token| token.to_lowercase().into())
+                .collect()
+        }));
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut position = Position::default();
+
+    assert!(smol::block_on(dispatcher.dispatch(
+        &mut nodes,
+        &mut errors,
+        &mut position,
+        "TP, 1,2 ,, 3 | grep x"
+    ))
+    .is_ok());
+    assert_eq!((position.0, position.1, position.2), (1, 2, 3));
+
+    assert!(smol::block_on(dispatcher.dispatch(
+        &mut nodes,
+        &mut errors,
+        &mut position,
+        "tp,1|,2,3"
+    ))
+    .is_err());
+}
+
+#[test]
+fn parser_priority() {
+    #[derive(Debug, PartialEq, Eq)]
+    enum Picked {
+        None,
+        Number(i32),
+        Text(String),
+    }
+
+    impl Context for Picked {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "pick <value>", priority = 1)]
+    async fn pick_number(ctx: &mut Picked, value: i32) -> Result<(), Error> {
+        *ctx = Picked::Number(value);
+        Ok(())
+    }
+
+    #[command(usage = "pick <value>")]
+    async fn pick_text(ctx: &mut Picked, value: String) -> Result<(), Error> {
+        *ctx = Picked::Text(value);
+        Ok(())
+    }
+
+    #[command(usage = "pick <value>")]
+    async fn pick_number_low(ctx: &mut Picked, value: i32) -> Result<(), Error> {
+        *ctx = Picked::Number(value);
+        Ok(())
+    }
+
+    #[command(usage = "pick <value>", priority = 1)]
+    async fn pick_text_high(ctx: &mut Picked, value: String) -> Result<(), Error> {
+        *ctx = Picked::Text(value);
+        Ok(())
+    }
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+
+    // Registration order is the same in both dispatchers; only priorities differ.
+    let dispatcher = CommandDispatcher::default()
+        .with(pick_text)
+        .with(pick_number);
+    let mut picked = Picked::None;
+    assert!(
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut picked, "pick 5")).is_ok()
+    );
+    assert_eq!(picked, Picked::Number(5));
+
+    let dispatcher = CommandDispatcher::default()
+        .with(pick_text_high)
+        .with(pick_number_low);
+    let mut picked = Picked::None;
+    assert!(
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut picked, "pick 5")).is_ok()
+    );
+    assert_eq!(picked, Picked::Text("5".into()));
+}
+
+#[test]
+fn try_dispatch_before_locking() {
+    use std::sync::RwLock;
+
+    #[derive(Debug, Default, PartialEq, Eq)]
+    struct Counter {
+        value: i32,
+        writes: i32,
+    }
+
+    impl Context for Counter {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "add <x>")]
+    async fn add(ctx: &mut Counter, x: i32) -> Result<(), Error> {
+        ctx.value += x;
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(add);
+    let counter = RwLock::new(Counter::default());
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+
+    for input in &["hello world", "add", "add one", "add 2", "add 3"] {
+        let outcome = {
+            let counter = counter.read().unwrap();
+            smol::block_on(dispatcher.try_dispatch(&mut nodes, &counter, input))
+        };
+        if let DispatchOutcome::Matched(pending) = outcome {
+            let mut counter = counter.write().unwrap();
+            counter.writes += 1;
+            assert!(smol::block_on(pending.run(&mut errors, &mut counter)).is_ok());
+        }
+    }
+
+    assert_eq!(
+        *counter.read().unwrap(),
+        Counter {
+            value: 5,
+            writes: 2
+        }
+    );
+}
+
+#[test]
+fn dispatch_scoped_only_borrows_context_to_execute() {
+    use std::cell::Cell;
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct Counter(i32);
+
+    impl Context for Counter {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "set <x>")]
+    async fn set(ctx: &mut Counter, x: i32) -> Result<(), Error> {
+        *ctx = Counter(x);
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(set);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+
+    let view = Counter(0);
+    let mut counter = Counter(0);
+    let accessed = Cell::new(0);
+
+    assert!(smol::block_on(dispatcher.dispatch_scoped(
+        &mut nodes,
+        &mut errors,
+        &view,
+        "unknown 5",
+        || {
+            accessed.set(accessed.get() + 1);
+            &mut counter
+        }
+    ))
+    .is_err());
+    assert_eq!(accessed.get(), 0);
+
+    assert!(smol::block_on(dispatcher.dispatch_scoped(
+        &mut nodes,
+        &mut errors,
+        &view,
+        "set five",
+        || {
+            accessed.set(accessed.get() + 1);
+            &mut counter
+        }
+    ))
+    .is_err());
+    assert_eq!(accessed.get(), 0);
+
+    assert!(smol::block_on(dispatcher.dispatch_scoped(
+        &mut nodes,
+        &mut errors,
+        &view,
+        "set 5",
+        || {
+            accessed.set(accessed.get() + 1);
+            &mut counter
+        }
+    ))
+    .is_ok());
+    assert_eq!(accessed.get(), 1);
+    assert_eq!(counter, Counter(5));
+}
+
+#[test]
+fn is_complete() {
+    #[command(usage = "tp <x> <y> <z>")]
+    async fn tp(_ctx: &mut State, _x: i32, _y: i32, _z: i32) -> Result<(), Error> {
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(tp);
+
+    assert!(!smol::block_on(dispatcher.is_complete(&State, "tp")));
+    assert!(!smol::block_on(dispatcher.is_complete(&State, "tp 1 2")));
+    assert!(smol::block_on(dispatcher.is_complete(&State, "tp 1 2 3")));
+    assert!(!smol::block_on(
+        dispatcher.is_complete(&State, "tp 1 2 3 4")
+    ));
+    assert!(!smol::block_on(dispatcher.is_complete(&State, "tp 1 2 z")));
+}
+
+#[test]
+fn literal_suggestions() {
+    #[command(usage = "teleport <x>")]
+    async fn teleport(_ctx: &mut State, _x: i32) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "team add <name>")]
+    async fn team_add(_ctx: &mut State, _name: String) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "team remove <name>")]
+    async fn team_remove(_ctx: &mut State, _name: String) -> Result<(), Error> {
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default()
+        .with(teleport)
+        .with(team_add)
+        .with(team_remove);
+
+    let suggestions = |input| -> Vec<String> {
+        smol::block_on(dispatcher.suggestions(&State, input))
+            .into_iter()
+            .map(|completion| completion.text)
+            .collect()
+    };
+
+    assert_eq!(suggestions("te"), vec!["teleport", "team"]);
+    assert_eq!(suggestions("team "), vec!["add", "remove"]);
+    assert_eq!(suggestions("team r"), vec!["remove"]);
+    assert!(smol::block_on(dispatcher.suggestions(&State, "stop")).is_empty());
+}
+
+#[test]
+fn ranked_suggestions() {
+    #[command(usage = "stop")]
+    async fn stop(_ctx: &mut State) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "setspawn")]
+    async fn setspawn(_ctx: &mut State) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "spawn")]
+    async fn spawn(_ctx: &mut State) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "kill")]
+    async fn kill(_ctx: &mut State) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "respawn")]
+    async fn respawn(_ctx: &mut State) -> Result<(), Error> {
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default()
+        .with(stop)
+        .with(setspawn)
+        .with(spawn)
+        .with(kill)
+        .with(respawn);
+
+    let suggestions = |dispatcher: &CommandDispatcher<State>, input| -> Vec<String> {
+        smol::block_on(dispatcher.suggestions(&State, input))
+            .into_iter()
+            .map(|completion| completion.text)
+            .collect()
+    };
+
+    assert_eq!(suggestions(&dispatcher, "sp"), ["spawn"]);
+
+    let dispatcher = dispatcher.with_ranking(lieutenant::Completion::rank);
+    assert_eq!(
+        suggestions(&dispatcher, "sp"),
+        ["spawn", "setspawn", "respawn", "stop"]
+    );
+
+    let dispatcher = dispatcher
+        .with_ranking(|partial, text| text.contains(partial).then_some(text.len() as u32));
+    assert_eq!(
+        suggestions(&dispatcher, "sp"),
+        ["setspawn", "respawn", "spawn"]
+    );
+}
+
+#[test]
+fn validate_lines() {
+    #[command(usage = "tp <x> <y> <z>")]
+    async fn tp(_ctx: &mut State, _x: i32, _y: i32, _z: i32) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "stop")]
+    async fn stop(_ctx: &mut State) -> Result<(), Error> {
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(tp).with(stop);
+
+    let config = "tp 1 2 3\ntp 1 2\n\nstop\nteleport 1 2 3\n";
+    assert_eq!(
+        smol::block_on(dispatcher.validate_lines(&State, config.lines())),
+        vec![
+            (1, Ok(())),
+            (
+                2,
+                Err(DispatchError::UnknownCommand(InputContext::new(
+                    "tp 1 2", 6
+                )))
+            ),
+            (4, Ok(())),
+            (
+                5,
+                Err(DispatchError::UnknownCommand(InputContext::new(
+                    "teleport 1 2 3",
+                    0
+                )))
+            ),
+        ]
+    );
+}
+
+#[test]
+fn diff_command_trees() {
+    #[command(usage = "tp <x> <y> <z>")]
+    async fn tp(_ctx: &mut State, _x: i32, _y: i32, _z: i32) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "stop", description = "Stops the server.")]
+    async fn stop(_ctx: &mut State) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "stop", description = "Stops the server gracefully.")]
+    async fn stop_gracefully(_ctx: &mut State) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "tp <target>")]
+    async fn tp_target(_ctx: &mut State, _target: String) -> Result<(), Error> {
+        Ok(())
+    }
+
+    let old = CommandDispatcher::default().with(tp).with(stop);
+    assert!(old
+        .diff(&CommandDispatcher::default().with(tp).with(stop))
+        .is_empty());
+
+    let new = CommandDispatcher::default()
+        .with(tp)
+        .with(stop)
+        .with(tp_target);
+    assert_eq!(
+        old.diff(&new),
+        TreeDiff {
+            added: vec![CommandPath(vec!["tp".into(), "<target>".into()])],
+            ..TreeDiff::default()
+        }
+    );
+    assert_eq!(
+        new.diff(&old),
+        TreeDiff {
+            removed: vec![CommandPath(vec!["tp".into(), "<target>".into()])],
+            ..TreeDiff::default()
+        }
+    );
+
+    let reloaded = CommandDispatcher::default().with(tp).with(stop_gracefully);
+    assert_eq!(
+        old.diff(&reloaded),
+        TreeDiff {
+            changed: vec![CommandPath(vec!["stop".into()])],
+            ..TreeDiff::default()
+        }
+    );
+}
+
+#[test]
+fn structurally_equal_trees() {
+    #[command(usage = "tp <x> <y> <z>")]
+    async fn tp(_ctx: &mut State, _x: i32, _y: i32, _z: i32) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "tp <x> <y> <z>")]
+    async fn tp_bounded(_ctx: &mut State, x: i32, y: i32, z: i32) -> Result<(), Error> {
+        if [x, y, z].iter().any(|coord| coord.abs() > 30_000_000) {
+            return Err(Error::ParsingInt);
+        }
+        Ok(())
+    }
+
+    #[command(usage = "tp <x> <y> <z>")]
+    async fn tp_named(_ctx: &mut State, _x: String, _y: i32, _z: i32) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "stop", description = "Stops the server.")]
+    async fn stop(_ctx: &mut State) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "stop", description = "Stops the server gracefully.")]
+    async fn stop_gracefully(_ctx: &mut State) -> Result<(), Error> {
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(tp).with(stop);
+    assert!(dispatcher.structurally_eq(&CommandDispatcher::default().with(tp_bounded).with(stop)));
+    assert!(!dispatcher.structurally_eq(&CommandDispatcher::default().with(tp_named).with(stop)));
+    assert!(
+        !dispatcher.structurally_eq(&CommandDispatcher::default().with(tp).with(stop_gracefully))
+    );
+    assert!(!dispatcher.structurally_eq(&CommandDispatcher::default().with(tp)));
+}
+
+#[test]
+fn register_identical_command_twice() {
+    #[derive(Default)]
+    struct Counter(i32);
+
+    impl Context for Counter {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "fail [x]")]
+    async fn fail(ctx: &mut Counter, _x: Option<i32>) -> Result<(), Error> {
+        ctx.0 += 1;
+        Err(Error::ParsingInt)
+    }
+
+    let mut dispatcher = CommandDispatcher::default();
+    assert!(dispatcher.register(fail).is_ok());
+    assert!(dispatcher.register(fail).is_ok());
+    assert_eq!(dispatcher.commands().count(), 1);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    for input in &["fail", "fail 1"] {
+        let mut counter = Counter::default();
+        assert_eq!(
+            smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut counter, input)),
+            Err(&vec![Error::ParsingInt])
+        );
+        assert_eq!(counter.0, 1);
+    }
+}
+
+#[test]
+fn trailing_input() {
+    #[derive(Default)]
+    struct Stopped(bool);
+
+    impl Context for Stopped {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "stop")]
+    async fn stop(ctx: &mut Stopped) -> Result<(), Error> {
+        ctx.0 = true;
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(stop);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut stopped = Stopped::default();
+
+    assert!(smol::block_on(dispatcher.dispatch(
+        &mut nodes,
+        &mut errors,
+        &mut stopped,
+        "stop extra"
+    ))
+    .is_err());
+    assert!(!stopped.0);
+    assert_eq!(
+        smol::block_on(dispatcher.check(&stopped, "stop extra")),
+        Err(DispatchError::TrailingInput(InputContext::new(
+            "stop extra",
+            5
+        )))
+    );
+    assert_eq!(
+        smol::block_on(dispatcher.check(&stopped, "start")),
+        Err(DispatchError::UnknownCommand(InputContext::new("start", 0)))
+    );
+
+    assert!(
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut stopped, "stop")).is_ok()
+    );
+    assert!(stopped.0);
+}
+
+#[test]
+fn disable_command() {
+    #[derive(Default)]
+    struct Counter(i32);
+
+    impl Context for Counter {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "add <x>")]
+    async fn add(ctx: &mut Counter, x: i32) -> Result<(), Error> {
+        ctx.0 += x;
+        Ok(())
+    }
+
+    #[command(usage = "stop")]
+    async fn stop(_ctx: &mut Counter) -> Result<(), Error> {
+        Ok(())
+    }
+
+    let mut dispatcher = CommandDispatcher::default().with(add).with(stop);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut counter = Counter::default();
+
+    dispatcher.set_enabled("add", false);
+    assert!(
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut counter, "add 1"))
+            .is_err()
+    );
+    assert_eq!(counter.0, 0);
+    assert_eq!(
+        smol::block_on(dispatcher.check(&counter, "add 1")),
+        Err(DispatchError::UnknownCommand(InputContext::new("add 1", 0)))
+    );
+    assert_eq!(
+        smol::block_on(dispatcher.suggestions(&counter, "")).len(),
+        1
+    );
+    assert!(smol::block_on(dispatcher.check(&counter, "stop")).is_ok());
+
+    dispatcher.set_enabled("add", true);
+    assert!(
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut counter, "add 1")).is_ok()
+    );
+    assert_eq!(counter.0, 1);
+    assert_eq!(
+        smol::block_on(dispatcher.suggestions(&counter, "")).len(),
+        2
+    );
+}
+
+#[test]
+fn dispatch_returns_matched_command() {
+    #[command(usage = "tp <x> <y> <z>", description = "Teleports to a position.")]
+    async fn tp(_ctx: &mut State, _x: i32, _y: i32, _z: i32) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "tp <target>", description = "Teleports to a player.")]
+    async fn tp_target(_ctx: &mut State, _target: String) -> Result<(), Error> {
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(tp).with(tp_target);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+
+    let (_, command) = smol::block_on(dispatcher.dispatch_matched(
+        &mut nodes,
+        &mut errors,
+        &mut State,
+        "tp 1 2 3",
+    ))
+    .unwrap();
+    assert_eq!(command.path().to_string(), "tp <x> <y> <z>");
+
+    let (_, command) = smol::block_on(dispatcher.dispatch_matched(
+        &mut nodes,
+        &mut errors,
+        &mut State,
+        "tp alice",
+    ))
+    .unwrap();
+    assert_eq!(command.path().to_string(), "tp <target>");
+    assert_eq!(
+        command.description.as_deref(),
+        Some("Teleports to a player.")
+    );
+}
+
+#[test]
+fn interned_literals() {
+    #[derive(Default)]
+    struct Ran(Option<&'static str>);
+
+    impl Context for Ran {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "world border set center")]
+    async fn set_center(ran: &mut Ran) -> Result<(), Error> {
+        ran.0 = Some("world border set center");
+        Ok(())
+    }
+
+    #[command(usage = "world border set size")]
+    async fn set_size(ran: &mut Ran) -> Result<(), Error> {
+        ran.0 = Some("world border set size");
+        Ok(())
+    }
+
+    #[command(usage = "world border get center")]
+    async fn get_center(ran: &mut Ran) -> Result<(), Error> {
+        ran.0 = Some("world border get center");
+        Ok(())
+    }
+
+    #[command(usage = "world spawn set")]
+    async fn set_spawn(ran: &mut Ran) -> Result<(), Error> {
+        ran.0 = Some("world spawn set");
+        Ok(())
+    }
+
+    #[command(usage = "set world")]
+    async fn set_world(ran: &mut Ran) -> Result<(), Error> {
+        ran.0 = Some("set world");
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default()
+        .with(set_center)
+        .with(set_size)
+        .with(get_center)
+        .with(set_spawn)
+        .with(set_world);
+    let usages = [
+        "world border set center",
+        "world border set size",
+        "world border get center",
+        "world spawn set",
+        "set world",
+    ];
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+
+    for input in &[
+        "world border set center",
+        "world border set size",
+        "world border get center",
+        "world border get size",
+        "world spawn set",
+        "world spawn get",
+        "world set",
+        "set world",
+        "set border",
+        "world border set center now",
+        "border set size",
+        "world",
+        "",
+    ] {
+        let naive = usages.iter().copied().find(|usage| usage == input);
+
+        let mut ran = Ran::default();
+        let result = smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut ran, input));
+        assert_eq!(result.is_ok(), naive.is_some(), "{}", input);
+        assert_eq!(ran.0, naive, "{}", input);
+    }
+}
+
+#[test]
+fn register_all() {
+    #[command(usage = "tp <x> <y> <z>")]
+    async fn tp(_ctx: &mut State, _x: i32, _y: i32, _z: i32) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "[page]")]
+    async fn help(_ctx: &mut State, _page: Option<i32>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "stop")]
+    async fn stop(_ctx: &mut State) -> Result<(), Error> {
+        Ok(())
+    }
+
+    let commands: Vec<Box<dyn Command<State>>> = vec![Box::new(tp), Box::new(help), Box::new(stop)];
+
+    let mut dispatcher = CommandDispatcher::default();
+    assert_eq!(
+        dispatcher.register_all(commands),
+        vec![Ok(()), Err(RegisterError::ExecutableRoot), Ok(())]
+    );
+    assert_eq!(
+        dispatcher.walk(),
+        vec![
+            CommandPath(vec!["tp".into(), "<x>".into(), "<y>".into(), "<z>".into()]),
+            CommandPath(vec!["stop".into()]),
+        ]
+    );
+}
+
+#[test]
+fn typed_walk() {
+    #[command(usage = "tp <x> <y> <z>")]
+    async fn tp(_ctx: &mut State, _x: i32, _y: i32, _z: i64) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "give <player> [amount]")]
+    async fn give(_ctx: &mut State, _player: String, _amount: Option<u8>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(tp).with(give);
+    let paths: Vec<String> = dispatcher
+        .walk_typed()
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    assert_eq!(
+        paths,
+        [
+            "tp <x:i32> <y:i32> <z:i64>",
+            "give <player:String>",
+            "give <player:String> [amount:u8]",
+        ]
+    );
+    assert_eq!(
+        dispatcher
+            .find(&["give", "<player>"])
+            .unwrap()
+            .argument()
+            .typed_label(),
+        "<player:String>"
+    );
+}
+
+#[test]
+fn error_input_context() {
+    #[command(usage = "team add <name>")]
+    async fn team_add(_ctx: &mut State, _name: String) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "tp <x> <y> <z>")]
+    async fn tp(_ctx: &mut State, _x: i32, _y: i32, _z: i32) -> Result<(), Error> {
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(team_add).with(tp);
+
+    let error = smol::block_on(dispatcher.check(&State, "team remove alice")).unwrap_err();
+    assert_eq!(error.context().line(), "team remove alice");
+    assert_eq!(error.context().position(), 5);
+    assert_eq!(error.context().token(), "remove");
+    assert_eq!(error.context().remaining(), "remove alice");
+    assert_eq!(error.to_string(), "unknown command at `remove`");
+
+    let error = smol::block_on(dispatcher.check(&State, "tp 1 two 3")).unwrap_err();
+    assert_eq!(error.context().line(), "tp 1 two 3");
+    assert_eq!(error.context().token(), "two");
+}
+
+#[test]
+fn input_context_clamps_position() {
+    let context = InputContext::new("tp 1", 10);
+    assert_eq!(context.position(), 4);
+    assert_eq!(context.remaining(), "");
+
+    let context = InputContext::new("say é", 5);
+    assert_eq!(context.position(), 4);
+    assert_eq!(context.token(), "é");
+}
+
+#[test]
+fn custom_argument_errors() {
+    #[command(usage = "give <amount>")]
+    async fn give(_ctx: &mut State, _amount: i32) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "take <amount>")]
+    async fn take(_ctx: &mut State, _amount: i32) -> Result<(), Error> {
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default()
+        .with(
+            give.build()
+                .on_error("amount", "Please enter a whole number between 1 and 100"),
+        )
+        .with(take);
+
+    let error = smol::block_on(dispatcher.check(&State, "give lots")).unwrap_err();
+    assert_eq!(
+        error,
+        DispatchError::ArgumentParseFailed {
+            context: InputContext::new("give lots", 5),
+            argument: "amount".into(),
+            message: Some("Please enter a whole number between 1 and 100".into()),
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "Please enter a whole number between 1 and 100"
+    );
+
+    let error = smol::block_on(dispatcher.check(&State, "take lots")).unwrap_err();
+    assert_eq!(
+        error,
+        DispatchError::ArgumentParseFailed {
+            context: InputContext::new("take lots", 5),
+            argument: "amount".into(),
+            message: None,
+        }
+    );
+    assert_eq!(error.to_string(), "invalid <amount> at `lots`");
+
+    assert_eq!(
+        smol::block_on(dispatcher.check(&State, "give")),
+        Err(DispatchError::UnknownCommand(InputContext::new("give", 4)))
+    );
+}
+
+#[test]
+fn commit_to_first_match() {
+    #[command(usage = "set <value> px", priority = 1)]
+    async fn set_pixels(_ctx: &mut State, _value: i32) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "set <name> <value>")]
+    async fn set_named(_ctx: &mut State, _name: String, _value: String) -> Result<(), Error> {
+        Ok(())
+    }
+
+    let backtracking = CommandDispatcher::default()
+        .with(set_pixels)
+        .with(set_named);
+    let committing = CommandDispatcher::default()
+        .with(set_pixels)
+        .with(set_named)
+        .with_commit(true);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+
+    for dispatcher in &[&backtracking, &committing] {
+        assert!(smol::block_on(dispatcher.dispatch(
+            &mut nodes,
+            &mut errors,
+            &mut State,
+            "set 5 px"
+        ))
+        .is_ok());
+        assert!(smol::block_on(dispatcher.dispatch(
+            &mut nodes,
+            &mut errors,
+            &mut State,
+            "set width 5"
+        ))
+        .is_ok());
+    }
+
+    // `5` matches `<value> px` first, which fails on `apples`.
+    assert!(smol::block_on(backtracking.dispatch(
+        &mut nodes,
+        &mut errors,
+        &mut State,
+        "set 5 apples"
+    ))
+    .is_ok());
+    assert!(smol::block_on(committing.dispatch(
+        &mut nodes,
+        &mut errors,
+        &mut State,
+        "set 5 apples"
+    ))
+    .is_err());
+    assert!(smol::block_on(committing.check(&State, "set 5 apples")).is_err());
+}
+
+#[test]
+fn collect_commands() {
+    #[derive(Default)]
+    struct Counter(i32);
+
+    impl Context for Counter {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "add <x>")]
+    async fn add(ctx: &mut Counter, x: i32) -> Result<(), Error> {
+        ctx.0 += x;
+        Ok(())
+    }
+
+    #[command(usage = "reset")]
+    async fn reset(ctx: &mut Counter) -> Result<(), Error> {
+        ctx.0 = 0;
+        Ok(())
+    }
+
+    #[command(usage = "stop")]
+    async fn stop(_ctx: &mut Counter) -> Result<(), Error> {
+        Ok(())
+    }
+
+    let commands: Vec<Box<dyn Command<Counter>>> =
+        vec![Box::new(add), Box::new(reset), Box::new(stop)];
+    let dispatcher: CommandDispatcher<Counter> = commands.into_iter().collect();
+    assert_eq!(dispatcher.commands().count(), 3);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut counter = Counter::default();
+    assert!(
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut counter, "add 3")).is_ok()
+    );
+    assert_eq!(counter.0, 3);
+}
+
+#[test]
+fn help_pages() {
+    struct Player {
+        admin: bool,
+    }
+
+    impl Context for Player {
+        type Error = Error;
+        type Ok = ();
+
+        fn has_permission(&self, permission: &str) -> bool {
+            self.admin || !permission.starts_with("admin.")
+        }
+    }
+
+    #[command(usage = "tp <x> <y> <z>")]
+    async fn tp(_ctx: &mut Player, _x: i32, _y: i32, _z: i32) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "stop", permission = "admin.stop")]
+    async fn stop(_ctx: &mut Player) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "help [page]")]
+    async fn help(_ctx: &mut Player, _page: Option<usize>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "ban <player>", permission = "admin.ban")]
+    async fn ban(_ctx: &mut Player, _player: String) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "msg <player> <message>")]
+    async fn msg(_ctx: &mut Player, _player: String, _message: String) -> Result<(), Error> {
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default()
+        .with(tp)
+        .with(stop)
+        .with(help)
+        .with(ban)
+        .with(msg);
+
+    let admin = Player { admin: true };
+    assert_eq!(
+        dispatcher.help_page(&admin, 1, 2),
+        vec!["ban <player>", "help [page]"]
+    );
+    assert_eq!(
+        dispatcher.help_page(&admin, 2, 2),
+        vec!["msg <player> <message>", "stop"]
+    );
+    assert_eq!(dispatcher.help_page(&admin, 3, 2), vec!["tp <x> <y> <z>"]);
+    assert!(dispatcher.help_page(&admin, 4, 2).is_empty());
+    assert!(dispatcher.help_page(&admin, 0, 2).is_empty());
+
+    let player = Player { admin: false };
+    assert_eq!(
+        dispatcher.help_page(&player, 1, 2),
+        vec!["help [page]", "msg <player> <message>"]
+    );
+    assert_eq!(dispatcher.help_page(&player, 2, 2), vec!["tp <x> <y> <z>"]);
+}
+
+#[test]
+fn sorted_commands() {
+    #[command(usage = "tp <x> <y> <z>")]
+    async fn tp(_ctx: &mut State, _x: i32, _y: i32, _z: i32) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "stop")]
+    async fn stop(_ctx: &mut State) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "ban <player>")]
+    async fn ban(_ctx: &mut State, _player: String) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "ban <player> <reason>")]
+    async fn ban_reason(_ctx: &mut State, _player: String, _reason: String) -> Result<(), Error> {
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default()
+        .with(tp)
+        .with(stop)
+        .with(ban_reason)
+        .with(ban);
+
+    let paths: Vec<String> = dispatcher
+        .sorted_commands()
+        .iter()
+        .map(|command| command.path().to_string())
+        .collect();
+    assert_eq!(
+        paths,
+        [
+            "ban <player>",
+            "ban <player> <reason>",
+            "stop",
+            "tp <x> <y> <z>"
+        ]
+    );
+}
+
+#[test]
+fn localized_usage() {
+    struct Player {
+        locale: &'static str,
+    }
+
+    impl Context for Player {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "give <player> [amount]", description = "give.description")]
+    async fn give(_ctx: &mut Player, _player: String, _amount: Option<u32>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    let mut dispatcher = CommandDispatcher::default().with(give);
+    dispatcher.set_localizer(|key, player: &Player| {
+        let translated = match (player.locale, key) {
+            ("de", "player") => "spieler",
+            ("de", "amount") => "anzahl",
+            ("de", "give.description") => "Gibt einem Spieler Gegenstände.",
+            (_, "give.description") => "Gives items to a player.",
+            (_, key) => key,
+        };
+        translated.to_owned()
+    });
+
+    let english = Player { locale: "en" };
+    let german = Player { locale: "de" };
+    let command = dispatcher.commands().next().unwrap();
+
+    assert_eq!(
+        dispatcher.usage(&english, command),
+        "give <player> [amount]"
+    );
+    assert_eq!(
+        dispatcher.usage(&german, command),
+        "give <spieler> [anzahl]"
+    );
+    assert_eq!(
+        dispatcher.description(&english, command).as_deref(),
+        Some("Gives items to a player.")
+    );
+    assert_eq!(
+        dispatcher.description(&german, command).as_deref(),
+        Some("Gibt einem Spieler Gegenstände.")
+    );
+    assert_eq!(
+        dispatcher.help_page(&german, 1, 10),
+        vec!["give <spieler> [anzahl]"]
+    );
+}
+
+#[test]
+fn with_capacity() {
+    #[command(usage = "tp <x> <y> <z>")]
+    async fn tp(_ctx: &mut State, _x: i32, _y: i32, _z: i32) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "tp <target>")]
+    async fn tp_target(_ctx: &mut State, _target: String) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "stop")]
+    async fn stop(_ctx: &mut State) -> Result<(), Error> {
+        Ok(())
+    }
+
+    let default = CommandDispatcher::default()
+        .with(tp)
+        .with(tp_target)
+        .with(stop);
+    let reserved = CommandDispatcher::with_capacity(64, 16)
+        .with(tp)
+        .with(tp_target)
+        .with(stop);
+
+    assert_eq!(format!("{:?}", reserved), format!("{:?}", default));
+    assert_eq!(reserved.walk(), default.walk());
+    assert!(reserved.diff(&default).is_empty());
+}
+
+#[test]
+fn lenient_trailing_input() {
+    use lieutenant::Trailing;
+
+    #[derive(Default)]
+    struct Shouted {
+        target: String,
+        message: String,
+    }
+
+    impl Context for Shouted {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "shout <target>")]
+    async fn shout(ctx: &mut Shouted, target: String, rest: Trailing) -> Result<(), Error> {
+        ctx.target = target;
+        ctx.message = rest.0;
+        Ok(())
+    }
+
+    let strict = CommandDispatcher::default().with(shout);
+    let lenient = CommandDispatcher::default().with(shout).with_lenient(true);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+
+    let mut shouted = Shouted::default();
+    assert!(smol::block_on(strict.dispatch(
+        &mut nodes,
+        &mut errors,
+        &mut shouted,
+        "shout alice extra words"
+    ))
+    .is_err());
+    assert!(
+        smol::block_on(strict.dispatch(&mut nodes, &mut errors, &mut shouted, "shout alice"))
+            .is_ok()
+    );
+    assert_eq!(shouted.target, "alice");
+    assert_eq!(shouted.message, "");
+
+    let mut shouted = Shouted::default();
+    assert!(smol::block_on(lenient.dispatch(
+        &mut nodes,
+        &mut errors,
+        &mut shouted,
+        "shout alice extra words"
+    ))
+    .is_ok());
+    assert_eq!(shouted.target, "alice");
+    assert_eq!(shouted.message, "extra words");
+    assert!(smol::block_on(lenient.check(&shouted, "shout alice extra words")).is_ok());
+    assert!(smol::block_on(lenient.check(&shouted, "whisper alice")).is_err());
+}
+
+#[test]
+fn middleware() {
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct Player {
+        muted: bool,
+        said: Vec<String>,
+    }
+
+    impl Context for Player {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "say <message>")]
+    async fn say(ctx: &mut Player, message: String) -> Result<(), Error> {
+        ctx.said.push(message);
+        Ok(())
+    }
+
+    struct Log(Arc<Mutex<Vec<String>>>);
+
+    impl Middleware<Player> for Log {
+        fn before(&self, _ctx: &mut Player, command: &str) -> bool {
+            self.0.lock().unwrap().push(format!("before {}", command));
+            true
+        }
+
+        fn after(&self, _ctx: &mut Player, command: &str, executed: bool) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("after {} {}", command, executed));
+        }
+    }
+
+    struct Mute;
+
+    impl Middleware<Player> for Mute {
+        fn before(&self, ctx: &mut Player, _command: &str) -> bool {
+            !ctx.muted
+        }
+    }
+
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut dispatcher = CommandDispatcher::default().with(say);
+    dispatcher.add_middleware(Log(Arc::clone(&log)));
+    dispatcher.add_middleware(Mute);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut player = Player::default();
+
+    assert!(
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut player, "say hi")).is_ok()
+    );
+    player.muted = true;
+    assert!(
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut player, "say hey"))
+            .is_err()
+    );
+    assert!(errors.is_empty());
+
+    assert_eq!(player.said, vec!["hi"]);
+    assert_eq!(
+        *log.lock().unwrap(),
+        vec![
+            "before say hi",
+            "after say hi true",
+            "before say hey",
+            "after say hey false",
+        ]
+    );
+}
+
+#[test]
+fn stateful_executable() {
+    #[derive(Default)]
+    struct Invocations(usize);
+
+    impl Context for Invocations {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    let mut counter = 0;
+    let spec = CommandSpec {
+        arguments: vec![Argument::Literal {
+            value: "count".into(),
+        }],
+        description: None,
+        permissions: Vec::new(),
+        deprecated: None,
+        named: false,
+        exec: Executable::boxed(move |ctx: &mut Invocations, _| {
+            counter += 1;
+            ctx.0 = counter;
+            Box::pin(async { Ok(()) })
+        }),
+        extract: None,
+    };
+    let dispatcher = CommandDispatcher::default().with(spec);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut invocations = Invocations::default();
+
+    for expected in 1..=2 {
+        assert!(smol::block_on(dispatcher.dispatch(
+            &mut nodes,
+            &mut errors,
+            &mut invocations,
+            "count"
+        ))
+        .is_ok());
+        assert_eq!(invocations.0, expected);
+    }
+}
+
+#[test]
+fn analyze_partial_input() {
+    #[derive(Default)]
+    struct State;
+
+    impl Context for State {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "tp <x> <y> <z>")]
+    async fn tp(_state: &mut State, x: i32, y: i32, z: i32) -> Result<(), Error> {
+        let _ = (x, y, z);
+        Ok(())
+    }
+
+    #[command(usage = "stop")]
+    async fn stop(_state: &mut State) -> Result<(), Error> {
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(tp).with(stop);
+    let analyze = |input| smol::block_on(dispatcher.analyze(&State, input));
+
+    assert_eq!(
+        analyze("tp 1 two"),
+        ParseAnalysis {
+            path: CommandPath(vec!["tp".into(), "<x>".into()]),
+            offset: 5,
+            expected: vec!["<y>".into()],
+        }
+    );
+    assert_eq!(
+        analyze("tp 1 2"),
+        ParseAnalysis {
+            path: CommandPath(vec!["tp".into(), "<x>".into(), "<y>".into()]),
+            offset: 6,
+            expected: vec!["<z>".into()],
+        }
+    );
+    assert_eq!(
+        analyze("sto"),
+        ParseAnalysis {
+            path: CommandPath::default(),
+            offset: 0,
+            expected: vec!["tp".into(), "stop".into()],
+        }
+    );
+}
+
+#[test]
+fn trace_ambiguous_dispatch() {
+    #[command(usage = "set <x> <y>")]
+    async fn set_position(_ctx: &mut State, _x: i32, _y: i32) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "set <name> <y>")]
+    async fn set_named(_ctx: &mut State, _name: String, _y: i32) -> Result<(), Error> {
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default()
+        .with(set_position)
+        .with(set_named);
+
+    // Both `<x>` and `<name>` accept `1`, and both paths then reject `foo`.
+    assert_eq!(
+        smol::block_on(dispatcher.dispatch_traced(&mut State, "set 1 foo")),
+        (
+            false,
+            DispatchTrace {
+                visited: 5,
+                satisfies_calls: 4,
+            }
+        )
+    );
+    assert_eq!(
+        smol::block_on(dispatcher.dispatch_traced(&mut State, "set 1 2")),
+        (
+            true,
+            DispatchTrace {
+                visited: 3,
+                satisfies_calls: 2,
+            }
+        )
+    );
+}
+
+#[test]
+fn break_chained_commands() {
+    use std::ops::ControlFlow;
+
+    #[derive(Default)]
+    struct Chat(Vec<String>);
+
+    impl Context for Chat {
+        type Error = Error;
+        type Ok = ControlFlow<&'static str>;
+    }
+
+    #[command(usage = "say <message>")]
+    async fn say(chat: &mut Chat, message: String) -> Result<ControlFlow<&'static str>, Error> {
+        chat.0.push(message);
+        Ok(ControlFlow::Continue(()))
+    }
+
+    #[command(usage = "abort")]
+    async fn abort(_chat: &mut Chat) -> Result<ControlFlow<&'static str>, Error> {
+        Ok(ControlFlow::Break("aborted"))
+    }
+
+    let dispatcher = CommandDispatcher::default().with(say).with(abort);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut chat = Chat::default();
+
+    assert_eq!(
+        smol::block_on(dispatcher.dispatch_line(
+            &mut nodes,
+            &mut errors,
+            &mut chat,
+            "say hello; say world"
+        )),
+        Ok(ControlFlow::Continue(()))
+    );
+    assert_eq!(chat.0, vec!["hello", "world"]);
+
+    let mut chat = Chat::default();
+    assert_eq!(
+        smol::block_on(dispatcher.dispatch_line(
+            &mut nodes,
+            &mut errors,
+            &mut chat,
+            "abort; say never"
+        )),
+        Ok(ControlFlow::Break("aborted"))
+    );
+    assert!(chat.0.is_empty());
+
+    let mut chat = Chat::default();
+    assert!(smol::block_on(dispatcher.dispatch_line(
+        &mut nodes,
+        &mut errors,
+        &mut chat,
+        "unknown; say never"
+    ))
+    .is_err());
+    assert!(chat.0.is_empty());
+}
+
+#[test]
+fn last_matched_path() {
+    #[command(usage = "region flags <flags>")]
+    async fn flags(_ctx: &mut State, _flags: u8) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "region list")]
+    async fn list(_ctx: &mut State) -> Result<(), Error> {
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(flags).with(list);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let input = "region flags build,xyz";
+    assert!(
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut State, input)).is_err()
+    );
+    assert_eq!(
+        smol::block_on(dispatcher.last_matched_path(&State, input)),
+        CommandPath(vec!["region".into(), "flags".into()])
+    );
+    assert_eq!(
+        smol::block_on(dispatcher.last_matched_path(&State, "teleport")),
+        CommandPath::default()
+    );
+}
+
+#[test]
+fn match_cache() {
+    #[derive(Debug, PartialEq, Eq)]
+    enum Picked {
+        None,
+        Number(i32),
+        Text(String),
+    }
+
+    impl Context for Picked {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "pick <value>")]
+    async fn pick_text(ctx: &mut Picked, value: String) -> Result<(), Error> {
+        *ctx = Picked::Text(value);
+        Ok(())
+    }
+
+    #[command(usage = "pick <value>", priority = 1)]
+    async fn pick_number(ctx: &mut Picked, value: i32) -> Result<(), Error> {
+        *ctx = Picked::Number(value);
+        Ok(())
+    }
+
+    let mut dispatcher = CommandDispatcher::default()
+        .with_match_cache(8)
+        .with(pick_text);
+    let mut picked = Picked::None;
+
+    let (executed, trace) = smol::block_on(dispatcher.dispatch_traced(&mut picked, "pick 5"));
+    assert!(executed);
+    assert_ne!(trace.visited, 0);
+    assert_eq!(picked, Picked::Text("5".into()));
+
+    // The second dispatch of the same input is served from the cache.
+    picked = Picked::None;
+    assert_eq!(
+        smol::block_on(dispatcher.dispatch_traced(&mut picked, "pick 5")),
+        (true, DispatchTrace::default())
+    );
+    assert_eq!(picked, Picked::Text("5".into()));
+
+    // Registering a command clears the cache, so the new, higher-priority
+    // command is matched instead.
+    dispatcher.register(pick_number).unwrap();
+    let (executed, trace) = smol::block_on(dispatcher.dispatch_traced(&mut picked, "pick 5"));
+    assert!(executed);
+    assert_ne!(trace.visited, 0);
+    assert_eq!(picked, Picked::Number(5));
+}
+
+#[test]
+fn root_names() {
+    #[command(usage = "config reload")]
+    async fn reload(_ctx: &mut State) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "config set <key> <value>")]
+    async fn set(_ctx: &mut State, _key: String, _value: i32) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "tp <x> <y> <z>")]
+    async fn tp(_ctx: &mut State, _x: i32, _y: i32, _z: i32) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "stop")]
+    async fn stop(_ctx: &mut State) -> Result<(), Error> {
+        Ok(())
+    }
+
+    let mut dispatcher = CommandDispatcher::default()
+        .with(reload)
+        .with(set)
+        .with(tp)
+        .with(stop);
+    let mut roots: Vec<&str> = dispatcher.roots().collect();
+    roots.sort_unstable();
+    assert_eq!(roots, ["config", "stop", "tp"]);
+
+    dispatcher.set_enabled("stop", false);
+    let mut roots: Vec<&str> = dispatcher.roots().collect();
+    roots.sort_unstable();
+    assert_eq!(roots, ["config", "tp"]);
+}
+
+#[test]
+fn collect_feedback() {
+    #[derive(Default)]
+    struct Player {
+        position: (i32, i32, i32),
+        messages: Vec<String>,
+    }
+
+    impl Context for Player {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    impl Feedback for Player {
+        fn feedback(&mut self) -> &mut Vec<String> {
+            &mut self.messages
+        }
+    }
+
+    #[command(usage = "tp <x> <y> <z>")]
+    async fn tp(player: &mut Player, x: i32, y: i32, z: i32) -> Result<(), Error> {
+        let from = player.position;
+        player.position = (x, y, z);
+        player
+            .feedback()
+            .push(format!("Left {} {} {}", from.0, from.1, from.2));
+        player
+            .feedback()
+            .push(format!("Teleported to {} {} {}", x, y, z));
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(tp);
+    let mut player = Player::default();
+    player.messages.push("Welcome".into());
+
+    assert_eq!(
+        smol::block_on(dispatcher.dispatch_collecting(&mut player, "tp 1 2 3")),
+        (
+            true,
+            vec!["Left 0 0 0".to_owned(), "Teleported to 1 2 3".to_owned()]
+        )
+    );
+    assert_eq!(
+        smol::block_on(dispatcher.dispatch_collecting(&mut player, "tp 1 2")),
+        (false, Vec::new())
+    );
+    // Messages written before dispatching are left in place.
+    assert_eq!(player.messages, ["Welcome"]);
+}
+
+#[test]
+fn named_arguments() {
+    #[derive(Default)]
+    struct Player {
+        position: Option<(i32, i32, i32)>,
+    }
+
+    impl Context for Player {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "tp <x> <y> <z>", named)]
+    async fn tp(player: &mut Player, x: i32, y: i32, z: i32) -> Result<(), Error> {
+        player.position = Some((x, y, z));
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(tp);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut dispatch = |input| {
+        let mut player = Player::default();
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut player, input))
+            .ok()
+            .and(player.position)
+    };
+
+    assert_eq!(dispatch("tp 1 2 3"), Some((1, 2, 3)));
+    assert_eq!(dispatch("tp z=3 x=1 y=2"), Some((1, 2, 3)));
+    assert_eq!(dispatch("tp x=1 y=2 z=3"), Some((1, 2, 3)));
+    assert_eq!(dispatch("tp x=1 y=2"), None);
+    assert_eq!(dispatch("tp x=1 y=2 w=3"), None);
+    assert_eq!(dispatch("tp x=1 y=2 z=3 z=4"), None);
+}
+
+#[test]
+fn conditional_subtree() {
+    struct World {
+        pvp: bool,
+        duels: usize,
+    }
+
+    impl Context for World {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "pvp duel <player>")]
+    async fn duel(world: &mut World, _player: String) -> Result<(), Error> {
+        world.duels += 1;
+        Ok(())
+    }
+
+    #[command(usage = "pvp status")]
+    async fn status(_world: &mut World) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[command(usage = "spawn")]
+    async fn spawn(_world: &mut World) -> Result<(), Error> {
+        Ok(())
+    }
+
+    let mut dispatcher = CommandDispatcher::default()
+        .with(duel)
+        .with(status)
+        .with(spawn);
+    dispatcher.set_condition("pvp", |world: &World| world.pvp);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut world = World {
+        pvp: false,
+        duels: 0,
+    };
+
+    let dispatched = |dispatcher: &CommandDispatcher<World>, world: &mut World, input| {
+        smol::block_on(dispatcher.dispatch(&mut Vec::new(), &mut Vec::new(), world, input)).is_ok()
+    };
+
+    assert!(!dispatched(&dispatcher, &mut world, "pvp duel alice"));
+    assert!(!dispatched(&dispatcher, &mut world, "pvp status"));
+    assert!(dispatched(&dispatcher, &mut world, "spawn"));
+    assert_eq!(
+        smol::block_on(dispatcher.suggestions(&world, "")),
+        vec![lieutenant::Completion::new("spawn")]
+    );
+    assert_eq!(dispatcher.help_page(&world, 1, 10), ["spawn"]);
+
+    world.pvp = true;
+    assert!(smol::block_on(dispatcher.dispatch(
+        &mut nodes,
+        &mut errors,
+        &mut world,
+        "pvp duel alice"
+    ))
+    .is_ok());
+    assert_eq!(world.duels, 1);
+    assert!(dispatched(&dispatcher, &mut world, "pvp status"));
+    assert_eq!(
+        smol::block_on(dispatcher.suggestions(&world, "p")),
+        vec![lieutenant::Completion::new("pvp")]
+    );
+}
+
+#[test]
+fn parse_without_executing() {
+    #[derive(Default)]
+    struct Player {
+        teleported: bool,
+    }
+
+    impl Context for Player {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "tp <x> <y> <z>")]
+    async fn tp(player: &mut Player, _x: i32, _y: i32, _z: i32) -> Result<(), Error> {
+        player.teleported = true;
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(tp);
+    let mut player = Player::default();
+
+    let values = smol::block_on(dispatcher.parse_args(&mut player, "tp 1 2 3")).unwrap();
+    assert_eq!(values.downcast_ref::<(i32, i32, i32)>(), Some(&(1, 2, 3)));
+    assert!(!player.teleported);
+    assert!(smol::block_on(dispatcher.parse_args(&mut player, "tp 1 2")).is_none());
+}
+
+#[test]
+fn argument_spans() {
+    struct State;
+
+    impl Context for State {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "tp <x> <y> <z>")]
+    async fn tp(_state: &mut State, _x: i32, _y: i32, _z: i32) -> Result<(), Error> {
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(tp);
+    let span = |label: &str, start, end| lieutenant::ArgSpan {
+        label: label.into(),
+        start,
+        end,
+    };
+
+    assert_eq!(
+        smol::block_on(dispatcher.parse_with_spans(&State, "tp 1 -20 300")),
+        Some(vec![
+            span("tp", 0, 2),
+            span("<x>", 3, 4),
+            span("<y>", 5, 8),
+            span("<z>", 9, 12),
+        ])
+    );
+    assert_eq!(
+        smol::block_on(dispatcher.parse_with_spans(&State, "tp 1 2")),
+        None
+    );
+}
+
+#[test]
+fn full_dispatch() {
+    struct Player;
+
+    impl Context for Player {
+        type Error = Error;
+        type Ok = i32;
+    }
+
+    #[command(
+        usage = "give <item> <amount>",
+        description = "Gives an item",
+        permission = "admin.give"
+    )]
+    async fn give(_player: &mut Player, _item: String, amount: i32) -> Result<i32, Error> {
+        if amount < 0 {
+            return Err(Error::ParsingInt);
+        }
+        Ok(amount)
+    }
+
+    let dispatcher = CommandDispatcher::default().with(give);
+
+    let success = smol::block_on(dispatcher.dispatch_full(&mut Player, "give apple 12")).unwrap();
+    assert_eq!(success.ok, 12);
+    assert_eq!(success.command.path().to_string(), "give <item> <amount>");
+    assert_eq!(
+        success.command.description.as_deref(),
+        Some("Gives an item")
+    );
+    assert_eq!(success.command.permissions, ["admin.give"]);
+    let spans: Vec<_> = success
+        .spans
+        .iter()
+        .map(|span| (span.label.as_str(), span.start, span.end))
+        .collect();
+    assert_eq!(
+        spans,
+        [("give", 0, 4), ("<item>", 5, 10), ("<amount>", 11, 13)]
+    );
+
+    assert_eq!(success.line, "give apple 12");
+
+    assert_eq!(
+        smol::block_on(dispatcher.dispatch_full(&mut Player, "give apple -1")).err(),
+        Some(DispatchFailure::CommandFailed(vec![Error::ParsingInt]))
+    );
+    assert_eq!(
+        smol::block_on(dispatcher.dispatch_full(&mut Player, "take apple")).err(),
+        Some(DispatchFailure::Unmatched(DispatchError::UnknownCommand(
+            InputContext::new("take apple", 0)
+        )))
+    );
+}
+
+#[test]
+fn full_dispatch_spans_of_fallback() {
+    #[command(usage = "set <value>")]
+    async fn set_value(_ctx: &mut State, _value: i32) -> Result<(), Error> {
+        Err(Error::ParsingInt)
+    }
+
+    #[command(usage = "set <name>")]
+    async fn set_name(_ctx: &mut State, _name: String) -> Result<(), Error> {
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(set_value).with(set_name);
+
+    let mut errors = Vec::new();
+    assert!(
+        smol::block_on(dispatcher.dispatch(&mut Vec::new(), &mut errors, &mut State, "set 1"))
+            .is_ok()
+    );
+    assert_eq!(errors, [Error::ParsingInt]);
+
+    let success = smol::block_on(dispatcher.dispatch_full(&mut State, "set 1")).unwrap();
+    assert_eq!(success.command.path().to_string(), "set <name>");
+    let labels: Vec<_> = success
+        .spans
+        .iter()
+        .map(|span| span.label.as_str())
+        .collect();
+    assert_eq!(labels, ["set", "<name>"]);
+}
+
+#[test]
+fn bulk_aliases() {
+    #[derive(Default)]
+    struct Player {
+        position: Option<(i32, i32, i32)>,
+        stopped: bool,
+    }
+
+    impl Context for Player {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "teleport <x> <y> <z>")]
+    async fn teleport(player: &mut Player, x: i32, y: i32, z: i32) -> Result<(), Error> {
+        player.position = Some((x, y, z));
+        Ok(())
+    }
+
+    #[command(usage = "stop")]
+    async fn stop(player: &mut Player) -> Result<(), Error> {
+        player.stopped = true;
+        Ok(())
+    }
+
+    let mut dispatcher = CommandDispatcher::default().with(teleport).with(stop);
+    let results = dispatcher.register_aliases(vec![
+        ("tp".to_owned(), "teleport".to_owned()),
+        ("halt".to_owned(), "stop".to_owned()),
+        ("gm".to_owned(), "gamemode".to_owned()),
+    ]);
+    assert_eq!(
+        results,
+        [
+            Ok(()),
+            Ok(()),
+            Err(AliasError::TargetNotFound("gamemode".into()))
+        ]
+    );
+    assert_eq!(
+        dispatcher.register_alias("stop", "teleport"),
+        Err(AliasError::Conflict("stop".into()))
+    );
+    assert_eq!(
+        dispatcher.register_alias("tp", "stop"),
+        Err(AliasError::Conflict("tp".into()))
+    );
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut player = Player::default();
+    assert!(
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut player, "tp 1 2 3"))
+            .is_ok()
+    );
+    assert_eq!(player.position, Some((1, 2, 3)));
+    assert!(
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut player, "halt")).is_ok()
+    );
+    assert!(player.stopped);
+    assert!(
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut player, "gm 1")).is_err()
+    );
+}
+
+#[test]
+fn aliased_check() {
+    struct Player;
+
+    impl Context for Player {
+        type Error = Error;
+        type Ok = i32;
+    }
+
+    #[command(usage = "teleport <x>")]
+    async fn teleport(_player: &mut Player, x: i32) -> Result<i32, Error> {
+        Ok(x)
+    }
+
+    let mut dispatcher = CommandDispatcher::default().with(teleport);
+    dispatcher.register_alias("t", "teleport").unwrap();
+
+    assert_eq!(smol::block_on(dispatcher.check(&Player, "t 1")), Ok(()));
+    let error = smol::block_on(dispatcher.check(&Player, "t one")).unwrap_err();
+    assert_eq!(error.context().line(), "teleport one");
+    assert_eq!(error.context().token(), "one");
+    assert_eq!(
+        smol::block_on(dispatcher.validate_lines(&Player, vec!["t 1", "t 1 2"])),
+        [
+            (1, Ok(())),
+            (
+                2,
+                Err(DispatchError::TrailingInput(InputContext::new(
+                    "teleport 1 2",
+                    11
+                )))
+            )
+        ]
+    );
+
+    let success = smol::block_on(dispatcher.dispatch_full(&mut Player, "t 1")).unwrap();
+    assert_eq!(success.ok, 1);
+    assert_eq!(success.line, "teleport 1");
+    assert_eq!(success.spans[1].start, 9);
+    match smol::block_on(dispatcher.dispatch_full(&mut Player, "t one")) {
+        Err(DispatchFailure::Unmatched(error)) => {
+            assert_eq!(error.context().line(), "teleport one")
+        }
+        _ => panic!("expected `t one` not to match"),
+    }
+}
+
+#[test]
+fn closure_commands() {
+    struct Sum(i32);
+
+    impl Context for Sum {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    let add = CommandSpec::new(
+        ParserChain::new()
+            .literal("add")
+            .parser::<i32>("a")
+            .parser::<i32>("b"),
+        |sum: &mut Sum, values| {
+            let a = *values[0].downcast_ref::<i32>().unwrap();
+            let b = *values[1].downcast_ref::<i32>().unwrap();
+            Box::pin(async move {
+                sum.0 = a + b;
+                Ok(())
+            })
+        },
+    );
+    let dispatcher = CommandDispatcher::default().with(add);
+    assert_eq!(
+        dispatcher.commands().next().unwrap().path().to_string(),
+        "add <a> <b>"
+    );
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut sum = Sum(0);
+    assert!(
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut sum, "add 2 40")).is_ok()
+    );
+    assert_eq!(sum.0, 42);
+    assert!(
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut sum, "add 2 x")).is_err()
+    );
+}
+
+#[test]
+fn commands_take_precedence_over_aliases() {
+    #[derive(Default)]
+    struct Last(&'static str);
+
+    impl Context for Last {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "teleport <x>")]
+    async fn teleport(last: &mut Last, _x: i32) -> Result<(), Error> {
+        last.0 = "teleport";
+        Ok(())
+    }
+
+    #[command(usage = "tp <x>")]
+    async fn tp(last: &mut Last, _x: i32) -> Result<(), Error> {
+        last.0 = "tp";
+        Ok(())
+    }
+
+    let mut dispatcher = CommandDispatcher::default().with(teleport);
+    dispatcher.register_alias("tp", "teleport").unwrap();
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut last = Last::default();
+    assert!(
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut last, "tp 1")).is_ok()
+    );
+    assert_eq!(last.0, "teleport");
+
+    dispatcher.register(tp).unwrap();
+    assert_eq!(
+        dispatcher.warnings(),
+        [RegisterWarning::ShadowedAlias("tp".into())]
+    );
+    assert!(
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut last, "tp 1")).is_ok()
+    );
+    assert_eq!(last.0, "tp");
+}
+
+#[test]
+fn chained_commands_with_quoted_separators() {
+    use lieutenant::parsers::{InvalidString, StringArg};
+    use std::ops::ControlFlow;
+
+    #[derive(Default)]
+    struct Chat(Vec<String>);
+
+    impl Context for Chat {
+        type Error = InvalidString;
+        type Ok = ControlFlow<()>;
+    }
+
+    #[command(usage = "say <message>")]
+    async fn say(chat: &mut Chat, message: StringArg) -> Result<ControlFlow<()>, InvalidString> {
+        chat.0.push(message.value);
+        Ok(ControlFlow::Continue(()))
+    }
+
+    let dispatcher = CommandDispatcher::default().with(say);
+
+    let mut chat = Chat::default();
+    assert_eq!(
+        smol::block_on(dispatcher.dispatch_line(
+            &mut Vec::new(),
+            &mut Vec::new(),
+            &mut chat,
+            r#"say "a; b"; say "c \"d;\""; say e"#
+        )),
+        Ok(ControlFlow::Continue(()))
+    );
+    assert_eq!(chat.0, ["a; b", "c \"d;\"", "e"]);
+}
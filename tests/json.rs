@@ -0,0 +1,329 @@
+#![cfg(feature = "serde")]
+
+use lieutenant::parsers::{BoundedInt, BoundedIntChecker, InvalidRange, InvalidString};
+use lieutenant::{
+    command, ArgumentChecker, ArgumentRegistry, BoxedParser, CommandDispatcher, Context,
+    HandlerRegistry, RegisterError,
+};
+use serde_json::{json, Value};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+enum Error {
+    #[error("invalid json: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Range(#[from] InvalidRange),
+    #[error(transparent)]
+    Int(#[from] std::num::ParseIntError),
+    #[error(transparent)]
+    Float(#[from] std::num::ParseFloatError),
+    #[error(transparent)]
+    Bool(#[from] std::str::ParseBoolError),
+    #[error(transparent)]
+    String(#[from] InvalidString),
+}
+
+impl From<std::convert::Infallible> for Error {
+    fn from(infallible: std::convert::Infallible) -> Self {
+        match infallible {}
+    }
+}
+
+struct State {
+    data: Value,
+}
+
+impl Context for State {
+    type Error = Error;
+    type Ok = ();
+}
+
+#[command(usage = "data <value>")]
+async fn data(state: &mut State, value: Value) -> Result<(), Error> {
+    state.data = value;
+    Ok(())
+}
+
+#[test]
+fn parse_json_object() {
+    let dispatcher = CommandDispatcher::default().with(data);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut state = State { data: Value::Null };
+
+    assert!(smol::block_on(dispatcher.dispatch(
+        &mut nodes,
+        &mut errors,
+        &mut state,
+        r#"data {"name":"x","n":1}"#
+    ))
+    .is_ok());
+    assert_eq!(state.data, json!({ "name": "x", "n": 1 }));
+
+    assert!(smol::block_on(dispatcher.dispatch(
+        &mut nodes,
+        &mut errors,
+        &mut state,
+        r#"data {"text": "a } in {a string}", "nested": {"list": [1, 2]}}"#
+    ))
+    .is_ok());
+    assert_eq!(
+        state.data,
+        json!({ "text": "a } in {a string}", "nested": { "list": [1, 2] } })
+    );
+}
+
+#[test]
+fn reject_unbalanced_json() {
+    let dispatcher = CommandDispatcher::default().with(data);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut state = State { data: Value::Null };
+
+    for input in &[
+        r#"data {"name":"x""#,
+        r#"data {"name":"x"}}"#,
+        "data {oops}",
+    ] {
+        assert!(
+            smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut state, input))
+                .is_err()
+        );
+    }
+    assert_eq!(state.data, Value::Null);
+}
+
+#[command(usage = "limit <amount>")]
+async fn limit(_state: &mut State, _amount: BoundedInt<1, 100>) -> Result<(), Error> {
+    Ok(())
+}
+
+#[command(usage = "limit reset [seconds]")]
+async fn limit_reset(_state: &mut State, _seconds: Option<i32>) -> Result<(), Error> {
+    Ok(())
+}
+
+#[test]
+fn brigadier_properties() {
+    let dispatcher = CommandDispatcher::default().with(limit).with(limit_reset);
+
+    assert_eq!(
+        dispatcher.to_brigadier_json(),
+        json!({
+            "type": "root",
+            "children": {
+                "limit": {
+                    "type": "literal",
+                    "children": {
+                        "amount": {
+                            "type": "argument",
+                            "parser": "brigadier:integer",
+                            "properties": { "min": 1, "max": 100 },
+                            "executable": true,
+                        },
+                        "reset": {
+                            "type": "literal",
+                            "children": {
+                                "seconds": {
+                                    "type": "argument",
+                                    "parser": "brigadier:integer",
+                                    "executable": true,
+                                },
+                            },
+                            "executable": true,
+                        },
+                    },
+                },
+            },
+        })
+    );
+}
+
+#[test]
+fn commands_from_data() {
+    let handlers = HandlerRegistry::new()
+        .with_argument::<String>("word")
+        .with_argument::<i32>("integer")
+        .with_handler("give", |state: &mut State, values| {
+            let player = values[0].downcast_ref::<String>().unwrap();
+            let amount = values
+                .get(1)
+                .map_or(1, |amount| *amount.downcast_ref::<i32>().unwrap());
+            state.data = json!({ "player": player, "amount": amount });
+            Box::pin(async { Ok(()) })
+        });
+
+    let mut dispatcher = CommandDispatcher::default();
+    dispatcher
+        .register_json(
+            &json!([{
+                "usage": "give <player> [amount]",
+                "arguments": { "player": "word", "amount": "integer" },
+                "handler": "give",
+                "description": "Gives an item to a player",
+            }]),
+            &handlers,
+        )
+        .unwrap();
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut state = State { data: Value::Null };
+
+    assert!(smol::block_on(dispatcher.dispatch(
+        &mut nodes,
+        &mut errors,
+        &mut state,
+        "give alice 3"
+    ))
+    .is_ok());
+    assert_eq!(state.data, json!({ "player": "alice", "amount": 3 }));
+
+    assert!(
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut state, "give bob"))
+            .is_ok()
+    );
+    assert_eq!(state.data, json!({ "player": "bob", "amount": 1 }));
+
+    assert_eq!(
+        dispatcher.register_json(
+            &json!({ "usage": "take <player>", "arguments": { "player": "word" }, "handler": "take" }),
+            &handlers,
+        ),
+        Err(RegisterError::UnknownHandler("take".into()))
+    );
+    assert_eq!(
+        dispatcher.register_json(
+            &json!({ "usage": "take <player>", "arguments": { "player": "uuid" }, "handler": "give" }),
+            &handlers,
+        ),
+        Err(RegisterError::UnknownArgumentType("uuid".into()))
+    );
+    assert_eq!(
+        dispatcher.register_json(
+            &json!({
+                "usage": "give [amount] <player>",
+                "arguments": { "player": "word", "amount": "integer" },
+                "handler": "give",
+            }),
+            &handlers,
+        ),
+        Err(RegisterError::InvalidData(
+            "optional `amount` is followed by a required argument".into()
+        ))
+    );
+    assert_eq!(
+        dispatcher.register_json(
+            &json!({
+                "usage": "give [amount] all",
+                "arguments": { "amount": "integer" },
+                "handler": "give",
+            }),
+            &handlers,
+        ),
+        Err(RegisterError::InvalidData(
+            "optional `amount` is followed by a required argument".into()
+        ))
+    );
+}
+
+#[test]
+fn custom_argument_factory() {
+    type Percent = BoundedInt<0, 100>;
+
+    let mut arguments = ArgumentRegistry::with_builtins();
+    arguments.register_parser("percent", || {
+        (
+            Box::new(BoundedIntChecker::<0, 100>) as Box<dyn ArgumentChecker<State>>,
+            BoxedParser::of::<Percent>(),
+        )
+    });
+    let handlers = HandlerRegistry::new()
+        .with_arguments(arguments)
+        .with_handler("volume", |state: &mut State, values| {
+            let channel = values[0].downcast_ref::<String>().unwrap();
+            let level = values[1].downcast_ref::<Percent>().unwrap();
+            state.data = json!({ "channel": channel, "level": level.0 });
+            Box::pin(async { Ok(()) })
+        });
+
+    let mut dispatcher = CommandDispatcher::default();
+    dispatcher
+        .register_json(
+            &json!({
+                "usage": "volume <channel> <level>",
+                "arguments": { "channel": "word", "level": "percent" },
+                "handler": "volume",
+            }),
+            &handlers,
+        )
+        .unwrap();
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut state = State { data: Value::Null };
+
+    assert!(smol::block_on(dispatcher.dispatch(
+        &mut nodes,
+        &mut errors,
+        &mut state,
+        "volume music 40"
+    ))
+    .is_ok());
+    assert_eq!(state.data, json!({ "channel": "music", "level": 40 }));
+
+    assert!(smol::block_on(dispatcher.dispatch(
+        &mut nodes,
+        &mut errors,
+        &mut state,
+        "volume music 140"
+    ))
+    .is_err());
+    assert_eq!(state.data, json!({ "channel": "music", "level": 40 }));
+}
+
+#[test]
+fn command_table() {
+    #[command(
+        usage = "give <player> [amount]",
+        description = "Gives an item to a player",
+        permission = "admin.give"
+    )]
+    async fn give(_state: &mut State, _player: String, _amount: Option<i32>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default()
+        .with(give)
+        .with(limit)
+        .with(limit_reset);
+
+    let table = dispatcher.command_table();
+    assert_eq!(
+        table
+            .iter()
+            .map(|entry| entry.usage.as_str())
+            .collect::<Vec<_>>(),
+        [
+            "give <player> [amount]",
+            "limit <amount>",
+            "limit reset [seconds]"
+        ]
+    );
+    assert_eq!(
+        serde_json::to_value(&table[0]).unwrap(),
+        json!({
+            "name": "give",
+            "usage": "give <player> [amount]",
+            "description": "Gives an item to a player",
+            "permissions": ["admin.give"],
+            "arguments": [
+                { "name": "player", "parser": "brigadier:string", "optional": false },
+                { "name": "amount", "parser": "brigadier:integer", "optional": true },
+            ],
+        })
+    );
+}
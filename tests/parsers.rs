@@ -0,0 +1,1958 @@
+use lieutenant::parsers::{
+    recursive, AnyToken, ByteSize, Coord, Coordinates, Delimiter, Dependent, DependentArgument,
+    Either, EnumArgument, EnumChecker, EnumParser, Flag, FlagSpec, Flags, FromStrChecker,
+    FromStrParser, GreedyString, InvalidByteSize, InvalidCoord, InvalidDependent,
+    InvalidExpression, InvalidFlags, InvalidPath, InvalidRange, InvalidResourceLocation,
+    InvalidSelector, InvalidString, InvalidVariant, MissingDelimiter, MultilineString, OpenRange,
+    PathRoot, Peek, Present, Recursive, RecursiveArgument, RecursiveChecker, RecursiveParser,
+    RecursiveRef, ResourceLocation, RestTokens, SandboxedPath, Selector, SelectorKind, Separated,
+    StringArg, SubCommand, SubDispatcher, Switch, Until,
+};
+use lieutenant::{command, ArgumentKind, CommandDispatcher, Completion, Context, RegisterError};
+use std::num;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+enum Error {
+    #[error("failed to parse int")]
+    ParsingInt,
+    #[error(transparent)]
+    Selector(#[from] InvalidSelector),
+    #[error(transparent)]
+    Flags(#[from] InvalidFlags),
+    #[error(transparent)]
+    Path(#[from] InvalidPath),
+    #[error(transparent)]
+    Range(#[from] InvalidRange),
+    #[error(transparent)]
+    Dependent(#[from] InvalidDependent),
+    #[error(transparent)]
+    String(#[from] InvalidString),
+    #[error(transparent)]
+    ByteSize(#[from] InvalidByteSize),
+    #[error(transparent)]
+    ResourceLocation(#[from] InvalidResourceLocation),
+    #[error(transparent)]
+    Variant(#[from] InvalidVariant),
+    #[error(transparent)]
+    Expression(#[from] InvalidExpression),
+    #[error(transparent)]
+    Coord(#[from] InvalidCoord),
+    #[error(transparent)]
+    Float(#[from] num::ParseFloatError),
+    #[error(transparent)]
+    Delimiter(#[from] MissingDelimiter),
+}
+
+impl From<num::ParseIntError> for Error {
+    fn from(_: num::ParseIntError) -> Self {
+        Error::ParsingInt
+    }
+}
+
+impl From<std::convert::Infallible> for Error {
+    fn from(_: std::convert::Infallible) -> Self {
+        panic!()
+    }
+}
+
+#[test]
+fn separated_list() {
+    #[derive(Default)]
+    struct State {
+        values: Vec<i32>,
+        names: Vec<String>,
+    }
+
+    impl Context for State {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "sum <values>")]
+    async fn sum(state: &mut State, values: Separated<i32>) -> Result<(), Error> {
+        state.values = values.into_inner();
+        Ok(())
+    }
+
+    #[command(usage = "team add <names>")]
+    async fn team_add(state: &mut State, names: Separated<String, ';'>) -> Result<(), Error> {
+        state.names = names.into();
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(sum).with(team_add);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut state = State::default();
+
+    assert!(
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut state, "sum 1,2,3"))
+            .is_ok()
+    );
+    assert_eq!(state.values, vec![1, 2, 3]);
+
+    assert!(smol::block_on(dispatcher.dispatch(
+        &mut nodes,
+        &mut errors,
+        &mut state,
+        "team add alice;bob;carol"
+    ))
+    .is_ok());
+    assert_eq!(state.names, vec!["alice", "bob", "carol"]);
+
+    for input in &["sum 1,,3", "sum 1,2,", "sum 1,two,3", "team add alice;;bob"] {
+        assert!(
+            smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut state, input))
+                .is_err()
+        );
+    }
+    assert_eq!(state.values, vec![1, 2, 3]);
+}
+
+#[test]
+fn entity_selector() {
+    #[derive(Default)]
+    struct State {
+        selector: Option<Selector>,
+    }
+
+    impl Context for State {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "kill <targets>")]
+    async fn kill(state: &mut State, targets: Selector) -> Result<(), Error> {
+        state.selector = Some(targets);
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(kill);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut state = State::default();
+
+    assert!(
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut state, "kill @a")).is_ok()
+    );
+    assert_eq!(
+        state.selector.take(),
+        Some(Selector {
+            kind: SelectorKind::AllPlayers,
+            filters: vec![],
+        })
+    );
+
+    assert!(smol::block_on(dispatcher.dispatch(
+        &mut nodes,
+        &mut errors,
+        &mut state,
+        "kill @e[type=zombie, nbt={Tags:[a,b]}]"
+    ))
+    .is_ok());
+    assert_eq!(
+        state.selector.take(),
+        Some(Selector {
+            kind: SelectorKind::AllEntities,
+            filters: vec![
+                ("type".into(), "zombie".into()),
+                ("nbt".into(), "{Tags:[a,b]}".into())
+            ],
+        })
+    );
+
+    for input in &[
+        "kill @x",
+        "kill @ax",
+        "kill @e[type=zombie",
+        "kill @e[type]",
+        "kill alice",
+    ] {
+        assert!(
+            smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut state, input))
+                .is_err()
+        );
+    }
+    assert_eq!(state.selector, None);
+
+    assert_eq!(
+        smol::block_on(dispatcher.suggestions(&State::default(), "kill @")),
+        ["@a", "@p", "@r", "@s", "@e"]
+            .iter()
+            .map(|&kind| Completion::from(kind))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        smol::block_on(dispatcher.suggestions(&State::default(), "kill @e")),
+        vec![Completion::from("@e")]
+    );
+}
+
+#[test]
+fn completion_tooltips() {
+    use lieutenant::{ArgumentChecker, ArgumentKind, ArgumentParser, ParserUtil};
+    use std::any::Any;
+    use std::future::Future;
+    use std::pin::Pin;
+
+    struct Server {
+        players: Vec<(&'static str, &'static str)>,
+    }
+
+    impl Context for Server {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    struct Player;
+
+    #[derive(Clone)]
+    struct PlayerChecker;
+
+    impl ArgumentChecker<Server> for PlayerChecker {
+        fn satisfies<'a, 'b>(
+            &'a self,
+            ctx: &'a Server,
+            input: &'a mut &'b str,
+        ) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>> {
+            Box::pin(async move {
+                let head = input.advance_until(" ");
+                ctx.players.iter().any(|(name, _)| *name == head)
+            })
+        }
+
+        fn equals(&self, other: &dyn Any) -> bool {
+            other.downcast_ref::<Self>().is_some()
+        }
+
+        fn default() -> Self {
+            PlayerChecker
+        }
+
+        fn box_clone(&self) -> Box<dyn ArgumentChecker<Server>> {
+            Box::new(self.clone())
+        }
+
+        fn complete<'a>(
+            &'a self,
+            ctx: &'a Server,
+            partial: &'a str,
+        ) -> Pin<Box<dyn Future<Output = Vec<Completion>> + Send + 'a>> {
+            Box::pin(async move {
+                ctx.players
+                    .iter()
+                    .filter(|(name, _)| name.starts_with(partial))
+                    .map(|(name, display_name)| Completion::new(*name).with_tooltip(*display_name))
+                    .collect()
+            })
+        }
+    }
+
+    struct PlayerParser;
+
+    impl ArgumentParser<Server> for PlayerParser {
+        type Output = Player;
+
+        fn parse<'a, 'b>(
+            &'a self,
+            _ctx: &'a mut Server,
+            input: &'a mut &'b str,
+        ) -> Pin<Box<dyn Future<Output = Result<Player, Error>> + Send + Sync + 'a>> {
+            Box::pin(async move {
+                input.advance_until(" ");
+                Ok(Player)
+            })
+        }
+
+        fn default() -> Self {
+            PlayerParser
+        }
+    }
+
+    impl ArgumentKind<Server> for Player {
+        type Checker = PlayerChecker;
+        type Parser = PlayerParser;
+    }
+
+    #[command(usage = "msg <player>")]
+    async fn msg(_server: &mut Server, _player: Player) -> Result<(), Error> {
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(msg);
+    let server = Server {
+        players: vec![("alice", "Alice the Brave"), ("bob", "Bob")],
+    };
+
+    assert_eq!(
+        smol::block_on(dispatcher.suggestions(&server, "msg a")),
+        vec![Completion::new("alice").with_tooltip("Alice the Brave")]
+    );
+    assert_eq!(
+        smol::block_on(dispatcher.suggestions(&server, "m")),
+        vec![Completion::new("msg")]
+    );
+}
+
+#[test]
+fn flags_in_any_order() {
+    struct DeleteFlags;
+
+    impl FlagSpec for DeleteFlags {
+        const FLAGS: &'static [Flag] = &[
+            Flag::switch("force").short('f'),
+            Flag::value("count").short('c'),
+        ];
+    }
+
+    #[derive(Default)]
+    struct State {
+        flags: Option<Flags<DeleteFlags>>,
+        name: String,
+    }
+
+    impl Context for State {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "delete <flags> <name>")]
+    async fn delete(
+        state: &mut State,
+        flags: Flags<DeleteFlags>,
+        name: String,
+    ) -> Result<(), Error> {
+        state.flags = Some(flags);
+        state.name = name;
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(delete);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut dispatch = |input| {
+        let mut state = State::default();
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut state, input))
+            .ok()
+            .map(|_| (state.flags.unwrap(), state.name))
+    };
+
+    let (flags, name) = dispatch("delete --force --count 3 world").unwrap();
+    assert!(flags.is_set("force"));
+    assert_eq!(flags.get::<u32>("count"), Some(Ok(3)));
+    assert_eq!(name, "world");
+
+    assert_eq!(dispatch("delete --count 3 --force world").unwrap().0, flags);
+    assert_eq!(dispatch("delete -c 3 -f world").unwrap().0, flags);
+    assert_eq!(dispatch("delete --count=3 -f world").unwrap().0, flags);
+
+    let (flags, name) = dispatch("delete world").unwrap();
+    assert!(!flags.is_set("force"));
+    assert_eq!(flags.value("count"), None);
+    assert_eq!(name, "world");
+
+    assert!(dispatch("delete --unknown world").is_none());
+    assert!(dispatch("delete --force --count").is_none());
+}
+
+#[test]
+fn flags_before_greedy_tail() {
+    struct MsgFlags;
+
+    impl FlagSpec for MsgFlags {
+        const FLAGS: &'static [Flag] = &[Flag::value("color").short('c')];
+    }
+
+    #[derive(Default)]
+    struct Chat {
+        sent: Option<(String, Option<String>, String)>,
+    }
+
+    impl Context for Chat {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "msg <player> <flags> <message>")]
+    async fn msg(
+        chat: &mut Chat,
+        player: String,
+        flags: Flags<MsgFlags>,
+        message: GreedyString,
+    ) -> Result<(), Error> {
+        let color = flags.value("color").map(str::to_owned);
+        chat.sent = Some((player, color, message.0));
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(msg);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut dispatch = |input| {
+        let mut chat = Chat::default();
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut chat, input))
+            .ok()
+            .and(chat.sent)
+    };
+
+    let sent = |color: Option<&str>, message: &str| {
+        Some((
+            String::from("alice"),
+            color.map(str::to_owned),
+            String::from(message),
+        ))
+    };
+    assert_eq!(
+        dispatch("msg alice --color red hello there"),
+        sent(Some("red"), "hello there")
+    );
+    assert_eq!(
+        dispatch("msg alice -c red hello --color blue"),
+        sent(Some("red"), "hello --color blue")
+    );
+    assert_eq!(dispatch("msg alice hello there"), sent(None, "hello there"));
+    assert_eq!(dispatch("msg alice --size 3 hello"), None);
+}
+
+#[test]
+fn take_until_delimiter() {
+    struct Colon;
+
+    impl Delimiter for Colon {
+        const DELIMITER: char = ':';
+    }
+
+    #[derive(Default)]
+    struct Mail {
+        sent: Option<(String, String)>,
+    }
+
+    impl Context for Mail {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "mail send <recipient> : <body>")]
+    async fn send(
+        mail: &mut Mail,
+        recipient: Until<Colon>,
+        body: GreedyString,
+    ) -> Result<(), Error> {
+        mail.sent = Some((recipient.into_inner(), body.0));
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(send);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut dispatch = |input| {
+        let mut mail = Mail::default();
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut mail, input))
+            .ok()
+            .and(mail.sent)
+    };
+
+    let sent = |recipient: &str, body: &str| Some((recipient.to_owned(), body.to_owned()));
+    assert_eq!(dispatch("mail send alice: hello"), sent("alice", "hello"));
+    assert_eq!(
+        dispatch("mail send alice: hello: there"),
+        sent("alice", "hello: there")
+    );
+    assert_eq!(
+        dispatch("mail send the admins: hi"),
+        sent("the admins", "hi")
+    );
+    assert_eq!(dispatch("mail send alice hello"), None);
+    assert_eq!(dispatch("mail send : hello"), None);
+    assert_eq!(dispatch("mail send alice:hello"), None);
+}
+
+#[test]
+fn switch_presence() {
+    struct Force;
+
+    impl Switch for Force {
+        const NAME: &'static str = "--force";
+    }
+
+    #[derive(Default)]
+    struct State {
+        force: Option<bool>,
+    }
+
+    impl Context for State {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "delete [force=]")]
+    async fn delete(state: &mut State, force: Present<Force>) -> Result<(), Error> {
+        state.force = Some(force.is_present());
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(delete);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut dispatch = |input| {
+        let mut state = State::default();
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut state, input))
+            .ok()
+            .and(state.force)
+    };
+
+    assert_eq!(dispatch("delete --force"), Some(true));
+    assert_eq!(dispatch("delete"), Some(false));
+    assert_eq!(dispatch("delete --other"), None);
+}
+
+#[test]
+fn wildcard_token_fallback() {
+    #[derive(Default)]
+    struct Route(Option<String>);
+
+    impl Context for Route {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "page <name>")]
+    async fn page(route: &mut Route, name: AnyToken) -> Result<(), Error> {
+        route.0 = Some(format!("page {}", name));
+        Ok(())
+    }
+
+    #[command(usage = "page home")]
+    async fn home(route: &mut Route) -> Result<(), Error> {
+        route.0 = Some("home".into());
+        Ok(())
+    }
+
+    // The wildcard is registered first, yet the literal still wins.
+    let dispatcher = CommandDispatcher::default().with(page).with(home);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut dispatch = |input| {
+        let mut route = Route::default();
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut route, input))
+            .ok()
+            .and(route.0)
+    };
+
+    assert_eq!(dispatch("page home").as_deref(), Some("home"));
+    assert_eq!(dispatch("page about").as_deref(), Some("page about"));
+    assert_eq!(dispatch("page"), None);
+}
+
+#[test]
+fn enum_aliases() {
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    enum GameMode {
+        Survival,
+        Creative,
+        Spectator,
+    }
+
+    impl EnumArgument for GameMode {
+        const VARIANTS: &'static [(&'static str, Self)] = &[
+            ("survival", GameMode::Survival),
+            ("creative", GameMode::Creative),
+            ("spectator", GameMode::Spectator),
+        ];
+        const ALIASES: &'static [(&'static str, Self)] =
+            &[("c", GameMode::Creative), ("sp", GameMode::Spectator)];
+    }
+
+    impl<C> ArgumentKind<C> for GameMode
+    where
+        C: Context,
+        C::Error: From<InvalidVariant>,
+    {
+        type Checker = EnumChecker<Self>;
+        type Parser = EnumParser<Self>;
+    }
+
+    #[derive(Default)]
+    struct State(Option<GameMode>);
+
+    impl Context for State {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "gamemode <mode>")]
+    async fn gamemode(state: &mut State, mode: GameMode) -> Result<(), Error> {
+        state.0 = Some(mode);
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(gamemode);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut dispatch = |input| {
+        let mut state = State::default();
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut state, input))
+            .ok()
+            .and(state.0)
+    };
+
+    assert_eq!(dispatch("gamemode c"), Some(GameMode::Creative));
+    assert_eq!(dispatch("gamemode creative"), Some(GameMode::Creative));
+    assert_eq!(dispatch("gamemode sp"), Some(GameMode::Spectator));
+    assert_eq!(dispatch("gamemode adventure"), None);
+
+    assert_eq!(
+        smol::block_on(dispatcher.suggestions(&State::default(), "gamemode s")),
+        vec![Completion::new("survival"), Completion::new("spectator")]
+    );
+}
+
+#[test]
+fn enum_ordinals() {
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    enum GameMode {
+        Survival,
+        Creative,
+        Spectator,
+    }
+
+    impl EnumArgument for GameMode {
+        const VARIANTS: &'static [(&'static str, Self)] = &[
+            ("survival", GameMode::Survival),
+            ("creative", GameMode::Creative),
+            ("spectator", GameMode::Spectator),
+        ];
+        const ORDINALS: bool = true;
+    }
+
+    impl<C> ArgumentKind<C> for GameMode
+    where
+        C: Context,
+        C::Error: From<InvalidVariant>,
+    {
+        type Checker = EnumChecker<Self>;
+        type Parser = EnumParser<Self>;
+    }
+
+    #[derive(Default)]
+    struct State(Option<GameMode>);
+
+    impl Context for State {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "gamemode <mode>")]
+    async fn gamemode(state: &mut State, mode: GameMode) -> Result<(), Error> {
+        state.0 = Some(mode);
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(gamemode);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut dispatch = |input| {
+        let mut state = State::default();
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut state, input))
+            .ok()
+            .and(state.0)
+    };
+
+    assert_eq!(dispatch("gamemode 1"), Some(GameMode::Creative));
+    assert_eq!(dispatch("gamemode 0"), Some(GameMode::Survival));
+    assert_eq!(dispatch("gamemode spectator"), Some(GameMode::Spectator));
+    assert_eq!(dispatch("gamemode 3"), None);
+    assert_eq!(dispatch("gamemode -1"), None);
+}
+
+#[test]
+fn recursive_expression() {
+    #[derive(Debug, PartialEq, Eq)]
+    struct Sum(i64);
+
+    /// A number, or an expression in parentheses.
+    fn term(expr: &RecursiveRef<'_, Sum>, input: &mut &str) -> Option<i64> {
+        if let Some(rest) = input.strip_prefix('(') {
+            *input = rest;
+            let Sum(value) = expr.parse(input)?;
+            *input = input.strip_prefix(')')?;
+            return Some(value);
+        }
+        let end = input
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(input.len());
+        let value = input[..end].parse().ok()?;
+        *input = &input[end..];
+        Some(value)
+    }
+
+    impl RecursiveArgument for Sum {
+        fn grammar() -> Recursive<Self> {
+            recursive(3, |expr, input| {
+                let mut sum = term(expr, input)?;
+                while let Some(rest) = input.strip_prefix(" + ") {
+                    *input = rest;
+                    sum += term(expr, input)?;
+                }
+                Some(Sum(sum))
+            })
+        }
+    }
+
+    impl<C> ArgumentKind<C> for Sum
+    where
+        C: Context,
+        C::Error: From<InvalidExpression>,
+    {
+        type Checker = RecursiveChecker<Self>;
+        type Parser = RecursiveParser<Self>;
+    }
+
+    #[derive(Default)]
+    struct State(Option<i64>);
+
+    impl Context for State {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "calc <sum>")]
+    async fn calc(state: &mut State, sum: Sum) -> Result<(), Error> {
+        state.0 = Some(sum.0);
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(calc);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut dispatch = |input| {
+        let mut state = State::default();
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut state, input))
+            .ok()
+            .and(state.0)
+    };
+
+    assert_eq!(dispatch("calc 4"), Some(4));
+    assert_eq!(dispatch("calc (1 + (2 + 3))"), Some(6));
+    assert_eq!(dispatch("calc ((1) + 2) + (3 + 4)"), Some(10));
+    // Three levels of parentheses exceed the depth limit of three.
+    assert_eq!(dispatch("calc (((1)))"), None);
+    assert_eq!(dispatch("calc (1 + 2"), None);
+    assert_eq!(dispatch("calc 1 +"), None);
+}
+
+#[test]
+fn sub_dispatcher() {
+    use std::sync::Arc;
+
+    struct Server {
+        commands: Arc<CommandDispatcher<Server>>,
+        player: String,
+        position: (i32, i32, i32),
+    }
+
+    impl Context for Server {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    struct Commands;
+
+    impl SubDispatcher<Server> for Commands {
+        fn dispatcher(server: &Server) -> Arc<CommandDispatcher<Server>> {
+            Arc::clone(&server.commands)
+        }
+    }
+
+    #[command(usage = "tp <x> <y> <z>")]
+    async fn tp(server: &mut Server, x: i32, y: i32, z: i32) -> Result<(), Error> {
+        server.position = (x, y, z);
+        Ok(())
+    }
+
+    #[command(usage = "sudo <player> <command>")]
+    async fn sudo(
+        server: &mut Server,
+        player: String,
+        command: SubCommand<Commands>,
+    ) -> Result<(), Error> {
+        assert_eq!(command.command(), "tp 1 2 3");
+        server.player = player;
+        let mut errors = Vec::new();
+        command.dispatch(&mut errors, server).await.unwrap();
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(sudo);
+    let mut server = Server {
+        commands: Arc::new(CommandDispatcher::default().with(tp)),
+        player: String::new(),
+        position: (0, 0, 0),
+    };
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    assert!(smol::block_on(dispatcher.dispatch(
+        &mut nodes,
+        &mut errors,
+        &mut server,
+        "sudo alice tp 1 2 3"
+    ))
+    .is_ok());
+    assert_eq!(server.player, "alice");
+    assert_eq!(server.position, (1, 2, 3));
+
+    assert!(smol::block_on(dispatcher.dispatch(
+        &mut nodes,
+        &mut errors,
+        &mut server,
+        "sudo alice fly"
+    ))
+    .is_err());
+    assert_eq!(
+        smol::block_on(dispatcher.suggestions(&server, "sudo alice t")),
+        vec![Completion::new("tp")]
+    );
+}
+
+#[test]
+fn either_arguments() {
+    #[derive(Default)]
+    struct State(Option<Either<i32, String>>);
+
+    impl Context for State {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "kill <target>")]
+    async fn kill(state: &mut State, target: Either<i32, String>) -> Result<(), Error> {
+        state.0 = Some(target);
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(kill);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut dispatch = |input| {
+        let mut state = State::default();
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut state, input))
+            .ok()
+            .and(state.0)
+    };
+
+    let id = dispatch("kill 42").unwrap();
+    assert_eq!(id, Either::A(42));
+    assert_eq!(id.as_ref().left(), Some(&42));
+    assert_eq!(id.clone().right(), None);
+    assert_eq!(id.to_string(), "42");
+
+    let name = dispatch("kill alice").unwrap();
+    assert_eq!(name, Either::B("alice".to_owned()));
+    assert_eq!(name.as_ref().map_b(String::len), Either::B(5));
+    assert_eq!(
+        name.clone().map_a(|id| id * 2).right().as_deref(),
+        Some("alice")
+    );
+    assert_eq!(name.to_string(), "alice");
+
+    let result: Result<i32, String> = id.into();
+    assert_eq!(result, Ok(42));
+    assert_eq!(Either::from(Err::<i32, _>("bob")), Either::B("bob"));
+    assert_eq!(Either::<u8, u8>::B(3).into_inner(), 3);
+}
+
+#[test]
+fn greedy_string() {
+    #[derive(Default)]
+    struct Chat(String);
+
+    impl Context for Chat {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "say <message>")]
+    async fn say(chat: &mut Chat, message: GreedyString) -> Result<(), Error> {
+        chat.0 = message.0;
+        Ok(())
+    }
+
+    #[command(usage = "whisper <message> <times>")]
+    async fn whisper(chat: &mut Chat, message: GreedyString, times: u32) -> Result<(), Error> {
+        chat.0 = message.repeat(times as usize);
+        Ok(())
+    }
+
+    let mut dispatcher = CommandDispatcher::default().with(say);
+    assert_eq!(
+        dispatcher.register(whisper),
+        Err(RegisterError::UnreachableAfterGreedy)
+    );
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut chat = Chat::default();
+    assert!(smol::block_on(dispatcher.dispatch(
+        &mut nodes,
+        &mut errors,
+        &mut chat,
+        "say hello  world"
+    ))
+    .is_ok());
+    assert_eq!(chat.0, "hello  world");
+}
+
+#[test]
+fn multiline_string() {
+    #[derive(Default)]
+    struct Mail(Option<String>);
+
+    impl Context for Mail {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "mail <body>")]
+    async fn mail(outbox: &mut Mail, body: MultilineString) -> Result<(), Error> {
+        outbox.0 = Some(body.0);
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(mail);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut dispatch = |input| {
+        let mut outbox = Mail::default();
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut outbox, input))
+            .ok()
+            .and(outbox.0)
+    };
+
+    assert_eq!(
+        dispatch(r"mail line1\nline2"),
+        Some("line1\nline2".to_owned())
+    );
+    assert_eq!(
+        dispatch(r"mail C:\\new \t end\"),
+        Some(r"C:\new \t end\".to_owned())
+    );
+}
+
+#[test]
+fn rest_tokens() {
+    #[derive(Default)]
+    struct Shell(Vec<String>);
+
+    impl Context for Shell {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "exec <args>")]
+    async fn exec(shell: &mut Shell, args: RestTokens) -> Result<(), Error> {
+        shell.0 = args.0;
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(exec);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut dispatch = |input| {
+        let mut shell = Shell::default();
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut shell, input))
+            .ok()
+            .map(|_| shell.0)
+    };
+
+    assert_eq!(
+        dispatch(r#"exec a b "c d""#),
+        Some(vec!["a".to_owned(), "b".to_owned(), "c d".to_owned()])
+    );
+    assert_eq!(dispatch(r#"exec a "b"#), None);
+}
+
+#[test]
+fn sandboxed_path() {
+    struct CrateRoot;
+
+    impl PathRoot for CrateRoot {
+        fn root() -> std::path::PathBuf {
+            env!("CARGO_MANIFEST_DIR").into()
+        }
+    }
+
+    #[derive(Default)]
+    struct State {
+        path: Option<std::path::PathBuf>,
+    }
+
+    impl Context for State {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "load <path>")]
+    async fn load(state: &mut State, path: SandboxedPath<CrateRoot>) -> Result<(), Error> {
+        state.path = Some(path.resolve());
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(load);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut state = State::default();
+
+    assert!(smol::block_on(dispatcher.dispatch(
+        &mut nodes,
+        &mut errors,
+        &mut state,
+        "load src/lib.rs"
+    ))
+    .is_ok());
+    assert_eq!(
+        state.path,
+        Some(std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src/lib.rs"))
+    );
+
+    for input in &[
+        "load ../../etc/passwd",
+        "load src/../../x",
+        "load /etc/passwd",
+    ] {
+        let mut state = State::default();
+        assert!(
+            smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut state, input))
+                .is_err()
+        );
+        assert_eq!(state.path, None);
+    }
+
+    let suggestions: Vec<String> = smol::block_on(dispatcher.suggestions(&state, "load src/pa"))
+        .into_iter()
+        .map(|completion| completion.text)
+        .collect();
+    assert_eq!(suggestions, vec!["src/parser.rs"]);
+    assert!(smol::block_on(dispatcher.suggestions(&state, "load ../")).is_empty());
+}
+
+#[test]
+fn integer_ranges() {
+    use std::ops::RangeInclusive;
+
+    #[derive(Default)]
+    struct State {
+        range: Option<RangeInclusive<i64>>,
+        open: Option<OpenRange<u8>>,
+    }
+
+    impl Context for State {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "fill <range>")]
+    async fn fill(state: &mut State, range: RangeInclusive<i64>) -> Result<(), Error> {
+        state.range = Some(range);
+        Ok(())
+    }
+
+    #[command(usage = "fill open <range>")]
+    async fn fill_open(state: &mut State, range: OpenRange<u8>) -> Result<(), Error> {
+        state.open = Some(range);
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(fill).with(fill_open);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut dispatch = |input| {
+        let mut state = State::default();
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut state, input))
+            .ok()
+            .map(|_| state)
+    };
+
+    assert_eq!(dispatch("fill 1..10").unwrap().range, Some(1..=9));
+    assert_eq!(dispatch("fill 1..=10").unwrap().range, Some(1..=10));
+    assert_eq!(dispatch("fill -5..=-1").unwrap().range, Some(-5..=-1));
+    for input in &[
+        "fill 1..",
+        "fill ..10",
+        "fill 10..1",
+        "fill 1..1",
+        "fill 1-10",
+        "fill a..b",
+    ] {
+        assert!(dispatch(input).is_none());
+    }
+
+    assert_eq!(
+        dispatch("fill open 1..").unwrap().open,
+        Some(OpenRange(1..=255))
+    );
+    assert_eq!(
+        dispatch("fill open ..10").unwrap().open,
+        Some(OpenRange(0..=9))
+    );
+    assert_eq!(
+        dispatch("fill open ..").unwrap().open,
+        Some(OpenRange(0..=255))
+    );
+    assert!(dispatch("fill open 1..300").is_none());
+}
+
+#[test]
+fn dependent_arguments() {
+    #[derive(Debug, PartialEq, Eq)]
+    enum ResourceKind {
+        Block,
+        Item,
+    }
+
+    impl std::str::FromStr for ResourceKind {
+        type Err = ();
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "block" => Ok(ResourceKind::Block),
+                "item" => Ok(ResourceKind::Item),
+                _ => Err(()),
+            }
+        }
+    }
+
+    struct Resource;
+
+    impl DependentArgument for Resource {
+        type First = ResourceKind;
+        type Second = String;
+
+        fn and_then(kind: &ResourceKind, id: &str) -> Option<String> {
+            let ids: &[&str] = match kind {
+                ResourceKind::Block => &["stone", "dirt"],
+                ResourceKind::Item => &["sword", "apple"],
+            };
+            ids.iter()
+                .find(|valid| **valid == id)
+                .map(|id| (*id).to_owned())
+        }
+    }
+
+    #[derive(Default)]
+    struct State {
+        given: Option<(ResourceKind, String)>,
+    }
+
+    impl Context for State {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "give <resource>")]
+    async fn give(state: &mut State, resource: Dependent<Resource>) -> Result<(), Error> {
+        state.given = Some((resource.first, resource.second));
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(give);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut dispatch = |input| {
+        let mut state = State::default();
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut state, input))
+            .ok()
+            .and(state.given)
+    };
+
+    assert_eq!(
+        dispatch("give block stone"),
+        Some((ResourceKind::Block, "stone".to_owned()))
+    );
+    assert_eq!(
+        dispatch("give item apple"),
+        Some((ResourceKind::Item, "apple".to_owned()))
+    );
+    for input in &[
+        "give block apple",
+        "give item stone",
+        "give fluid water",
+        "give block",
+    ] {
+        assert_eq!(dispatch(input), None);
+    }
+}
+
+#[test]
+fn peek_without_consuming() {
+    #[derive(Default)]
+    struct State {
+        peeked: Option<i32>,
+        word: String,
+    }
+
+    impl Context for State {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "echo <number> <word>")]
+    async fn echo(state: &mut State, number: Peek<i32>, word: String) -> Result<(), Error> {
+        state.peeked = Some(number.into_inner());
+        state.word = word;
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(echo);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut state = State::default();
+
+    assert!(
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut state, "echo 5")).is_ok()
+    );
+    assert_eq!(state.peeked, Some(5));
+    assert_eq!(state.word, "5");
+
+    let mut state = State::default();
+    assert!(
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut state, "echo five"))
+            .is_err()
+    );
+    assert!(
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut state, "echo 5 5"))
+            .is_err()
+    );
+    assert_eq!(state.peeked, None);
+}
+
+#[test]
+fn coordinates_with_optional_dimension() {
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    enum Dimension {
+        Overworld,
+        Nether,
+        End,
+    }
+
+    impl std::str::FromStr for Dimension {
+        type Err = InvalidDimension;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "overworld" => Ok(Dimension::Overworld),
+                "nether" => Ok(Dimension::Nether),
+                "end" => Ok(Dimension::End),
+                _ => Err(InvalidDimension),
+            }
+        }
+    }
+
+    #[derive(Debug, Error)]
+    #[error("invalid dimension")]
+    struct InvalidDimension;
+
+    impl From<InvalidDimension> for Error {
+        fn from(_: InvalidDimension) -> Self {
+            Error::ParsingInt
+        }
+    }
+
+    #[derive(Default)]
+    struct State {
+        position: Option<(Coordinates<i32>, Option<Dimension>)>,
+    }
+
+    impl Context for State {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    impl ArgumentKind<State> for Dimension {
+        type Checker = FromStrChecker<Self>;
+        type Parser = FromStrParser<Self>;
+    }
+
+    #[command(usage = "tp <position> [dimension]")]
+    async fn tp(
+        state: &mut State,
+        position: Coordinates<i32>,
+        dimension: Option<Dimension>,
+    ) -> Result<(), Error> {
+        state.position = Some((position, dimension));
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(tp);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut dispatch = |input| {
+        let mut state = State::default();
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut state, input))
+            .ok()
+            .and(state.position)
+    };
+
+    let position = Coordinates { x: 1, y: 2, z: 3 };
+    assert_eq!(dispatch("tp 1 2 3"), Some((position, None)));
+    assert_eq!(
+        dispatch("tp 1 2 3 nether"),
+        Some((position, Some(Dimension::Nether)))
+    );
+    assert_eq!(dispatch("tp 1,2,3"), Some((position, None)));
+    assert_eq!(
+        dispatch("tp 1,2,3 end"),
+        Some((position, Some(Dimension::End)))
+    );
+    for input in &[
+        "tp 1 2",
+        "tp 1 2 3 mars",
+        "tp 1,2 3",
+        "tp 1,2,3,4",
+        "tp 1 2 3 nether 4",
+    ] {
+        assert_eq!(dispatch(input), None);
+    }
+}
+
+#[test]
+fn location_with_rotation() {
+    #[derive(Debug, PartialEq)]
+    struct Location {
+        pos: [Coord; 3],
+        rot: Option<(f32, f32)>,
+    }
+
+    #[derive(Default)]
+    struct State {
+        location: Option<Location>,
+    }
+
+    impl Context for State {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "tp <pos> [yaw] [pitch]")]
+    async fn tp(
+        state: &mut State,
+        pos: [Coord; 3],
+        yaw: Option<f32>,
+        pitch: Option<f32>,
+    ) -> Result<(), Error> {
+        state.location = Some(Location {
+            pos,
+            rot: yaw.zip(pitch),
+        });
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(tp);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut dispatch = |input| {
+        let mut state = State::default();
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut state, input))
+            .ok()
+            .and(state.location)
+    };
+
+    let pos = [
+        Coord::Absolute(1.0),
+        Coord::Absolute(2.0),
+        Coord::Absolute(3.0),
+    ];
+    assert_eq!(dispatch("tp 1 2 3"), Some(Location { pos, rot: None }));
+    assert_eq!(
+        dispatch("tp 1 2 3 90 0"),
+        Some(Location {
+            pos,
+            rot: Some((90.0, 0.0)),
+        })
+    );
+    assert_eq!(
+        dispatch("tp ~ ~-1.5 3"),
+        Some(Location {
+            pos: [
+                Coord::Relative(0.0),
+                Coord::Relative(-1.5),
+                Coord::Absolute(3.0)
+            ],
+            rot: None,
+        })
+    );
+    assert_eq!(dispatch("tp 1 2"), None);
+    assert_eq!(dispatch("tp 1 2 ~x"), None);
+    assert_eq!(dispatch("tp 1 2 3 north"), None);
+    assert_eq!(dispatch("tp NaN inf ~inf"), None);
+    assert_eq!(dispatch("tp 1 2 NaN"), None);
+    assert_eq!(dispatch("tp 1 2 -infinity"), None);
+    assert_eq!(dispatch("tp 1 2 ~NaN"), None);
+    assert_eq!(dispatch("tp 1 2 ~inf"), None);
+}
+
+#[test]
+fn quoted_or_bare_strings() {
+    #[derive(Default)]
+    struct State {
+        name: Option<StringArg>,
+        count: Option<i32>,
+    }
+
+    impl Context for State {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "rename <name> <count>")]
+    async fn rename(state: &mut State, name: StringArg, count: i32) -> Result<(), Error> {
+        state.name = Some(name);
+        state.count = Some(count);
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(rename);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut dispatch = |input| {
+        let mut state = State::default();
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut state, input))
+            .ok()
+            .and(state.name.zip(state.count))
+    };
+
+    let quoted = StringArg {
+        value: "hello world".into(),
+        was_quoted: true,
+    };
+    assert_eq!(
+        dispatch(r#"rename "hello world" 1"#),
+        Some((quoted.clone(), 1))
+    );
+    assert_eq!(quoted.to_string(), r#""hello world""#);
+
+    let bare = StringArg {
+        value: "hello".into(),
+        was_quoted: false,
+    };
+    assert_eq!(dispatch("rename hello 2"), Some((bare.clone(), 2)));
+    assert_eq!(bare.to_string(), "hello");
+
+    let escaped = StringArg {
+        value: r#"say "hi" \ bye"#.into(),
+        was_quoted: true,
+    };
+    assert_eq!(
+        dispatch(r#"rename "say \"hi\" \\ bye" 3"#),
+        Some((escaped.clone(), 3))
+    );
+    assert_eq!(escaped.to_string(), r#""say \"hi\" \\ bye""#);
+
+    assert_eq!(
+        dispatch(r#"rename "" 4"#),
+        Some((
+            StringArg {
+                value: String::new(),
+                was_quoted: true
+            },
+            4
+        ))
+    );
+
+    for input in &[
+        r#"rename "hello world 1"#,
+        r#"rename "hello"world 1"#,
+        r#"rename hel"lo 1"#,
+        "rename hello",
+    ] {
+        assert_eq!(dispatch(input), None);
+    }
+}
+
+#[test]
+fn quoted_string_before_argument() {
+    #[derive(Default)]
+    struct State {
+        sent: Option<(Selector, String, String)>,
+    }
+
+    impl Context for State {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "tellraw <targets> <message> <extra>")]
+    async fn tellraw(
+        state: &mut State,
+        targets: Selector,
+        message: StringArg,
+        extra: String,
+    ) -> Result<(), Error> {
+        state.sent = Some((targets, message.value, extra));
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(tellraw);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut dispatch = |input| {
+        let mut state = State::default();
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut state, input))
+            .ok()
+            .and(state.sent)
+            .map(|(_, message, extra)| (message, extra))
+    };
+
+    assert_eq!(
+        dispatch(r#"tellraw @a "{\"text\": \"hi there\"}" extra"#),
+        Some((r#"{"text": "hi there"}"#.to_owned(), "extra".to_owned()))
+    );
+    assert_eq!(
+        dispatch(r#"tellraw @a "hello" world"#),
+        Some(("hello".to_owned(), "world".to_owned()))
+    );
+    assert_eq!(dispatch(r#"tellraw @a "hello world""#), None);
+    assert_eq!(dispatch(r#"tellraw @a "hello"world extra"#), None);
+}
+
+#[test]
+fn borrowed_strings() {
+    use std::borrow::Cow;
+
+    let mut input = "hello world";
+    assert!(matches!(
+        StringArg::borrowed(&mut input),
+        Some(Cow::Borrowed("hello"))
+    ));
+    assert_eq!(input, "world");
+
+    let mut input = r#""hello world" 1"#;
+    assert!(matches!(
+        StringArg::borrowed(&mut input),
+        Some(Cow::Borrowed("hello world"))
+    ));
+    assert_eq!(input, "1");
+
+    let mut input = r#""say \"hi\"""#;
+    match StringArg::borrowed(&mut input) {
+        Some(Cow::Owned(value)) => assert_eq!(value, r#"say "hi""#),
+        other => panic!("expected an owned string, got {:?}", other),
+    }
+    assert_eq!(input, "");
+
+    assert_eq!(StringArg::borrowed(&mut r#"hel"lo"#), None);
+}
+
+#[test]
+fn async_checker_lookup() {
+    use lieutenant::{ArgumentChecker, ArgumentParser, ParserUtil};
+    use std::any::Any;
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Stands in for a database, answering from another thread.
+    #[derive(Default)]
+    struct Store {
+        players: Vec<&'static str>,
+        lookups: AtomicUsize,
+    }
+
+    impl Store {
+        async fn exists(&self, name: &str) -> bool {
+            let (sender, receiver) = futures::channel::oneshot::channel();
+            let exists = self.players.contains(&name);
+            std::thread::spawn(move || sender.send(exists));
+            self.lookups.fetch_add(1, Ordering::SeqCst);
+            receiver.await.unwrap()
+        }
+    }
+
+    #[derive(Default)]
+    struct Server {
+        store: Store,
+        banned: Option<String>,
+    }
+
+    impl Context for Server {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    struct Player(String);
+
+    #[derive(Clone)]
+    struct PlayerChecker;
+
+    impl ArgumentChecker<Server> for PlayerChecker {
+        fn satisfies<'a, 'b>(
+            &'a self,
+            ctx: &'a Server,
+            input: &'a mut &'b str,
+        ) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>> {
+            Box::pin(async move {
+                let head = input.advance_until(" ");
+                ctx.store.exists(head).await
+            })
+        }
+
+        fn equals(&self, other: &dyn Any) -> bool {
+            other.downcast_ref::<Self>().is_some()
+        }
+
+        fn default() -> Self {
+            PlayerChecker
+        }
+
+        fn box_clone(&self) -> Box<dyn ArgumentChecker<Server>> {
+            Box::new(self.clone())
+        }
+    }
+
+    struct PlayerParser;
+
+    impl ArgumentParser<Server> for PlayerParser {
+        type Output = Player;
+
+        fn parse<'a, 'b>(
+            &'a self,
+            _ctx: &'a mut Server,
+            input: &'a mut &'b str,
+        ) -> Pin<Box<dyn Future<Output = Result<Player, Error>> + Send + Sync + 'a>> {
+            Box::pin(async move { Ok(Player(input.advance_until(" ").to_owned())) })
+        }
+
+        fn default() -> Self {
+            PlayerParser
+        }
+    }
+
+    impl ArgumentKind<Server> for Player {
+        type Checker = PlayerChecker;
+        type Parser = PlayerParser;
+    }
+
+    #[command(usage = "ban <player>")]
+    async fn ban(server: &mut Server, player: Player) -> Result<(), Error> {
+        server.banned = Some(player.0);
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(ban);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut server = Server {
+        store: Store {
+            players: vec!["alice"],
+            ..Store::default()
+        },
+        ..Server::default()
+    };
+
+    assert!(smol::block_on(dispatcher.dispatch(
+        &mut nodes,
+        &mut errors,
+        &mut server,
+        "ban mallory"
+    ))
+    .is_err());
+    assert_eq!(server.banned, None);
+    assert_eq!(server.store.lookups.load(Ordering::SeqCst), 1);
+
+    assert!(
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut server, "ban alice"))
+            .is_ok()
+    );
+    assert_eq!(server.banned.as_deref(), Some("alice"));
+    assert_eq!(server.store.lookups.load(Ordering::SeqCst), 2);
+
+    assert!(smol::block_on(dispatcher.check(&server, "ban alice")).is_ok());
+    assert!(smol::block_on(dispatcher.check(&server, "ban mallory")).is_err());
+}
+
+#[test]
+fn fixed_size_arrays() {
+    #[derive(Default)]
+    struct State {
+        block: Option<([i64; 3], String)>,
+    }
+
+    impl Context for State {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "setblock <position> <block>")]
+    async fn setblock(state: &mut State, position: [i64; 3], block: String) -> Result<(), Error> {
+        state.block = Some((position, block));
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(setblock);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut dispatch = |input| {
+        let mut state = State::default();
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut state, input))
+            .ok()
+            .and(state.block)
+    };
+
+    assert_eq!(
+        dispatch("setblock 1 -2 3 stone"),
+        Some(([1, -2, 3], "stone".to_owned()))
+    );
+    for input in &[
+        "setblock 1 2 stone",
+        "setblock 1 2",
+        "setblock 1 2 3",
+        "setblock 1 two 3 stone",
+    ] {
+        assert_eq!(dispatch(input), None);
+    }
+}
+
+#[test]
+fn commands_from_erased_parsers() {
+    use lieutenant::{Argument, ArgumentChecker, BoxedParser, CommandSpec, Executable, ParserUtil};
+    use std::any::Any;
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::Arc;
+
+    #[derive(Default)]
+    struct State {
+        parsers: Arc<Vec<BoxedParser<State>>>,
+        values: Vec<Box<dyn Any + Send + Sync>>,
+    }
+
+    impl Context for State {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    fn exec<'a>(
+        state: &'a mut State,
+        command: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'a>> {
+        Box::pin(async move {
+            let parsers = Arc::clone(&state.parsers);
+            let mut input = command;
+            input.advance_until(" ");
+            for parser in parsers.iter() {
+                let value = parser.parse(state, &mut input).await?;
+                state.values.push(value);
+            }
+            Ok(())
+        })
+    }
+
+    fn argument<K>(name: &'static str) -> (Argument<State>, BoxedParser<State>)
+    where
+        K: ArgumentKind<State> + 'static,
+    {
+        let argument = Argument::Parser {
+            name: name.into(),
+            checker: Box::new(<K::Checker as ArgumentChecker<State>>::default()),
+            priority: 0,
+            optional: false,
+            error: None,
+        };
+        (argument, BoxedParser::of::<K>())
+    }
+
+    // Argument types as they might be read from a configuration file.
+    let description = ["int", "word", "int"];
+    let (arguments, parsers): (Vec<_>, Vec<_>) = description
+        .iter()
+        .enumerate()
+        .map(|(index, kind)| {
+            let name = ["a", "b", "c"][index];
+            match *kind {
+                "int" => argument::<i32>(name),
+                "word" => argument::<String>(name),
+                _ => unreachable!(),
+            }
+        })
+        .unzip();
+
+    let spec = CommandSpec {
+        arguments: std::iter::once(Argument::Literal {
+            value: "custom".into(),
+        })
+        .chain(arguments)
+        .collect(),
+        description: None,
+        permissions: Vec::new(),
+        deprecated: None,
+        named: false,
+        exec: Executable::Fn(exec),
+        extract: None,
+    };
+    let dispatcher = CommandDispatcher::default().with(spec);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut state = State {
+        parsers: Arc::new(parsers),
+        ..State::default()
+    };
+
+    assert!(smol::block_on(dispatcher.dispatch(
+        &mut nodes,
+        &mut errors,
+        &mut state,
+        "custom 1 two 3"
+    ))
+    .is_ok());
+    assert_eq!(state.values.len(), 3);
+    assert_eq!(state.values[0].downcast_ref::<i32>(), Some(&1));
+    assert_eq!(
+        state.values[1].downcast_ref::<String>().map(String::as_str),
+        Some("two")
+    );
+    assert_eq!(state.values[2].downcast_ref::<i32>(), Some(&3));
+
+    state.values.clear();
+    assert!(smol::block_on(dispatcher.dispatch(
+        &mut nodes,
+        &mut errors,
+        &mut state,
+        "custom one two 3"
+    ))
+    .is_err());
+    assert!(state.values.is_empty());
+}
+
+#[test]
+fn byte_sizes() {
+    #[derive(Default)]
+    struct State {
+        limit: Option<u64>,
+    }
+
+    impl Context for State {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "setlimit <size>")]
+    async fn setlimit(state: &mut State, size: ByteSize) -> Result<(), Error> {
+        state.limit = Some(size.bytes());
+        Ok(())
+    }
+
+    #[command(usage = "setlimit decimal <size>")]
+    async fn setlimit_decimal(state: &mut State, size: ByteSize<true>) -> Result<(), Error> {
+        state.limit = Some(size.bytes());
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default()
+        .with(setlimit)
+        .with(setlimit_decimal);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut dispatch = |input| {
+        let mut state = State::default();
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut state, input))
+            .ok()
+            .and(state.limit)
+    };
+
+    assert_eq!(dispatch("setlimit 512K"), Some(512 * 1024));
+    assert_eq!(dispatch("setlimit 10MB"), Some(10 * 1024 * 1024));
+    assert_eq!(dispatch("setlimit 2gb"), Some(2 * 1024 * 1024 * 1024));
+    assert_eq!(dispatch("setlimit 100"), Some(100));
+    assert_eq!(dispatch("setlimit decimal 10MB"), Some(10_000_000));
+    assert_eq!(dispatch("setlimit decimal 512K"), Some(512_000));
+
+    for input in &[
+        "setlimit 10XB",
+        "setlimit MB",
+        "setlimit -1K",
+        "setlimit 1.5M",
+        "setlimit 99999999999999999G",
+    ] {
+        assert_eq!(dispatch(input), None);
+    }
+}
+
+#[test]
+fn resource_locations() {
+    #[derive(Default)]
+    struct State {
+        block: Option<ResourceLocation>,
+    }
+
+    impl Context for State {
+        type Error = Error;
+        type Ok = ();
+    }
+
+    #[command(usage = "setblock <block>")]
+    async fn setblock(state: &mut State, block: ResourceLocation) -> Result<(), Error> {
+        state.block = Some(block);
+        Ok(())
+    }
+
+    let dispatcher = CommandDispatcher::default().with(setblock);
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut dispatch = |input| {
+        let mut state = State::default();
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut state, input))
+            .ok()
+            .and(state.block)
+            .map(|block| block.to_string())
+    };
+
+    assert_eq!(
+        dispatch("setblock minecraft:stone").as_deref(),
+        Some("minecraft:stone")
+    );
+    assert_eq!(
+        dispatch("setblock stone").as_deref(),
+        Some("minecraft:stone")
+    );
+    assert_eq!(
+        dispatch("setblock my_mod:ores/deep-copper.v2").as_deref(),
+        Some("my_mod:ores/deep-copper.v2")
+    );
+
+    for input in &[
+        "setblock Minecraft:Stone",
+        "setblock my/mod:stone",
+        "setblock :stone",
+        "setblock minecraft:",
+        "setblock a:b:c",
+    ] {
+        assert_eq!(dispatch(input), None);
+    }
+}
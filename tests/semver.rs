@@ -0,0 +1,103 @@
+#![cfg(feature = "semver")]
+
+use lieutenant::parsers::InvalidVersion;
+use lieutenant::{command, CommandDispatcher, Context};
+use semver::{Version, VersionReq};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+enum Error {
+    #[error(transparent)]
+    Version(#[from] InvalidVersion),
+}
+
+#[derive(Default)]
+struct State {
+    installed: Option<Version>,
+    required: Option<VersionReq>,
+}
+
+impl Context for State {
+    type Error = Error;
+    type Ok = ();
+}
+
+#[command(usage = "install <version>")]
+async fn install(state: &mut State, version: Version) -> Result<(), Error> {
+    state.installed = Some(version);
+    Ok(())
+}
+
+#[command(usage = "require <requirement>")]
+async fn require(state: &mut State, requirement: VersionReq) -> Result<(), Error> {
+    state.required = Some(requirement);
+    Ok(())
+}
+
+#[test]
+fn exact_version() {
+    let dispatcher = CommandDispatcher::default().with(install);
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut state = State::default();
+
+    assert!(smol::block_on(dispatcher.dispatch(
+        &mut nodes,
+        &mut errors,
+        &mut state,
+        "install 1.2.3"
+    ))
+    .is_ok());
+    assert_eq!(state.installed, Some(Version::new(1, 2, 3)));
+}
+
+#[test]
+fn version_requirement() {
+    let dispatcher = CommandDispatcher::default().with(require);
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut state = State::default();
+
+    assert!(smol::block_on(dispatcher.dispatch(
+        &mut nodes,
+        &mut errors,
+        &mut state,
+        ">=1.0, <2.0"
+    ))
+    .is_err());
+    assert!(smol::block_on(dispatcher.dispatch(
+        &mut nodes,
+        &mut errors,
+        &mut state,
+        "require >=1.0, <2.0"
+    ))
+    .is_ok());
+    let required = state.required.unwrap();
+    assert!(required.matches(&Version::new(1, 4, 0)));
+    assert!(!required.matches(&Version::new(2, 0, 0)));
+}
+
+#[test]
+fn malformed_version() {
+    let dispatcher = CommandDispatcher::default().with(install).with(require);
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut state = State::default();
+
+    assert!(smol::block_on(dispatcher.dispatch(
+        &mut nodes,
+        &mut errors,
+        &mut state,
+        "install abc"
+    ))
+    .is_err());
+    assert!(smol::block_on(dispatcher.dispatch(
+        &mut nodes,
+        &mut errors,
+        &mut state,
+        "require abc"
+    ))
+    .is_err());
+    assert!(state.installed.is_none());
+    assert!(state.required.is_none());
+}
@@ -0,0 +1,31 @@
+use lieutenant::{command_spec, CommandDispatcher, Context};
+
+#[derive(Default)]
+struct State {
+    position: (i64, i64, i64),
+}
+
+impl Context for State {
+    type Error = std::num::ParseIntError;
+    type Ok = ();
+}
+
+fn main() {
+    let dispatcher = CommandDispatcher::default().with(command_spec!(
+        "tp <x:i64> <y:i64> <z:i64>",
+        |ctx: &mut State, x, y, z| {
+            ctx.position = (x, y, z);
+            Ok(())
+        }
+    ));
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut state = State::default();
+
+    assert!(
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut state, "tp 1 -2 3"))
+            .is_ok()
+    );
+    assert_eq!(state.position, (1, -2, 3));
+}
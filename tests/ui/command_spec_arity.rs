@@ -0,0 +1,14 @@
+use lieutenant::{command_spec, CommandSpec, Context};
+
+struct State;
+
+impl Context for State {
+    type Error = std::num::ParseIntError;
+    type Ok = ();
+}
+
+fn main() {
+    let _: CommandSpec<State> = command_spec!("tp <x:i64> <y:i64> <z:i64>", |_ctx: &mut State, _x, _y| {
+        Ok(())
+    });
+}
@@ -0,0 +1,58 @@
+use lieutenant::{Command, CommandDispatcher, Context};
+
+#[derive(Default)]
+struct State {
+    position: (i64, i64, i64),
+    speed: Option<u32>,
+}
+
+impl Context for State {
+    type Error = std::num::ParseIntError;
+    type Ok = ();
+}
+
+#[derive(Command)]
+#[command(name = "tp", context = "State", description = "Teleports the player")]
+struct Tp {
+    x: i64,
+    y: i64,
+    z: i64,
+    speed: Option<u32>,
+}
+
+impl Tp {
+    async fn execute(&self, ctx: &mut State) -> Result<(), std::num::ParseIntError> {
+        ctx.position = (self.x, self.y, self.z);
+        ctx.speed = self.speed;
+        Ok(())
+    }
+}
+
+fn main() {
+    let dispatcher = CommandDispatcher::default().with(Tp::command());
+    assert_eq!(
+        dispatcher.commands().next().unwrap().description.as_deref(),
+        Some("Teleports the player")
+    );
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+    let mut state = State::default();
+
+    assert!(
+        smol::block_on(dispatcher.dispatch(&mut nodes, &mut errors, &mut state, "tp 1 -2 3"))
+            .is_ok()
+    );
+    assert_eq!(state.position, (1, -2, 3));
+    assert_eq!(state.speed, None);
+
+    assert!(smol::block_on(dispatcher.dispatch(
+        &mut nodes,
+        &mut errors,
+        &mut state,
+        "tp 4 5 6 10"
+    ))
+    .is_ok());
+    assert_eq!(state.position, (4, 5, 6));
+    assert_eq!(state.speed, Some(10));
+}
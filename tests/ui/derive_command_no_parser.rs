@@ -0,0 +1,24 @@
+use lieutenant::{Command, Context};
+
+struct State;
+
+impl Context for State {
+    type Error = std::num::ParseIntError;
+    type Ok = ();
+}
+
+struct Player;
+
+#[derive(Command)]
+#[command(name = "kick", context = "State")]
+struct Kick {
+    player: Player,
+}
+
+impl Kick {
+    async fn execute(&self, _ctx: &mut State) -> Result<(), std::num::ParseIntError> {
+        Ok(())
+    }
+}
+
+fn main() {}